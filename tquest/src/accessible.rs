@@ -0,0 +1,240 @@
+//! Texts of the accessibility mode
+//!
+//! The mode is meant for screen readers: questions are announced with their
+//! number and type in words, hints are short and the full hint is given on
+//! request, options are read one per line with numbers starting at 1 and
+//! validation errors are announced before the question is repeated. Nothing
+//! depends on colours or glyphs.
+//!
+use crate::questionaire::{EntryType, OptionEntry, QuestionAnswerInput, QuestionEntry};
+use crate::ui::{get_valid_input_hint, validate_input};
+
+use std::time::Duration;
+
+
+/// Input that gives the full hint of a question
+pub(crate) const HINT_COMMAND: &str = "hint";
+
+pub(crate) const INFO_HINT: &str = "Press Enter to continue.";
+
+/// What a view does with an input line
+pub(crate) enum Reply {
+    Answer(QuestionAnswerInput),
+    /// lines to say, before the prompt is repeated
+    Say(Vec<String>),
+}
+
+fn type_words(entry_type: &EntryType) -> String {
+    match entry_type {
+        EntryType::String(_) => "text question".to_string(),
+        EntryType::Int(_) => "whole number question".to_string(),
+        EntryType::Float(_) => "number question".to_string(),
+        EntryType::Bool(_) => "yes or no question".to_string(),
+        EntryType::Option(o) => format!("choice of {} options", o.options.len()),
+        _ => "information".to_string(),
+    }
+}
+
+fn announcement(kind: &str, text: &str, question_count: usize, current: usize) -> String {
+    if current != 0 {
+        format!("Question {} of {}, {}: {}", current, question_count, kind, text)
+    } else {
+        let mut k = kind.to_string();
+        if let Some(first) = k.get_mut(0..1) {
+            first.make_ascii_uppercase();
+        }
+        format!("{}: {}", k, text)
+    }
+}
+
+/// e.g. `Question 2 of 5, text question: What's your name?`
+pub(crate) fn question_announcement(question_entry: &QuestionEntry, question_count: usize) -> String {
+    announcement(&type_words(&question_entry.entry_type), &question_entry.query_text, question_count, question_entry.pos)
+}
+
+pub(crate) fn info_announcement(text: &str) -> String {
+    format!("Information: {}", text)
+}
+
+pub(crate) fn timeout_hint(timeout: Duration) -> String {
+    format!("You have {} seconds to answer, then the default is taken.", timeout.as_secs())
+}
+
+pub(crate) fn proceed_announcement(text: &str, question_count: usize, current: usize) -> String {
+    announcement("yes or no question", text, question_count, current)
+}
+
+fn help_words(has_help: bool) -> String {
+    if has_help {
+        format!("Type {} for the full hint or help for help.", HINT_COMMAND)
+    } else {
+        format!("Type {} for the full hint.", HINT_COMMAND)
+    }
+}
+
+fn option_lines(o: &OptionEntry) -> Vec<String> {
+    o.options.iter().enumerate()
+        .map(|(i, v)| format!("Option {} of {}: {}", i + 1, o.options.len(), v))
+        .collect()
+}
+
+fn default_text(entry_type: &EntryType) -> Option<String> {
+    match entry_type {
+        EntryType::String(s) => s.default_value.clone(),
+        EntryType::Int(i) => i.default_value.map(|v| v.to_string()),
+        EntryType::Float(f) => f.default_value.map(|v| v.to_string()),
+        EntryType::Bool(b) => b.default_value.map(|v| if v { "yes" } else { "no" }.to_string()),
+        EntryType::Option(o) => o.default_value.and_then(|d| o.options.get(d as usize).cloned()),
+        _ => None,
+    }
+}
+
+/// Short hint, with the default value
+pub(crate) fn short_hint(question_entry: &QuestionEntry) -> String {
+    let mut s = match &question_entry.entry_type {
+        EntryType::Int(_) => "Type a whole number and press Enter.",
+        EntryType::Float(_) => "Type a number and press Enter.",
+        EntryType::Bool(_) => "Type yes or no and press Enter.",
+        EntryType::Option(_) => "Type the number of an option and press Enter.",
+        _ => "Type the text and press Enter.",
+    }.to_string();
+    if let Some(d) = default_text(&question_entry.entry_type) {
+        s.push_str(&format!(" Press Enter alone for {}.", d));
+    }
+    s.push(' ');
+    s.push_str(&help_words(question_entry.help_text.is_some()));
+    s
+}
+
+pub(crate) fn proceed_hint(has_help: bool, preferred: Option<bool>) -> String {
+    let default = if preferred.unwrap_or(true) { "yes" } else { "no" };
+    let help = if has_help { " Type help for help." } else { "" };
+    format!("Type yes or no and press Enter. Press Enter alone for {}.{}", default, help)
+}
+
+/// Lines that introduce a question: the announcement, the options and the hint
+pub(crate) fn question_intro(question_entry: &QuestionEntry, question_count: usize, preferred_txt: &str) -> Vec<String> {
+    let mut ret = vec![question_announcement(question_entry, question_count)];
+    if let EntryType::Option(o) = &question_entry.entry_type {
+        ret.extend(option_lines(o));
+    }
+    if ! preferred_txt.is_empty() {
+        ret.push(format!("Last answer: {}. Press Enter alone to keep it.", preferred_txt));
+    }
+    ret.push(short_hint(question_entry));
+    ret
+}
+
+/// Handles an input line of a question. Option numbers start at 1.
+pub(crate) fn question_reply(question_entry: &QuestionEntry, question_count: usize, input: &str, preferred_txt: &str) -> Reply {
+    let input = input.trim();
+    match input.to_lowercase().as_str() {
+        HINT_COMMAND => {
+            let mut lines: Vec<String> = Vec::new();
+            if let EntryType::Option(o) = &question_entry.entry_type {
+                lines.extend(option_lines(o));
+            } else {
+                lines.push(get_valid_input_hint(question_entry).replace('⏎', "Enter"));
+            }
+            return Reply::Say(lines);
+        },
+        "help" | "h" | "?" if question_entry.help_text.is_some() => {
+            return Reply::Say(vec![format!("Help: {}", question_entry.help_text.as_ref().unwrap())]);
+        },
+        _ => {},
+    }
+    let mut value = if input.is_empty() { preferred_txt.to_string() } else { input.to_string() };
+    if let EntryType::Option(o) = &question_entry.entry_type {
+        if let Ok(n) = value.parse::<usize>() {
+            if n >= 1 && n <= o.options.len() {
+                value = o.options[n - 1].clone();
+            } else {
+                return Reply::Say(vec![
+                    format!("Invalid answer: there is no option {}.", n),
+                    question_announcement(question_entry, question_count),
+                ]);
+            }
+        }
+    }
+    match validate_input(&value, &question_entry.entry_type, question_entry.required) {
+        Ok(a) => Reply::Answer(a),
+        Err(e) => Reply::Say(vec![
+            format!("Invalid answer: {}", e),
+            question_announcement(question_entry, question_count),
+        ]),
+    }
+}
+
+/// Handles an input line of a proceed screen
+pub(crate) fn proceed_reply(text: &str, help_text: Option<&str>, question_count: usize, current: usize, input: &str, preferred: Option<bool>) -> Result<bool, Vec<String>> {
+    match input.trim().to_lowercase().as_str() {
+        "y" | "yes" => Ok(true),
+        "n" | "no" => Ok(false),
+        "" => Ok(preferred.unwrap_or(true)),
+        "help" | "h" | "?" if help_text.is_some() => Err(vec![format!("Help: {}", help_text.unwrap())]),
+        HINT_COMMAND => Err(vec![proceed_hint(help_text.is_some(), preferred)]),
+        other => Err(vec![
+            format!("Invalid answer: {} is not yes or no.", other),
+            proceed_announcement(text, question_count, current),
+        ]),
+    }
+}
+
+/// Answer that is said after it was taken
+pub(crate) fn answer_text(answer: &QuestionAnswerInput) -> String {
+    let s = answer.to_string();
+    if s.is_empty() {
+        "Answer: none".to_string()
+    } else {
+        format!("Answer: {}", s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::questionaire::IntEntry;
+
+    #[test]
+    fn test_question_texts() {
+        let mut e = QuestionEntry::builder()
+            .id("color")
+            .query_text("Color?")
+            .help_text("Your favourite color")
+            .entry_type(EntryType::Option(OptionEntry::builder()
+                .options(vec!["red".to_string(), "green".to_string()])
+                .default_value(1)
+                .build()))
+            .build();
+        e.pos = 3;
+        assert_eq!(vec![
+            "Question 3 of 7, choice of 2 options: Color?",
+            "Option 1 of 2: red",
+            "Option 2 of 2: green",
+            "Type the number of an option and press Enter. Press Enter alone for green. Type hint for the full hint or help for help.",
+        ], question_intro(&e, 7, ""));
+        assert!(matches!(question_reply(&e, 7, "1", ""), Reply::Answer(QuestionAnswerInput::Option(Some(v))) if v == "red"));
+        assert!(matches!(question_reply(&e, 7, "green", ""), Reply::Answer(QuestionAnswerInput::Option(Some(v))) if v == "green"));
+        match question_reply(&e, 7, "3", "") {
+            Reply::Say(l) => assert_eq!(vec!["Invalid answer: there is no option 3.", "Question 3 of 7, choice of 2 options: Color?"], l),
+            Reply::Answer(_) => panic!("unexpected answer"),
+        }
+        e.pos = 0;
+        e.entry_type = EntryType::Int(IntEntry::builder().max(10).build());
+        assert_eq!("Whole number question: Color?", question_announcement(&e, 7));
+        match question_reply(&e, 7, "hint", "") {
+            Reply::Say(l) => assert_eq!(vec!["Please enter an integer and take it with Enter, max: 10 (for more info type 'h')"], l),
+            Reply::Answer(_) => panic!("unexpected answer"),
+        }
+        assert!(matches!(question_reply(&e, 7, "", "4"), Reply::Answer(QuestionAnswerInput::Int(Some(4)))));
+    }
+
+    #[test]
+    fn test_proceed_reply() {
+        assert_eq!(Ok(false), proceed_reply("Go?", None, 0, 0, "", Some(false)));
+        assert_eq!(Ok(true), proceed_reply("Go?", None, 0, 0, "Yes", None));
+        assert_eq!(Err(vec!["Invalid answer: x is not yes or no.".to_string(), "Yes or no question: Go?".to_string()]),
+            proceed_reply("Go?", None, 0, 0, "x", None));
+        assert_eq!(Err(vec!["Help: more".to_string()]), proceed_reply("Go?", Some("more"), 0, 0, "help", None));
+    }
+}
@@ -1,7 +1,7 @@
 use crate::{
-    persistence::QuestionairePersistence, questionaire::{AnswerEntry, BlockAnswer, QuestionAnswer, QuestionAnswerInput, Questionaire, RepeatedQuestionAnswers, RepeatedQuestionEntry, SubBlock}, ui::{MsgLevel, ProceedScreenResult, QuestionScreenResult, QuestionaireView}, QuestionEntry, QuestionaireEntry
+    persistence::QuestionairePersistence, progress::{entries_count, Progress}, questionaire::{AnswerEntry, BlockAnswer, EntryType, QuestionAnswer, QuestionAnswerInput, Questionaire, RepeatedQuestionAnswers, RepeatedQuestionEntry, SubBlock}, ui::{MsgLevel, ProceedScreenResult, QuestionScreenResult, QuestionaireView}, QuestionEntry, QuestionaireEntry
 };
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use serde::{Deserialize, Serialize};
 
 
@@ -28,17 +28,24 @@ impl<'a, V: QuestionaireView, P: QuestionairePersistence> QuestionaireController
         Self { questionaire, view, persistence }
     }
 
+    #[cfg(feature = "templates")]
+    pub fn view_mut(&mut self) -> &mut V {
+        &mut self.view
+    }
+
     pub fn run(&mut self) -> Result<QuestionaireResult> {   
         let pos_count = if let Some(pc) = self.questionaire.pos_count {
             pc
         } else {
             0
         };
-        match run_sub_block(&mut self.view, &mut self.persistence, &self.questionaire.init_block, true, pos_count)? {
+        let mut progress = Progress::new(&self.questionaire.init_block);
+        match run_sub_block(&mut self.view, &mut self.persistence, &self.questionaire.init_block, true, pos_count, &mut progress)? {
             ControllerResult::Canceled => return Ok(QuestionaireResult::Canceled),
             ControllerResult::Finished(answers) => {
                 match answers {
                     AnswerEntry::Block(ba) => {
+                        self.persistence.finish()?;
                         return Ok(QuestionaireResult::Finished(ba));
                     },
                     _ => panic!("receive wrong result for init-block"),
@@ -53,7 +60,8 @@ fn enter_sub_block<V: QuestionaireView, P: QuestionairePersistence> (
     persistence: &mut P,
     sub_block: &SubBlock,
     init: bool,
-    question_count: usize
+    question_count: usize,
+    progress: &mut Progress,
 ) -> Result<ControllerResult> {
     let mut block_answer: BlockAnswer = BlockAnswer {
         id: sub_block.id.clone(),
@@ -62,28 +70,82 @@ fn enter_sub_block<V: QuestionaireView, P: QuestionairePersistence> (
     let mut has_preferred = false;
     loop {
         let mut iteration_answers: Vec<AnswerEntry> = Vec::new();
-        for e in &sub_block.entries {
+        // entries, that are skipped after a proceed query was answered with 'no'
+        let mut skip_count: usize = 0;
+        for (i, e) in sub_block.entries.iter().enumerate() {
+            if skip_count > 0 {
+                skip_count -= 1;
+                continue;
+            }
             // ask the sub-queries ...
             match e {
+                QuestionaireEntry::Question(q) if q.entry_type == EntryType::InfoTxt => {
+                    // only shown, there is no answer
+                    if let ProceedScreenResult::Canceled = view.show_info_screen(q)? {
+                        return Ok(ControllerResult::Canceled);
+                    }
+                },
+                QuestionaireEntry::Question(q) if matches!(q.entry_type, EntryType::ProceedQuery(_)) => {
+                    let (preferred, preferred_timed_out) = match get_preferred(&q.id, persistence) {
+                        (Some(QuestionAnswerInput::Bool(b)), t) => (b, t),
+                        _ => (None, false),
+                    };
+                    view.set_progress(progress);
+                    let (b, timed_out) = match view.show_proceed_screen(&q.id, &q.query_text, q.help_text.as_deref(), question_count, q.pos, preferred)? {
+                        ProceedScreenResult::Canceled => return Ok(ControllerResult::Canceled),
+                        ProceedScreenResult::Proceeded(b) => (b, false),
+                        ProceedScreenResult::TimedOut(b) => (b, true),
+                    };
+                    // the unchanged answer of a stored timeout keeps its mark
+                    let timed_out = timed_out || (preferred_timed_out && preferred == Some(b));
+                    progress.step_done();
+                    let answer = QuestionAnswerInput::Bool(Some(b));
+                    store_answer(persistence, q, &answer, timed_out)?;
+                    iteration_answers.push(AnswerEntry::Question(QuestionAnswer {
+                        id: q.id.to_string(),
+                        answer,
+                        timed_out,
+                    }));
+                    if let (false, EntryType::ProceedQuery(n)) = (b, &q.entry_type) {
+                        let end = (i + 1 + *n as usize).min(sub_block.entries.len());
+                        skip_count = end - i - 1;
+                        progress.skip(entries_count(&sub_block.entries[i + 1..end]));
+                        // record the skipped entries, so that exports can tell them from unanswered ones
+                        for s in &sub_block.entries[i + 1..end] {
+                            match s {
+                                QuestionaireEntry::Question(q) if q.entry_type == EntryType::InfoTxt => {},
+                                QuestionaireEntry::Question(q) => iteration_answers.push(AnswerEntry::Skipped(q.id.clone())),
+                                QuestionaireEntry::RepeatedQuestion(rq) => iteration_answers.push(AnswerEntry::Skipped(rq.id.clone())),
+                                QuestionaireEntry::Block(b) => iteration_answers.push(AnswerEntry::Skipped(b.id.clone())),
+                            }
+                        }
+                    }
+                },
                 QuestionaireEntry::Question(q) => {
-                    let preferred = get_preferred(&q.id, persistence);
+                    let (preferred, preferred_timed_out) = get_preferred(&q.id, persistence);
                     if ! has_preferred {
                         has_preferred = true;
                     }
-                    match view.show_question_screen(&q, question_count, preferred)? {
+                    let timed_out_answer = preferred.clone().filter(|_| preferred_timed_out);
+                    view.set_progress(progress);
+                    let (answer, timed_out) = match view.show_question_screen(&q, question_count, preferred)? {
                         QuestionScreenResult::Canceled => return Ok(ControllerResult::Canceled),
-                        QuestionScreenResult::Proceeded(answer) => {
-                            let _ = persistence.store_question(&q , &answer);
-                            let qa = QuestionAnswer {
-                                id: q.id.to_string(),
-                                answer: answer.clone(),
-                            };
-                            iteration_answers.push(AnswerEntry::Question(qa));
-                        }
-                    }
+                        QuestionScreenResult::Proceeded(answer) => (answer, false),
+                        QuestionScreenResult::TimedOut(answer) => (answer, true),
+                    };
+                    // the unchanged answer of a stored timeout keeps its mark
+                    let timed_out = timed_out || timed_out_answer.as_ref() == Some(&answer);
+                    progress.step_done();
+                    store_answer(persistence, &q, &answer, timed_out)?;
+                    let qa = QuestionAnswer {
+                        id: q.id.to_string(),
+                        answer: answer.clone(),
+                        timed_out,
+                    };
+                    iteration_answers.push(AnswerEntry::Question(qa));
                 }
                 QuestionaireEntry::Block(b) => {
-                    match run_sub_block(view, persistence, b, false, question_count)? {
+                    match run_sub_block(view, persistence, b, false, question_count, progress)? {
                         ControllerResult::Canceled => return Ok(ControllerResult::Canceled),
                         ControllerResult::Finished(answer) => {
                             iteration_answers.push(answer);
@@ -91,7 +153,7 @@ fn enter_sub_block<V: QuestionaireView, P: QuestionairePersistence> (
                     }
                 },
                 QuestionaireEntry::RepeatedQuestion(rq) => {
-                    match run_repeated_question(view, persistence,  rq, question_count)? {
+                    match run_repeated_question(view, persistence, rq, question_count, progress)? {
                         ControllerResult::Canceled => return Ok(ControllerResult::Canceled),
                         ControllerResult::Finished(answer) => {
                             iteration_answers.push(answer);
@@ -102,17 +164,7 @@ fn enter_sub_block<V: QuestionaireView, P: QuestionairePersistence> (
         }
         block_answer.iterations.push(iteration_answers);
         if let Some(end_text) = sub_block.end_text.as_deref() {
-            let mut preferred = match has_preferred_block_answer(&sub_block.id, persistence) {
-                PreferredBlockAnswer::Exist => {
-                    Some(true)
-                },
-                PreferredBlockAnswer::NextHasWrongId => {
-                    Some(false)
-                },
-                _ => {
-                    None
-                },
-            };
+            let mut preferred = get_preferred_block_decision(&sub_block.id, persistence);
             if init {
                 preferred = None;
             }
@@ -122,13 +174,14 @@ fn enter_sub_block<V: QuestionaireView, P: QuestionairePersistence> (
                 .id("00000000")
                 .build();
                 let final_data = QuestionAnswerInput::String(Some("done".to_string()));
-                let _ = persistence.store_question(&dummy_entry, &final_data); // this is included to show that the questionary was finished
+                persistence.store_question(&dummy_entry, &final_data)?; // this is included to show that the questionary was finished
                 question_count
             } else {
                 0
             };
 
-            match view.show_proceed_screen(
+            view.set_progress(progress);
+            let (b, timed_out) = match view.show_proceed_screen(
                 &sub_block.id,
                 end_text,
                 sub_block.help_text.as_deref(),
@@ -138,21 +191,25 @@ fn enter_sub_block<V: QuestionaireView, P: QuestionairePersistence> (
                     //return Ok(ControllerResult::Canceled)
                     break;
                 },
-                ProceedScreenResult::Proceeded(b) => {
-                    if ! b {
-                        if init {
-                            return Ok(ControllerResult::Canceled);
-                        } else {
-                            break;
-                        }
-                    } else {
-                        // TODO ... it's some kind of critical. What's happen if the last question
-                        // is answered with 'No' but it should not be looped
-                        if ! sub_block.loop_over_entries {
-                            break;
-                        }
-                    }
+                ProceedScreenResult::Proceeded(b) => (b, false),
+                ProceedScreenResult::TimedOut(b) => (b, true),
+            };
+            persistence.store_block_decision(&sub_block.id, b)?;
+            if ! b {
+                if init {
+                    return Ok(ControllerResult::Canceled);
+                } else {
+                    break;
                 }
+            } else {
+                // TODO ... it's some kind of critical. What's happen if the last question
+                // is answered with 'No' but it should not be looped
+                // A timeout doesn't start another iteration, unattended runs would never end.
+                if ! sub_block.loop_over_entries || timed_out {
+                    break;
+                }
+                // the next iteration asks the entries again
+                progress.add(entries_count(&sub_block.entries));
             }
         } else {
             break;
@@ -184,12 +241,26 @@ fn has_preferred_block_answer<P: QuestionairePersistence>(id: &str, persistence:
     PreferredBlockAnswer::NoMoreAnswers
 }
 
+/// The stored decision for the block, or else the one derived from the ids of
+/// the stored answers
+fn get_preferred_block_decision<P: QuestionairePersistence>(id: &str, persistence: &mut P) -> Option<bool> {
+    if let Some(d) = persistence.next_block_decision(id) {
+        return Some(d);
+    }
+    match has_preferred_block_answer(id, persistence) {
+        PreferredBlockAnswer::Exist => Some(true),
+        PreferredBlockAnswer::NextHasWrongId => Some(false),
+        PreferredBlockAnswer::NoMoreAnswers => None,
+    }
+}
+
 fn run_sub_block<V: QuestionaireView, P: QuestionairePersistence> (
     view: &mut V,
     persistence: &mut P,
     sub_block: &SubBlock,
     init: bool, 
-    question_count: usize) -> Result<ControllerResult> {        
+    question_count: usize,
+    progress: &mut Progress) -> Result<ControllerResult> {        
 
     let current = if let Some(p) = sub_block.pos {
         p
@@ -197,21 +268,12 @@ fn run_sub_block<V: QuestionaireView, P: QuestionairePersistence> (
         0
     };
 
-    let mut preferred = match has_preferred_block_answer(&sub_block.id, persistence) {
-        PreferredBlockAnswer::Exist => {
-            Some(true)
-        },
-        PreferredBlockAnswer::NextHasWrongId => {
-            Some(false)
-        },
-        _ => {
-            None
-        },
-    };
+    let mut preferred = get_preferred_block_decision(&sub_block.id, persistence);
     if init && persistence.next_answer_id().is_some() {
         preferred = Some(true)
     }
 
+    view.set_progress(progress);
     match view.show_proceed_screen(
         &sub_block.id,
         &sub_block.start_text,
@@ -223,13 +285,19 @@ fn run_sub_block<V: QuestionaireView, P: QuestionairePersistence> (
         ProceedScreenResult::Canceled => {
             return Ok(ControllerResult::Canceled);
         },
-        ProceedScreenResult::Proceeded(b) => {
+        ProceedScreenResult::Proceeded(b) | ProceedScreenResult::TimedOut(b) => {
+            persistence.store_block_decision(&sub_block.id, b)?;
+            if ! init {
+                // the start screen of the questionaire isn't a step
+                progress.step_done();
+            }
             if b {
-                return enter_sub_block(view, persistence, sub_block, init, question_count);
+                return enter_sub_block(view, persistence, sub_block, init, question_count, progress);
             } else {
                 if init {
                     return Ok(ControllerResult::Canceled);
                 } else {
+                    progress.skip(entries_count(&sub_block.entries));
                     let mut ret: BlockAnswer = BlockAnswer::default();
                     ret.id = sub_block.id.clone();
                     return Ok(ControllerResult::Finished(AnswerEntry::Block(ret)));
@@ -243,10 +311,15 @@ fn run_sub_block<V: QuestionaireView, P: QuestionairePersistence> (
 fn run_repeated_question<V: QuestionaireView, P: QuestionairePersistence> (
     view: &mut V,
     persistence: &mut P,
-    repeated_question: &RepeatedQuestionEntry, question_count: usize) -> Result<ControllerResult> {
-        fn push_result<P: QuestionairePersistence>(q: &QuestionEntry, answers: &mut Vec<QuestionAnswerInput>, a: &QuestionAnswerInput, persistence: &mut P) {
-            let _ = persistence.store_question(&q , a);
+    repeated_question: &RepeatedQuestionEntry, question_count: usize, progress: &mut Progress) -> Result<ControllerResult> {
+        if matches!(repeated_question.entry_type, EntryType::InfoTxt | EntryType::ProceedQuery(_)) {
+            return Err(anyhow!("The repeated question '{}' needs a type with an input", repeated_question.id));
+        }
+        fn push_result<P: QuestionairePersistence>(q: &QuestionEntry, answers: &mut Vec<QuestionAnswerInput>, a: &QuestionAnswerInput, persistence: &mut P, progress: &mut Progress) -> Result<()> {
+            persistence.store_question(&q , a)?;
+            progress.repeated_step_done();
             answers.push(a.clone());
+            Ok(())
         }
     
 
@@ -264,11 +337,13 @@ fn run_repeated_question<V: QuestionaireView, P: QuestionairePersistence> (
 
     let mut loop_count: usize = 0;
     let mut answers: Vec<QuestionAnswerInput> = Vec::new();
+    let mut timed_out = false;
     let mut has_preferred = false; // this is needed to skip in fast-forward mode
     loop {
         loop_count += 1;
         if (repeated_question.max_count>0) && (loop_count > repeated_question.max_count) {
             view.show_msg("Reached maximum number of input entries. Go on with the next topic ...", MsgLevel::Normal);
+            progress.skip(1);
             break;
         }
 
@@ -287,7 +362,7 @@ fn run_repeated_question<V: QuestionaireView, P: QuestionairePersistence> (
             .query_text(&question_txt)
             .entry_type(repeated_question.entry_type.clone())
             .build();
-        let mut preferred = get_preferred(&repeated_question.id, persistence);
+        let (mut preferred, preferred_timed_out) = get_preferred(&repeated_question.id, persistence);
         if preferred.is_some() {
             has_preferred = true
         } else {
@@ -295,79 +370,124 @@ fn run_repeated_question<V: QuestionaireView, P: QuestionairePersistence> (
                 preferred = Some(QuestionAnswerInput::String(None));
             }
         }
+        view.set_progress(progress);
         match view.show_question_screen(&q, question_count, preferred)? {
             QuestionScreenResult::Canceled => return Ok(ControllerResult::Canceled),
+            // a timeout ends the list, or cancels, if there are too few elements
+            QuestionScreenResult::TimedOut(_) => {
+                if answers.len() < repeated_question.min_count {
+                    return Ok(ControllerResult::Canceled);
+                }
+                progress.step_done();
+                timed_out = true;
+                break;
+            },
             QuestionScreenResult::Proceeded(answer) => {
                 match &answer {
                     QuestionAnswerInput::String(a) => {
                         if check_for_min_input::<V, String>(&repeated_question, loop_count, view, &a) {
                             if a.is_none() {
+                                // ends where the stored run timed out
+                                timed_out = preferred_timed_out;
+                                progress.step_done();
                                 break;
                             }
-                            push_result(&q, &mut answers, &answer, persistence);
+                            push_result(&q, &mut answers, &answer, persistence, progress)?;
                         }
                     },
                     QuestionAnswerInput::Int(a) => {
                         if check_for_min_input::<V, i32>(&repeated_question, loop_count, view, &a) {
                             if a.is_none() {
+                                // ends where the stored run timed out
+                                timed_out = preferred_timed_out;
+                                progress.step_done();
                                 break;
                             }
-                            push_result(&q, &mut answers, &answer, persistence);
+                            push_result(&q, &mut answers, &answer, persistence, progress)?;
                         }
                     },
                     QuestionAnswerInput::Float(a) => {
                         if check_for_min_input::<V, f32>(&repeated_question, loop_count, view, &a) {
                             if a.is_none() {
+                                // ends where the stored run timed out
+                                timed_out = preferred_timed_out;
+                                progress.step_done();
                                 break;
                             }
-                            push_result(&q, &mut answers, &answer, persistence);
+                            push_result(&q, &mut answers, &answer, persistence, progress)?;
                         }
                     },
                     QuestionAnswerInput::Bool(a) => {
                         if check_for_min_input::<V, bool>(&repeated_question, loop_count, view, &a) {
                             if a.is_none() {
+                                // ends where the stored run timed out
+                                timed_out = preferred_timed_out;
+                                progress.step_done();
                                 break;
                             }
-                            push_result(&q, &mut answers, &answer, persistence);
+                            push_result(&q, &mut answers, &answer, persistence, progress)?;
                         }                        
                     },
                     QuestionAnswerInput::Option(a) => {
                         if check_for_min_input::<V, String>(&repeated_question, loop_count, view, &a) {
                             if a.is_none() {
+                                // ends where the stored run timed out
+                                timed_out = preferred_timed_out;
+                                progress.step_done();
                                 break;
                             }
-                            push_result(&q, &mut answers, &answer, persistence);
+                            push_result(&q, &mut answers, &answer, persistence, progress)?;
                         }                        
                     },
                     QuestionAnswerInput::None => {
-                        push_result(&q, &mut answers, &answer, persistence);
+                        push_result(&q, &mut answers, &answer, persistence, progress)?;
                     },
                 };
             }
         }
     }
+    if timed_out {
+        // the end of the list is stored, so that a resumed run keeps the mark
+        let q = QuestionEntry::builder()
+            .id(&repeated_question.id)
+            .pos(repeated_question.pos)
+            .query_text(&repeated_question.query_text)
+            .entry_type(repeated_question.entry_type.clone())
+            .build();
+        persistence.store_timed_out_question(&q, &QuestionAnswerInput::String(None))?;
+    }
     let r = RepeatedQuestionAnswers {
         id: repeated_question.id.to_string(),
         answers,
+        timed_out,
     };
 Ok(ControllerResult::Finished(
         AnswerEntry::RepeatedQuestion(r)
     ))
 }
 
-fn get_preferred<P: QuestionairePersistence>(id: &str, persistence: &mut P) -> Option<QuestionAnswerInput> {
+/// Returns the stored answer of the question, and if it was taken after a timeout
+fn get_preferred<P: QuestionairePersistence>(id: &str, persistence: &mut P) -> (Option<QuestionAnswerInput>, bool) {
     if let Some(i) = persistence.next_answer_id() {
         if i == id {
             if let Some(a) = persistence.next_answer() {
-                Some(a.answer.clone())
+                (Some(a.answer.clone()), a.timed_out)
             } else {
-                None
+                (None, false)
             }
         } else {
-            Some(QuestionAnswerInput::String(None))
+            (Some(QuestionAnswerInput::String(None)), false)
         }
     } else {
-        None
+        (None, false)
+    }
+}
+
+fn store_answer<P: QuestionairePersistence>(persistence: &mut P, q: &QuestionEntry, answer: &QuestionAnswerInput, timed_out: bool) -> Result<()> {
+    if timed_out {
+        persistence.store_timed_out_question(q, answer)
+    } else {
+        persistence.store_question(q, answer)
     }
 }
 
@@ -685,4 +805,389 @@ mod tests {
         
     }
 
+    #[test]
+    fn it_tracks_progress() {
+        use crate::questionaire::{EntryType, StringEntry};
+
+        #[derive(Default)]
+        struct UiMock {
+            progress: Progress,
+            /// id, answered and total of each screen
+            screens: Vec<(String, usize, usize)>,
+            loops: usize,
+        }
+
+        impl QuestionaireView for UiMock {
+            fn set_progress(&mut self, progress: &Progress) {
+                self.progress = *progress;
+            }
+            fn show_proceed_screen<'a, T: Into<Option<&'a str>>>(&mut self, id: &str, _text: &str, _help_text: T, _question_count: usize, _current: usize, _p: Option<bool>) -> Result<ProceedScreenResult> {
+                self.screens.push((id.to_string(), self.progress.answered, self.progress.total()));
+                let ret = match id {
+                    // start and end screen of the loop
+                    "loop" => { self.loops += 1; self.loops != 3 },
+                    "skip" => false,
+                    _ => true,
+                };
+                Ok(ProceedScreenResult::Proceeded(ret))
+            }
+            fn show_question_screen(&mut self, question_entry: &QuestionEntry, _question_count: usize, _p: Option<QuestionAnswerInput>) -> Result<QuestionScreenResult>{
+                self.screens.push((question_entry.id.clone(), self.progress.answered, self.progress.total()));
+                Ok(QuestionScreenResult::Proceeded(QuestionAnswerInput::String(Some("x".to_string()))))
+            }
+        }
+
+        let question = |id: &str| QuestionaireEntry::Question(QuestionEntry::builder()
+            .id(id)
+            .query_text(id)
+            .entry_type(EntryType::String(StringEntry::builder().build()))
+            .build());
+        let questionaire = Questionaire::builder()
+            .id("q")
+            .start_text("Start?")
+            .end_text("Finish?")
+            .questions(vec![
+                question("q1"),
+                QuestionaireEntry::Block(SubBlock::builder()
+                    .id("loop")
+                    .start_text("Loop?")
+                    .end_text("Again?")
+                    .loop_over_entries(true)
+                    .entries(vec![question("q2")])
+                    .build()),
+                QuestionaireEntry::Block(SubBlock::builder()
+                    .id("skip")
+                    .start_text("Skip?")
+                    .entries(vec![question("q3")])
+                    .build()),
+                question("q4"),
+            ])
+            .build();
+        let mut ui = UiMock::default();
+        let mut c = QuestionaireController::new(&questionaire, &mut ui, NoPersistence::new());
+        assert!(matches!(c.run().unwrap(), QuestionaireResult::Finished(_)));
+        let expected: Vec<(String, usize, usize)> = vec![
+            ("q", 0, 6), ("q1", 0, 6), ("loop", 1, 6), ("q2", 2, 6),
+            // the second iteration adds its question
+            ("loop", 3, 6), ("q2", 3, 7), ("loop", 4, 7),
+            // the declined block drops its question
+            ("skip", 4, 7), ("q4", 5, 6), ("q", 6, 6),
+        ].into_iter().map(|(id, a, t)| (id.to_string(), a, t)).collect();
+        assert_eq!(expected, ui.screens);
+    }
+
+    #[test]
+    fn it_shows_info_and_skips_after_proceed_query() {
+        use crate::questionaire::StringEntry;
+
+        #[derive(Default)]
+        struct UiMock {
+            progress: Progress,
+            screens: Vec<String>,
+        }
+
+        impl QuestionaireView for UiMock {
+            fn set_progress(&mut self, progress: &Progress) {
+                self.progress = *progress;
+            }
+            fn show_info_screen(&mut self, question_entry: &QuestionEntry) -> Result<ProceedScreenResult> {
+                self.screens.push(format!("info {}", question_entry.id));
+                Ok(ProceedScreenResult::Proceeded(true))
+            }
+            fn show_proceed_screen<'a, T: Into<Option<&'a str>>>(&mut self, id: &str, _text: &str, _help_text: T, _question_count: usize, _current: usize, _p: Option<bool>) -> Result<ProceedScreenResult> {
+                self.screens.push(format!("proceed {} {}/{}", id, self.progress.answered, self.progress.total()));
+                Ok(ProceedScreenResult::Proceeded(id != "pq"))
+            }
+            fn show_question_screen(&mut self, question_entry: &QuestionEntry, _question_count: usize, _p: Option<QuestionAnswerInput>) -> Result<QuestionScreenResult>{
+                self.screens.push(format!("question {} {}/{}", question_entry.id, self.progress.answered, self.progress.total()));
+                Ok(QuestionScreenResult::Proceeded(QuestionAnswerInput::String(Some("x".to_string()))))
+            }
+        }
+
+        let entry = |id: &str, entry_type: EntryType| QuestionaireEntry::Question(QuestionEntry::builder()
+            .id(id)
+            .query_text(id)
+            .entry_type(entry_type)
+            .build());
+        let string = || EntryType::String(StringEntry::builder().build());
+        let questionaire = Questionaire::builder()
+            .id("q")
+            .start_text("Start?")
+            .questions(vec![
+                entry("i1", EntryType::InfoTxt),
+                entry("pq", EntryType::ProceedQuery(1)),
+                entry("q1", string()),
+                entry("q2", string()),
+            ])
+            .build();
+        let mut ui = UiMock::default();
+        let mut c = QuestionaireController::new(&questionaire, &mut ui, NoPersistence::new());
+        let ba = match c.run().unwrap() {
+            QuestionaireResult::Finished(ba) => ba,
+            QuestionaireResult::Canceled => panic!("unexpected cancel"),
+        };
+        assert_eq!(vec!["proceed q 0/3", "info i1", "proceed pq 0/3", "question q2 1/2"], ui.screens);
+        // the info text has no answer, the skipped question is recorded as skipped
+        let answers = &ba.iterations[0];
+        assert_eq!(3, answers.len());
+        match &answers[0] {
+            AnswerEntry::Question(qa) => {
+                assert_eq!("pq", qa.id);
+                assert_eq!(QuestionAnswerInput::Bool(Some(false)), qa.answer);
+            },
+            _ => panic!("expected the answer of the proceed query"),
+        }
+        assert!(matches!(&answers[1], AnswerEntry::Skipped(id) if id == "q1"));
+        validate_question_string_input(&answers[2], "x");
+    }
+
+    #[test]
+    fn it_takes_defaults_after_timeout() {
+        use crate::questionaire::{EntryType, StringEntry};
+
+        #[derive(Default)]
+        struct UiMock {
+            screens: Vec<String>,
+        }
+
+        impl QuestionaireView for UiMock {
+            fn show_proceed_screen<'a, T: Into<Option<&'a str>>>(&mut self, id: &str, _text: &str, _help_text: T, _question_count: usize, _current: usize, _p: Option<bool>) -> Result<ProceedScreenResult> {
+                self.screens.push(id.to_string());
+                Ok(ProceedScreenResult::TimedOut(true))
+            }
+            fn show_question_screen(&mut self, question_entry: &QuestionEntry, _question_count: usize, _p: Option<QuestionAnswerInput>) -> Result<QuestionScreenResult>{
+                self.screens.push(question_entry.id.clone());
+                Ok(QuestionScreenResult::TimedOut(QuestionAnswerInput::String(Some("default".to_string()))))
+            }
+        }
+
+        let questionaire = Questionaire::builder()
+            .id("q")
+            .start_text("Start?")
+            .end_text("Finish?")
+            .questions(vec![
+                QuestionaireEntry::Block(SubBlock::builder()
+                    .id("loop")
+                    .start_text("Loop?")
+                    .end_text("Again?")
+                    .loop_over_entries(true)
+                    .entries(vec![QuestionaireEntry::Question(QuestionEntry::builder()
+                        .id("q1")
+                        .query_text("q1")
+                        .entry_type(EntryType::String(StringEntry::builder().default_value("default").build()))
+                        .build())])
+                    .build()),
+            ])
+            .build();
+        let mut ui = UiMock::default();
+        let mut c = QuestionaireController::new(&questionaire, &mut ui, NoPersistence::new());
+        let ba = match c.run().unwrap() {
+            QuestionaireResult::Finished(ba) => ba,
+            QuestionaireResult::Canceled => panic!("unexpected cancel"),
+        };
+        // the timed out end screen of the loop doesn't start another iteration
+        assert_eq!(vec!["q", "loop", "q1", "loop", "q"], ui.screens);
+        match &ba.iterations[0][0] {
+            AnswerEntry::Block(b) => match &b.iterations[0][0] {
+                AnswerEntry::Question(qa) => {
+                    assert_eq!(QuestionAnswerInput::String(Some("default".to_string())), qa.answer);
+                    assert!(qa.timed_out);
+                },
+                _ => panic!("expected a question answer"),
+            },
+            _ => panic!("expected a block answer"),
+        }
+    }
+
+    #[test]
+    fn it_records_timeout_of_repeated_question() {
+        use crate::questionaire::{EntryType, StringEntry};
+
+        #[derive(Default)]
+        struct UiMock {
+            count: usize,
+        }
+
+        impl QuestionaireView for UiMock {
+            fn show_proceed_screen<'a, T: Into<Option<&'a str>>>(&mut self, _id: &str, _text: &str, _help_text: T, _question_count: usize, _current: usize, _p: Option<bool>) -> Result<ProceedScreenResult> {
+                Ok(ProceedScreenResult::Proceeded(true))
+            }
+            fn show_question_screen(&mut self, _question_entry: &QuestionEntry, _question_count: usize, _p: Option<QuestionAnswerInput>) -> Result<QuestionScreenResult>{
+                self.count += 1;
+                if self.count == 1 {
+                    Ok(QuestionScreenResult::Proceeded(QuestionAnswerInput::String(Some("Bart".to_string()))))
+                } else {
+                    Ok(QuestionScreenResult::TimedOut(QuestionAnswerInput::String(None)))
+                }
+            }
+        }
+
+        let questionaire = Questionaire::builder()
+            .id("q")
+            .start_text("Start?")
+            .questions(vec![
+                QuestionaireEntry::RepeatedQuestion(RepeatedQuestionEntry::builder()
+                    .id("kids")
+                    .query_text("Name of a kid")
+                    .entry_type(EntryType::String(StringEntry::builder().build()))
+                    .build()),
+            ])
+            .build();
+        let mut ui = UiMock::default();
+        let mut c = QuestionaireController::new(&questionaire, &mut ui, NoPersistence::new());
+        let ba = match c.run().unwrap() {
+            QuestionaireResult::Finished(ba) => ba,
+            QuestionaireResult::Canceled => panic!("unexpected cancel"),
+        };
+        match &ba.iterations[0][0] {
+            AnswerEntry::RepeatedQuestion(ra) => {
+                assert_eq!(vec![QuestionAnswerInput::String(Some("Bart".to_string()))], ra.answers);
+                assert!(ra.timed_out);
+            },
+            _ => panic!("expected the answers of the repeated question"),
+        }
+    }
+
+    #[test]
+    fn it_keeps_stored_timeouts() {
+        use crate::questionaire::{EntryType, StringEntry};
+        use crate::QuestionAnswer;
+        use std::collections::VecDeque;
+
+        #[derive(Default)]
+        struct Journal {
+            data: VecDeque<QuestionAnswer>,
+            stored: Vec<(String, QuestionAnswerInput, bool)>,
+        }
+
+        impl QuestionairePersistence for Journal {
+            fn store_question(&mut self, entry: &QuestionEntry, data: &QuestionAnswerInput) -> Result<()> {
+                self.stored.push((entry.id.clone(), data.clone(), false));
+                Ok(())
+            }
+            fn store_timed_out_question(&mut self, entry: &QuestionEntry, data: &QuestionAnswerInput) -> Result<()> {
+                self.stored.push((entry.id.clone(), data.clone(), true));
+                Ok(())
+            }
+            fn load(&mut self, _s: Option<&str>) -> Result<()> {
+                Ok(())
+            }
+            fn import(&mut self, data_to_import: &Vec<QuestionAnswer>) {
+                self.data.extend(data_to_import.iter().cloned());
+            }
+            fn next_answer(&mut self) -> Option<QuestionAnswer> {
+                self.data.pop_front()
+            }
+            fn next_answer_id(&mut self) -> Option<String> {
+                self.data.front().map(|a| a.id.clone())
+            }
+        }
+
+        // takes the stored answers, like the fast-forward mode
+        struct UiMock {}
+
+        impl QuestionaireView for UiMock {
+            fn show_proceed_screen<'a, T: Into<Option<&'a str>>>(&mut self, _id: &str, _text: &str, _help_text: T, _question_count: usize, _current: usize, p: Option<bool>) -> Result<ProceedScreenResult> {
+                Ok(ProceedScreenResult::Proceeded(p.unwrap_or(true)))
+            }
+            fn show_question_screen(&mut self, _question_entry: &QuestionEntry, _question_count: usize, p: Option<QuestionAnswerInput>) -> Result<QuestionScreenResult>{
+                Ok(QuestionScreenResult::Proceeded(p.unwrap_or(QuestionAnswerInput::String(None))))
+            }
+        }
+
+        let questionaire = Questionaire::builder()
+            .id("q")
+            .start_text("Start?")
+            .questions(vec![
+                QuestionaireEntry::Question(QuestionEntry::builder()
+                    .id("name")
+                    .query_text("Name?")
+                    .entry_type(EntryType::String(StringEntry::builder().default_value("Homer").build()))
+                    .build()),
+                QuestionaireEntry::RepeatedQuestion(RepeatedQuestionEntry::builder()
+                    .id("kids")
+                    .query_text("Name of a kid")
+                    .entry_type(EntryType::String(StringEntry::builder().build()))
+                    .build()),
+            ])
+            .build();
+        let a = |id: &str, v: Option<&str>, timed_out: bool| QuestionAnswer {
+            id: id.to_string(),
+            answer: QuestionAnswerInput::String(v.map(|s| s.to_string())),
+            timed_out,
+        };
+        let mut journal = Journal::default();
+        journal.import(&vec![a("name", Some("Homer"), true), a("kids", Some("Bart"), false), a("kids", None, true)]);
+        let mut c = QuestionaireController::new(&questionaire, UiMock {}, journal);
+        let ba = match c.run().unwrap() {
+            QuestionaireResult::Finished(ba) => ba,
+            QuestionaireResult::Canceled => panic!("unexpected cancel"),
+        };
+        match &ba.iterations[0][..] {
+            [AnswerEntry::Question(qa), AnswerEntry::RepeatedQuestion(ra)] => {
+                assert!(qa.timed_out);
+                assert_eq!(vec![QuestionAnswerInput::String(Some("Bart".to_string()))], ra.answers);
+                assert!(ra.timed_out);
+            },
+            _ => panic!("unexpected answers"),
+        }
+        assert_eq!(vec![
+            ("name".to_string(), QuestionAnswerInput::String(Some("Homer".to_string())), true),
+            ("kids".to_string(), QuestionAnswerInput::String(Some("Bart".to_string())), false),
+            ("kids".to_string(), QuestionAnswerInput::String(None), true),
+        ], c.persistence.stored[..3]);
+    }
+
+    #[test]
+    fn it_stops_when_an_answer_can_not_be_stored() {
+        use crate::questionaire::{EntryType, StringEntry};
+        use crate::QuestionAnswer;
+
+        struct FullDisk {}
+
+        impl QuestionairePersistence for FullDisk {
+            fn store_question(&mut self, _entry: &QuestionEntry, _data: &QuestionAnswerInput) -> Result<()> {
+                Err(anyhow!("No space left on device"))
+            }
+            fn load(&mut self, _s: Option<&str>) -> Result<()> {
+                Ok(())
+            }
+            fn import(&mut self, _data_to_import: &Vec<QuestionAnswer>) {
+            }
+            fn next_answer(&mut self) -> Option<QuestionAnswer> {
+                None
+            }
+            fn next_answer_id(&mut self) -> Option<String> {
+                None
+            }
+        }
+
+        struct UiMock {}
+
+        impl QuestionaireView for UiMock {
+            fn show_proceed_screen<'a, T: Into<Option<&'a str>>>(&mut self, _id: &str, _text: &str, _help_text: T, _question_count: usize, _current: usize, _p: Option<bool>) -> Result<ProceedScreenResult> {
+                Ok(ProceedScreenResult::Proceeded(true))
+            }
+            fn show_question_screen(&mut self, _question_entry: &QuestionEntry, _question_count: usize, _p: Option<QuestionAnswerInput>) -> Result<QuestionScreenResult>{
+                Ok(QuestionScreenResult::Proceeded(QuestionAnswerInput::String(Some("x".to_string()))))
+            }
+        }
+
+        let questionaire = Questionaire::builder()
+            .id("q")
+            .start_text("Start?")
+            .questions(vec![QuestionaireEntry::Question(QuestionEntry::builder()
+                .id("q1")
+                .query_text("q1")
+                .entry_type(EntryType::String(StringEntry::builder().build()))
+                .build())])
+            .build();
+        let mut ui = UiMock {};
+        let mut c = QuestionaireController::new(&questionaire, &mut ui, FullDisk {});
+        match c.run() {
+            Err(e) => assert_eq!("No space left on device", e.to_string()),
+            Ok(_) => panic!("the lost answer has to be an error"),
+        }
+    }
+
 }
@@ -0,0 +1,148 @@
+//! Encryption of the persistence journal at rest
+//!
+//! Format of an encrypted journal:
+//! `MAGIC | key kind (1 byte) | salt (16 bytes) | nonce (12 bytes) | ciphertext`
+//!
+//! The key is derived with argon2id from the passphrase or the content of a key file.
+//! The derived key is cached together with its salt, because the journal is rewritten
+//! with every answer and the derivation is expensive by intention.
+//!
+use anyhow::{anyhow, Result};
+use argon2::Argon2;
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+
+use std::fs;
+
+
+const MAGIC: &[u8] = b"TQUEST-ENC1\n";
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+const KIND_PASSPHRASE: u8 = 0;
+const KIND_KEY_FILE: u8 = 1;
+
+/// Secret that is used to encrypt the journal
+#[derive(Clone)]
+pub enum JournalKey {
+    Passphrase(String),
+    /// content of a key file
+    KeyFile(Vec<u8>),
+}
+
+impl JournalKey {
+    pub fn from_key_file(path: &str) -> Result<JournalKey> {
+        let content = fs::read(path)?;
+        if content.is_empty() {
+            return Err(anyhow!("Key file is empty: {}", path));
+        }
+        Ok(JournalKey::KeyFile(content))
+    }
+
+    fn kind(&self) -> u8 {
+        match self {
+            JournalKey::Passphrase(_) => KIND_PASSPHRASE,
+            JournalKey::KeyFile(_) => KIND_KEY_FILE,
+        }
+    }
+
+    fn secret(&self) -> &[u8] {
+        match self {
+            JournalKey::Passphrase(p) => p.as_bytes(),
+            JournalKey::KeyFile(k) => k,
+        }
+    }
+}
+
+/// Encrypts and decrypts journals with one key
+pub struct JournalCipher {
+    key: JournalKey,
+    /// salt and derived key of the last en- or decryption
+    derived: Option<([u8; SALT_LEN], Key)>,
+}
+
+impl JournalCipher {
+    pub fn new(key: JournalKey) -> Self {
+        JournalCipher { key, derived: None }
+    }
+
+    fn derive(&mut self, salt: [u8; SALT_LEN]) -> Result<Key> {
+        if let Some((s, k)) = self.derived.as_ref() {
+            if *s == salt {
+                return Ok(*k);
+            }
+        }
+        let mut key = Key::default();
+        Argon2::default()
+            .hash_password_into(self.key.secret(), &salt, &mut key)
+            .map_err(|e| anyhow!("Error while deriving the key: {}", e))?;
+        self.derived = Some((salt, key));
+        Ok(key)
+    }
+
+    pub fn encrypt(&mut self, plain: &[u8]) -> Result<Vec<u8>> {
+        let salt = if let Some((s, _)) = self.derived.as_ref() {
+            *s
+        } else {
+            let mut s = [0u8; SALT_LEN];
+            OsRng.fill_bytes(&mut s);
+            s
+        };
+        let key = self.derive(salt)?;
+        let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let cipher_text = ChaCha20Poly1305::new(&key)
+            .encrypt(&nonce, plain)
+            .map_err(|_| anyhow!("Error while encrypting the persistence file"))?;
+        let mut ret: Vec<u8> = Vec::with_capacity(MAGIC.len() + 1 + SALT_LEN + NONCE_LEN + cipher_text.len());
+        ret.extend_from_slice(MAGIC);
+        ret.push(self.key.kind());
+        ret.extend_from_slice(&salt);
+        ret.extend_from_slice(&nonce);
+        ret.extend_from_slice(&cipher_text);
+        Ok(ret)
+    }
+
+    pub fn decrypt(&mut self, data: &[u8]) -> Result<Vec<u8>> {
+        if ! is_encrypted(data) || data.len() < MAGIC.len() + 1 + SALT_LEN + NONCE_LEN {
+            return Err(anyhow!("Persistence file isn't encrypted or is damaged"));
+        }
+        let rest = &data[MAGIC.len()..];
+        if rest[0] != self.key.kind() {
+            return Err(anyhow!("Persistence file was encrypted with a different kind of key"));
+        }
+        let mut salt = [0u8; SALT_LEN];
+        salt.copy_from_slice(&rest[1..1 + SALT_LEN]);
+        let nonce = Nonce::from_slice(&rest[1 + SALT_LEN..1 + SALT_LEN + NONCE_LEN]);
+        let key = self.derive(salt)?;
+        ChaCha20Poly1305::new(&key)
+            .decrypt(nonce, &rest[1 + SALT_LEN + NONCE_LEN..])
+            .map_err(|_| anyhow!("Can't decrypt the persistence file, wrong passphrase or key"))
+    }
+}
+
+/// Checks if the given file content is an encrypted journal
+pub fn is_encrypted(data: &[u8]) -> bool {
+    data.starts_with(MAGIC)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encrypt_decrypt() {
+        let plain = "id01={\"String\":\"Test Name\"}\n".as_bytes();
+        let mut cipher = JournalCipher::new(JournalKey::Passphrase("secret".to_string()));
+        let encrypted = cipher.encrypt(plain).unwrap();
+        assert!(is_encrypted(&encrypted));
+        assert!(! encrypted.windows(9).any(|w| w == b"Test Name"));
+        // a new cipher has to derive the key from the stored salt
+        let mut cipher2 = JournalCipher::new(JournalKey::Passphrase("secret".to_string()));
+        assert_eq!(plain, cipher2.decrypt(&encrypted).unwrap().as_slice());
+
+        let mut wrong = JournalCipher::new(JournalKey::Passphrase("wrong".to_string()));
+        assert!(wrong.decrypt(&encrypted).is_err());
+        let mut wrong_kind = JournalCipher::new(JournalKey::KeyFile(b"secret".to_vec()));
+        assert!(wrong_kind.decrypt(&encrypted).is_err());
+    }
+}
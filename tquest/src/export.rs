@@ -0,0 +1,381 @@
+//! Exporters, that turn the answers of a finished questionaire into other formats
+//!
+pub mod csv;
+pub mod env;
+pub mod report;
+#[cfg(feature = "templates")]
+pub mod template;
+
+use crate::questionaire::{AnswerEntry, BlockAnswer, QuestionAnswerInput};
+use serde_json::{Map, Number, Value};
+
+use std::fmt::{Display, Formatter};
+
+
+/// One element of the path to an answer. Blocks and repeated questions
+/// have an index for the iteration or the repetition.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PathSegment {
+    pub id: String,
+    pub index: Option<usize>,
+}
+
+impl Display for PathSegment {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.index {
+            Some(i) => write!(f, "{}[{}]", self.id, i),
+            None => write!(f, "{}", self.id),
+        }
+    }
+}
+
+/// Text of entries, that were skipped because a proceed query was answered with 'no'
+pub const SKIPPED_TEXT: &str = "(skipped)";
+
+/// Answer of a single question, together with the path of blocks and
+/// iterations that lead to it. Entries skipped by a proceed query have
+/// `skipped` set and no answer.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlatAnswer {
+    pub path: Vec<PathSegment>,
+    pub answer: QuestionAnswerInput,
+    pub skipped: bool,
+}
+
+impl FlatAnswer {
+    /// Qualified id, e.g. `id04[0].id04_01`
+    pub fn qualified_id(&self) -> String {
+        join_path(&self.path)
+    }
+
+    /// The answer as text, or `(skipped)` for skipped entries
+    pub fn answer_text(&self) -> String {
+        if self.skipped {
+            SKIPPED_TEXT.to_string()
+        } else {
+            self.answer.to_string()
+        }
+    }
+}
+
+pub fn join_path(path: &[PathSegment]) -> String {
+    path.iter().map(|s| s.to_string()).collect::<Vec<String>>().join(".")
+}
+
+/// Flattens the answer tree of a questionaire in the order of the answers. The
+/// init block itself is not part of the paths.
+pub fn flatten(answer: &BlockAnswer) -> Vec<FlatAnswer> {
+    fn flatten_entries(entries: &[AnswerEntry], path: &mut Vec<PathSegment>, ret: &mut Vec<FlatAnswer>) {
+        for e in entries {
+            match e {
+                AnswerEntry::Question(qa) => {
+                    path.push(PathSegment { id: qa.id.clone(), index: None });
+                    ret.push(FlatAnswer { path: path.clone(), answer: qa.answer.clone(), skipped: false });
+                    path.pop();
+                },
+                AnswerEntry::RepeatedQuestion(rq) => {
+                    for (i, a) in rq.answers.iter().enumerate() {
+                        path.push(PathSegment { id: rq.id.clone(), index: Some(i) });
+                        ret.push(FlatAnswer { path: path.clone(), answer: a.clone(), skipped: false });
+                        path.pop();
+                    }
+                },
+                AnswerEntry::Skipped(id) => {
+                    path.push(PathSegment { id: id.clone(), index: None });
+                    ret.push(FlatAnswer { path: path.clone(), answer: QuestionAnswerInput::None, skipped: true });
+                    path.pop();
+                },
+                AnswerEntry::Block(ba) => {
+                    for (i, iteration) in ba.iterations.iter().enumerate() {
+                        path.push(PathSegment { id: ba.id.clone(), index: Some(i) });
+                        flatten_entries(iteration, path, ret);
+                        path.pop();
+                    }
+                },
+            }
+        }
+    }
+
+    let mut ret: Vec<FlatAnswer> = Vec::new();
+    let mut path: Vec<PathSegment> = Vec::new();
+    for iteration in &answer.iterations {
+        flatten_entries(iteration, &mut path, &mut ret);
+    }
+    ret
+}
+
+/// Converts the answers into a JSON value, e.g. for templates. Questions become
+/// fields with their answer as value, blocks become lists of their iterations
+/// and repeated questions lists of their answers. Answers, that were taken after
+/// a timeout, and repeated questions, that a timeout ended, get an additional
+/// `<id>_timed_out: true` field.
+pub fn answers_to_json(answer: &BlockAnswer) -> Value {
+    fn input_to_json(a: &QuestionAnswerInput) -> Value {
+        match a {
+            QuestionAnswerInput::String(Some(v)) => Value::String(v.clone()),
+            QuestionAnswerInput::Option(Some(v)) => Value::String(v.clone()),
+            QuestionAnswerInput::Int(Some(v)) => Value::Number((*v).into()),
+            QuestionAnswerInput::Float(Some(v)) => {
+                Number::from_f64(*v as f64).map(Value::Number).unwrap_or(Value::Null)
+            },
+            QuestionAnswerInput::Bool(Some(v)) => Value::Bool(*v),
+            _ => Value::Null,
+        }
+    }
+
+    fn iteration_to_json(entries: &[AnswerEntry], ret: &mut Map<String, Value>) {
+        for e in entries {
+            match e {
+                AnswerEntry::Question(qa) => {
+                    ret.insert(qa.id.clone(), input_to_json(&qa.answer));
+                    if qa.timed_out {
+                        ret.insert(format!("{}_timed_out", qa.id), Value::Bool(true));
+                    }
+                },
+                AnswerEntry::RepeatedQuestion(rq) => {
+                    ret.insert(rq.id.clone(), Value::Array(rq.answers.iter().map(input_to_json).collect()));
+                    if rq.timed_out {
+                        ret.insert(format!("{}_timed_out", rq.id), Value::Bool(true));
+                    }
+                },
+                AnswerEntry::Block(ba) => {
+                    let iterations: Vec<Value> = ba.iterations.iter().map(|i| {
+                        let mut m = Map::new();
+                        iteration_to_json(i, &mut m);
+                        Value::Object(m)
+                    }).collect();
+                    ret.insert(ba.id.clone(), Value::Array(iterations));
+                },
+                AnswerEntry::Skipped(id) => {
+                    ret.insert(id.clone(), Value::Null);
+                },
+            }
+        }
+    }
+
+    let mut ret = Map::new();
+    for iteration in &answer.iterations {
+        iteration_to_json(iteration, &mut ret);
+    }
+    Value::Object(ret)
+}
+
+#[cfg(test)]
+pub(crate) mod test_data {
+    use crate::questionaire::{AnswerEntry, BlockAnswer, EntryType, QuestionAnswer, QuestionAnswerInput, QuestionEntry,
+        Questionaire, QuestionaireEntry, RepeatedQuestionAnswers, StringEntry, SubBlock};
+
+    fn q(id: &str, v: &str) -> AnswerEntry {
+        AnswerEntry::Question(QuestionAnswer {
+            id: id.to_string(),
+            answer: QuestionAnswerInput::String(Some(v.to_string())),
+            timed_out: false,
+        })
+    }
+
+    fn job(company: &str, title: &str, start: &str, end: Option<(&str, &str)>) -> Vec<AnswerEntry> {
+        let end_block = if let Some((end_date, reason)) = end {
+            BlockAnswer {
+                id: "id04_04".to_string(),
+                iterations: vec![vec![
+                    q("id04_04_01", end_date),
+                    AnswerEntry::Question(QuestionAnswer {
+                        id: "id04_04_02".to_string(),
+                        answer: QuestionAnswerInput::Option(Some(reason.to_string())),
+                        timed_out: false,
+                    }),
+                ]],
+            }
+        } else {
+            BlockAnswer { id: "id04_04".to_string(), iterations: vec![] }
+        };
+        vec![q("id04_01", company), q("id04_02", title), q("id04_03", start), AnswerEntry::Block(end_block)]
+    }
+
+    /// answers to the complex test questionaire
+    pub fn homer() -> BlockAnswer {
+        BlockAnswer {
+            id: "id00".to_string(),
+            iterations: vec![vec![
+                q("id01", "Homer"),
+                q("id02", "1956-03-12"),
+                AnswerEntry::Block(BlockAnswer { id: "id03".to_string(), iterations: vec![] }),
+                AnswerEntry::Block(BlockAnswer {
+                    id: "id04".to_string(),
+                    iterations: vec![
+                        job("Springfield Nuclear Power Plant", "Nuclear safety inspector", "1986", None),
+                    ],
+                }),
+            ]],
+        }
+    }
+
+    pub fn marge() -> BlockAnswer {
+        BlockAnswer {
+            id: "id00".to_string(),
+            iterations: vec![vec![
+                q("id01", "Marge"),
+                q("id02", "1956-10-01"),
+                AnswerEntry::Block(BlockAnswer {
+                    id: "id03".to_string(),
+                    iterations: vec![vec![
+                        AnswerEntry::Block(BlockAnswer {
+                            id: "id03_01".to_string(),
+                            iterations: vec![
+                                vec![q("id03_01_01", "Patty"), q("id03_01_02", "1948-01-01")],
+                                vec![q("id03_01_01", "Selma"), q("id03_01_02", "1948-01-01")],
+                            ],
+                        }),
+                        AnswerEntry::Block(BlockAnswer { id: "id03_02".to_string(), iterations: vec![] }),
+                    ]],
+                }),
+                AnswerEntry::Block(BlockAnswer {
+                    id: "id04".to_string(),
+                    iterations: vec![
+                        job("Springfield Police", "Officer", "1994", Some(("1994", "I left by my own"))),
+                        job("Pretzel Wagon", "Owner", "1995", Some(("1995", "Other reason"))),
+                    ],
+                }),
+            ]],
+        }
+    }
+
+    /// answers with a question, that timed out, and a repeated question, that a timeout ended
+    pub fn timed_out() -> BlockAnswer {
+        BlockAnswer {
+            id: "q".to_string(),
+            iterations: vec![vec![
+                AnswerEntry::Question(QuestionAnswer {
+                    id: "name".to_string(),
+                    answer: QuestionAnswerInput::String(Some("Homer".to_string())),
+                    timed_out: true,
+                }),
+                q("city", "Springfield"),
+                AnswerEntry::RepeatedQuestion(RepeatedQuestionAnswers {
+                    id: "kids".to_string(),
+                    answers: vec![QuestionAnswerInput::String(Some("Bart".to_string()))],
+                    timed_out: true,
+                }),
+            ]],
+        }
+    }
+
+    /// Questionaire with an info text and a proceed query, that skips a question
+    /// and a block, together with its answers
+    pub fn info_and_skipped() -> (Questionaire, BlockAnswer) {
+        let question = |id: &str, entry_type: EntryType| QuestionaireEntry::Question(QuestionEntry::builder()
+            .id(id)
+            .query_text(&format!("Text of {}", id))
+            .entry_type(entry_type)
+            .build());
+        let string = || EntryType::String(StringEntry::builder().build());
+        let questionaire = Questionaire::builder()
+            .id("q")
+            .start_text("Start?")
+            .questions(vec![
+                question("i1", EntryType::InfoTxt),
+                question("pq", EntryType::ProceedQuery(2)),
+                question("q1", string()),
+                QuestionaireEntry::Block(SubBlock::builder()
+                    .id("b1")
+                    .start_text("Block b1")
+                    .entries(vec![question("b1_01", string())])
+                    .build()),
+                question("q2", string()),
+            ])
+            .build();
+        let answer = BlockAnswer {
+            id: "q".to_string(),
+            iterations: vec![vec![
+                AnswerEntry::Question(QuestionAnswer {
+                    id: "pq".to_string(),
+                    answer: QuestionAnswerInput::Bool(Some(false)),
+                    timed_out: false,
+                }),
+                AnswerEntry::Skipped("q1".to_string()),
+                AnswerEntry::Skipped("b1".to_string()),
+                q("q2", "Bart"),
+            ]],
+        };
+        (questionaire, answer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_flatten() {
+        let flat = flatten(&test_data::marge());
+        let ids: Vec<String> = flat.iter().map(|f| f.qualified_id()).collect();
+        assert_eq!(vec![
+            "id01",
+            "id02",
+            "id03[0].id03_01[0].id03_01_01",
+            "id03[0].id03_01[0].id03_01_02",
+            "id03[0].id03_01[1].id03_01_01",
+            "id03[0].id03_01[1].id03_01_02",
+            "id04[0].id04_01",
+            "id04[0].id04_02",
+            "id04[0].id04_03",
+            "id04[0].id04_04[0].id04_04_01",
+            "id04[0].id04_04[0].id04_04_02",
+            "id04[1].id04_01",
+            "id04[1].id04_02",
+            "id04[1].id04_03",
+            "id04[1].id04_04[0].id04_04_01",
+            "id04[1].id04_04[0].id04_04_02",
+        ], ids);
+        assert_eq!("Selma", flat[4].answer.to_string());
+    }
+
+    #[test]
+    fn test_answers_to_json() {
+        let v = answers_to_json(&test_data::marge());
+        assert_eq!("Marge", v["id01"]);
+        assert_eq!("Selma", v["id03"][0]["id03_01"][1]["id03_01_01"]);
+        assert_eq!(0, v["id03"][0]["id03_02"].as_array().unwrap().len());
+        assert_eq!(2, v["id04"].as_array().unwrap().len());
+        assert_eq!("Other reason", v["id04"][1]["id04_04"][0]["id04_04_02"]);
+        assert!(v.get("id01_timed_out").is_none());
+
+        let v = answers_to_json(&test_data::timed_out());
+        assert_eq!("Homer", v["name"]);
+        assert_eq!(true, v["name_timed_out"]);
+        assert!(v.get("city_timed_out").is_none());
+        assert_eq!(true, v["kids_timed_out"]);
+    }
+
+    #[test]
+    fn test_info_texts_and_skipped_entries() {
+        let (questionaire, answer) = test_data::info_and_skipped();
+        let flat = flatten(&answer);
+        let ids: Vec<String> = flat.iter().map(|f| f.qualified_id()).collect();
+        assert_eq!(vec!["pq", "q1", "b1", "q2"], ids);
+        assert!(! flat[0].skipped && flat[1].skipped && flat[2].skipped && ! flat[3].skipped);
+        assert_eq!("(skipped)", flat[1].answer_text());
+
+        let v = answers_to_json(&answer);
+        assert_eq!(false, v["pq"]);
+        assert!(v["q1"].is_null());
+        assert_eq!("Bart", v["q2"]);
+
+        let md = report::render_markdown(&questionaire, &answer);
+        assert!(! md.contains("Text of i1"), "{}", md);
+        assert!(md.contains("**Text of q1**  \n(skipped)\n\n"), "{}", md);
+        assert!(md.contains("## Block b1\n\n_(skipped)_\n\n"), "{}", md);
+        assert!(md.contains("**Text of q2**  \nBart\n\n"), "{}", md);
+
+        let csv = csv::CsvExporter::new().export([&answer]);
+        assert_eq!("pq,q1,b1,q2\r\nfalse,(skipped),(skipped),Bart\r\n", csv);
+
+        let env = env::EnvExporter::new().entries(&answer);
+        assert_eq!(vec![
+            ("PQ".to_string(), "false".to_string()),
+            ("Q1_SKIPPED".to_string(), "1".to_string()),
+            ("B1_SKIPPED".to_string(), "1".to_string()),
+            ("Q2".to_string(), "Bart".to_string()),
+        ], env);
+    }
+}
@@ -0,0 +1,214 @@
+//! CSV export of questionaire results
+//!
+//! Every question, qualified by its block and iteration path, becomes a column.
+//! Each result is one row. Alternatively the iterations of one looping block
+//! can be exported as own rows, then the other answers are repeated in every row.
+//! Cells, that a spreadsheet would take as formula and that are no numbers, get
+//! a leading `'`. Entries, that were skipped by a proceed query, have the value
+//! `(skipped)`.
+//!
+use crate::export::{flatten, join_path, FlatAnswer, PathSegment};
+use crate::questionaire::BlockAnswer;
+use anyhow::Result;
+
+use std::collections::HashMap;
+use std::io::Write;
+
+
+/// How the iterations of looping blocks are exported
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum CsvLoopMode {
+    /// Every iteration gets its own columns, e.g. `id04[0].id04_01`, `id04[1].id04_01`
+    #[default]
+    IndexedColumns,
+    /// Every iteration of the block with the given id becomes its own row. The
+    /// columns of the block have no index, e.g. `id04.id04_01`
+    RowPerIteration(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct CsvExporter {
+    loop_mode: CsvLoopMode,
+    delimiter: char,
+}
+
+impl Default for CsvExporter {
+    fn default() -> Self {
+        CsvExporter {
+            loop_mode: CsvLoopMode::default(),
+            delimiter: ',',
+        }
+    }
+}
+
+/// One row of the table, column name to value
+type Row = HashMap<String, String>;
+
+impl CsvExporter {
+    pub fn new() -> Self {
+        CsvExporter::default()
+    }
+
+    pub fn loop_mode(&mut self, v: CsvLoopMode) -> &mut Self {
+        self.loop_mode = v;
+        self
+    }
+
+    pub fn delimiter(&mut self, v: char) -> &mut Self {
+        self.delimiter = v;
+        self
+    }
+
+    /// Returns the CSV text, with a header line and the rows of all results
+    pub fn export<'a, I: IntoIterator<Item = &'a BlockAnswer>>(&self, results: I) -> String {
+        let mut columns: Vec<String> = Vec::new();
+        let mut rows: Vec<Row> = Vec::new();
+        for r in results {
+            for row in self.rows_of_result(r, &mut columns) {
+                rows.push(row);
+            }
+        }
+
+        let mut ret = String::new();
+        ret.push_str(&self.line(columns.iter().map(|c| c.as_str())));
+        for row in &rows {
+            ret.push_str(&self.line(columns.iter().map(|c| row.get(c).map(|v| v.as_str()).unwrap_or(""))));
+        }
+        ret
+    }
+
+    pub fn write<'a, I: IntoIterator<Item = &'a BlockAnswer>, W: Write>(&self, results: I, writer: &mut W) -> Result<()> {
+        writer.write_all(self.export(results).as_bytes())?;
+        Ok(())
+    }
+
+    fn rows_of_result(&self, result: &BlockAnswer, columns: &mut Vec<String>) -> Vec<Row> {
+        fn add(row: &mut Row, columns: &mut Vec<String>, column: String, value: String) {
+            if ! columns.contains(&column) {
+                columns.push(column.clone());
+            }
+            row.insert(column, value);
+        }
+
+        let flat = flatten(result);
+        match &self.loop_mode {
+            CsvLoopMode::IndexedColumns => {
+                let mut row = Row::new();
+                for f in flat {
+                    add(&mut row, columns, f.qualified_id(), f.answer_text());
+                }
+                vec![row]
+            },
+            CsvLoopMode::RowPerIteration(block_id) => {
+                let mut common = Row::new();
+                let mut iterations: Vec<Row> = Vec::new();
+                for f in flat {
+                    if let Some((index, column)) = split_at_block(&f, block_id) {
+                        while iterations.len() <= index {
+                            iterations.push(Row::new());
+                        }
+                        add(&mut iterations[index], columns, column, f.answer_text());
+                    } else {
+                        add(&mut common, columns, f.qualified_id(), f.answer_text());
+                    }
+                }
+                if iterations.is_empty() {
+                    return vec![common];
+                }
+                iterations.into_iter().map(|mut r| {
+                    for (k, v) in &common {
+                        r.insert(k.clone(), v.clone());
+                    }
+                    r
+                }).collect()
+            },
+        }
+    }
+
+    fn line<'a, I: Iterator<Item = &'a str>>(&self, values: I) -> String {
+        let mut ret = values.map(|v| self.quote(v)).collect::<Vec<String>>().join(&self.delimiter.to_string());
+        ret.push_str("\r\n");
+        ret
+    }
+
+    fn quote(&self, v: &str) -> String {
+        // e.g. `=HYPERLINK(...)` in an answer must not run as formula, but
+        // negative numbers stay numbers
+        let is_number = v.parse::<i64>().is_ok() || v.parse::<f64>().is_ok();
+        let v = if v.starts_with(['=', '+', '-', '@', '\t', '\r']) && ! is_number {
+            format!("'{}", v)
+        } else {
+            v.to_string()
+        };
+        if v.contains(self.delimiter) || v.contains('"') || v.contains('\n') || v.contains('\r') {
+            format!("\"{}\"", v.replace('"', "\"\""))
+        } else {
+            v
+        }
+    }
+}
+
+/// If the answer belongs to an iteration of the block, the iteration index and the
+/// column name without the index of the block are returned.
+fn split_at_block(f: &FlatAnswer, block_id: &str) -> Option<(usize, String)> {
+    let pos = f.path.iter().position(|s| s.id == block_id && s.index.is_some())?;
+    let index = f.path[pos].index?;
+    let mut path: Vec<PathSegment> = f.path.clone();
+    path[pos].index = None;
+    Some((index, join_path(&path)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::test_data;
+
+    #[test]
+    fn test_indexed_columns() {
+        let results = vec![test_data::homer(), test_data::marge()];
+        let csv = CsvExporter::new().export(&results);
+        let lines: Vec<&str> = csv.split("\r\n").collect();
+        assert_eq!(4, lines.len());
+        assert_eq!("", lines[3]);
+        let header: Vec<&str> = lines[0].split(',').collect();
+        assert_eq!("id01", header[0]);
+        assert_eq!("id04[0].id04_01", header[2]);
+        // columns, that only exist in the second result, are appended
+        assert!(header.contains(&"id03[0].id03_01[1].id03_01_01"));
+        assert!(header.contains(&"id04[1].id04_04[0].id04_04_02"));
+        assert!(lines[1].starts_with("Homer,1956-03-12,Springfield Nuclear Power Plant,Nuclear safety inspector,1986,"));
+        assert!(lines[2].starts_with("Marge,1956-10-01,Springfield Police,Officer,1994,"));
+        assert_eq!(header.len(), lines[1].split(',').count());
+    }
+
+    #[test]
+    fn test_row_per_iteration() {
+        let results = vec![test_data::homer(), test_data::marge()];
+        let csv = CsvExporter::new()
+            .loop_mode(CsvLoopMode::RowPerIteration("id04".to_string()))
+            .delimiter(';')
+            .export(&results);
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(4, lines.len());
+        assert_eq!("id01;id02;id04.id04_01;id04.id04_02;id04.id04_03;id03[0].id03_01[0].id03_01_01;id03[0].id03_01[0].id03_01_02;id03[0].id03_01[1].id03_01_01;id03[0].id03_01[1].id03_01_02;id04.id04_04[0].id04_04_01;id04.id04_04[0].id04_04_02", lines[0]);
+        assert_eq!("Homer;1956-03-12;Springfield Nuclear Power Plant;Nuclear safety inspector;1986;;;;;;", lines[1]);
+        assert_eq!("Marge;1956-10-01;Springfield Police;Officer;1994;Patty;1948-01-01;Selma;1948-01-01;1994;I left by my own", lines[2]);
+        assert_eq!("Marge;1956-10-01;Pretzel Wagon;Owner;1995;Patty;1948-01-01;Selma;1948-01-01;1995;Other reason", lines[3]);
+    }
+
+    #[test]
+    fn test_quote() {
+        let e = CsvExporter::new();
+        assert_eq!("abc", e.quote("abc"));
+        assert_eq!("\"a,b\"", e.quote("a,b"));
+        assert_eq!("\"a \"\"b\"\"\"", e.quote("a \"b\""));
+        assert_eq!("\"a\nb\"", e.quote("a\nb"));
+        assert_eq!("'=1+2", e.quote("=1+2"));
+        assert_eq!("-5", e.quote("-5"));
+        assert_eq!("-1.5", e.quote("-1.5"));
+        assert_eq!("+3", e.quote("+3"));
+        assert_eq!("'-1+2", e.quote("-1+2"));
+        assert_eq!("\"'@SUM(A1,A2)\"", e.quote("@SUM(A1,A2)"));
+        assert_eq!("a=b", e.quote("a=b"));
+    }
+}
@@ -0,0 +1,232 @@
+//! Export of the answers as shell environment assignments
+//!
+//! The keys are derived from the question ids. Blocks and repeated questions add
+//! their index to the key, e.g. `ID04_1_ID04_01`, and get an additional `_COUNT`
+//! entry with the number of iterations, to loop over them in a script. Entries,
+//! that were skipped by a proceed query, get a `_SKIPPED=1` entry instead. Answers,
+//! that were taken after a timeout, and repeated questions, that a timeout ended,
+//! get an additional `_TIMED_OUT=1` entry.
+//!
+use crate::export::PathSegment;
+use crate::questionaire::{AnswerEntry, BlockAnswer};
+use anyhow::Result;
+
+use std::io::Write;
+
+
+/// Case of the generated keys
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum KeyCase {
+    #[default]
+    Upper,
+    Lower,
+    /// keeps the case of the question ids
+    Keep,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum EnvFormat {
+    /// `export KEY='value'` lines, that can be used with `eval`
+    #[default]
+    Export,
+    /// `KEY="value"` lines for `.env` files
+    DotEnv,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct EnvExporter {
+    prefix: String,
+    case: KeyCase,
+    format: EnvFormat,
+}
+
+impl EnvExporter {
+    pub fn new() -> Self {
+        EnvExporter::default()
+    }
+
+    /// Prefix for all keys, e.g. `MYTOOL_`
+    pub fn prefix(&mut self, v: &str) -> &mut Self {
+        self.prefix = v.to_string();
+        self
+    }
+
+    pub fn case(&mut self, v: KeyCase) -> &mut Self {
+        self.case = v;
+        self
+    }
+
+    pub fn format(&mut self, v: EnvFormat) -> &mut Self {
+        self.format = v;
+        self
+    }
+
+    /// Returns the key value pairs in the order of the answers
+    pub fn entries(&self, answer: &BlockAnswer) -> Vec<(String, String)> {
+        let mut ret: Vec<(String, String)> = Vec::new();
+        let mut path: Vec<PathSegment> = Vec::new();
+        for iteration in &answer.iterations {
+            self.add_entries(iteration, &mut path, &mut ret);
+        }
+        ret
+    }
+
+    fn add_entries(&self, entries: &[AnswerEntry], path: &mut Vec<PathSegment>, ret: &mut Vec<(String, String)>) {
+        for e in entries {
+            match e {
+                AnswerEntry::Question(qa) => {
+                    path.push(PathSegment { id: qa.id.clone(), index: None });
+                    ret.push((self.key(path, None), qa.answer.to_string()));
+                    if qa.timed_out {
+                        ret.push((self.key(path, Some("TIMED_OUT")), "1".to_string()));
+                    }
+                    path.pop();
+                },
+                AnswerEntry::RepeatedQuestion(rq) => {
+                    path.push(PathSegment { id: rq.id.clone(), index: None });
+                    ret.push((self.key(path, Some("COUNT")), rq.answers.len().to_string()));
+                    if rq.timed_out {
+                        ret.push((self.key(path, Some("TIMED_OUT")), "1".to_string()));
+                    }
+                    path.pop();
+                    for (i, a) in rq.answers.iter().enumerate() {
+                        path.push(PathSegment { id: rq.id.clone(), index: Some(i) });
+                        ret.push((self.key(path, None), a.to_string()));
+                        path.pop();
+                    }
+                },
+                AnswerEntry::Block(ba) => {
+                    path.push(PathSegment { id: ba.id.clone(), index: None });
+                    ret.push((self.key(path, Some("COUNT")), ba.iterations.len().to_string()));
+                    path.pop();
+                    for (i, iteration) in ba.iterations.iter().enumerate() {
+                        path.push(PathSegment { id: ba.id.clone(), index: Some(i) });
+                        self.add_entries(iteration, path, ret);
+                        path.pop();
+                    }
+                },
+                AnswerEntry::Skipped(id) => {
+                    path.push(PathSegment { id: id.clone(), index: None });
+                    ret.push((self.key(path, Some("SKIPPED")), "1".to_string()));
+                    path.pop();
+                },
+            }
+        }
+    }
+
+    fn key(&self, path: &[PathSegment], suffix: Option<&str>) -> String {
+        let mut parts: Vec<String> = Vec::new();
+        for s in path {
+            parts.push(s.id.clone());
+            if let Some(i) = s.index {
+                parts.push(i.to_string());
+            }
+        }
+        if let Some(s) = suffix {
+            parts.push(s.to_string());
+        }
+        let raw = format!("{}{}", self.prefix, parts.join("_"));
+        let mut key: String = raw.chars()
+            .map(|c| if c.is_ascii_alphanumeric() || c == '_' { c } else { '_' })
+            .collect();
+        key = match self.case {
+            KeyCase::Upper => key.to_uppercase(),
+            KeyCase::Lower => key.to_lowercase(),
+            KeyCase::Keep => key,
+        };
+        if key.starts_with(|c: char| c.is_ascii_digit()) {
+            key.insert(0, '_');
+        }
+        key
+    }
+
+    /// Returns the lines for all answers
+    pub fn export(&self, answer: &BlockAnswer) -> String {
+        let mut ret = String::new();
+        for (k, v) in self.entries(answer) {
+            match self.format {
+                EnvFormat::Export => ret.push_str(&format!("export {}={}\n", k, shell_quote(&v))),
+                EnvFormat::DotEnv => ret.push_str(&format!("{}={}\n", k, dotenv_quote(&v))),
+            }
+        }
+        ret
+    }
+
+    pub fn write<W: Write>(&self, answer: &BlockAnswer, writer: &mut W) -> Result<()> {
+        writer.write_all(self.export(answer).as_bytes())?;
+        Ok(())
+    }
+}
+
+/// Quotes a value for POSIX shells. Inside single quotes nothing is expanded,
+/// only the single quote itself has to be replaced.
+pub fn shell_quote(v: &str) -> String {
+    format!("'{}'", v.replace('\'', "'\\''"))
+}
+
+fn dotenv_quote(v: &str) -> String {
+    let mut ret = String::from("\"");
+    for c in v.chars() {
+        match c {
+            '\\' => ret.push_str("\\\\"),
+            '"' => ret.push_str("\\\""),
+            '$' => ret.push_str("\\$"),
+            '`' => ret.push_str("\\`"),
+            '\n' => ret.push_str("\\n"),
+            '\r' => ret.push_str("\\r"),
+            _ => ret.push(c),
+        }
+    }
+    ret.push('"');
+    ret
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::test_data;
+
+    #[test]
+    fn test_export() {
+        let env = EnvExporter::new().prefix("tq_").export(&test_data::marge());
+        let lines: Vec<&str> = env.lines().collect();
+        assert_eq!("export TQ_ID01='Marge'", lines[0]);
+        assert_eq!("export TQ_ID02='1956-10-01'", lines[1]);
+        assert_eq!("export TQ_ID03_COUNT='1'", lines[2]);
+        assert_eq!("export TQ_ID03_0_ID03_01_COUNT='2'", lines[3]);
+        assert_eq!("export TQ_ID03_0_ID03_01_1_ID03_01_01='Selma'", lines[6]);
+        assert!(lines.contains(&"export TQ_ID04_COUNT='2'"));
+        assert!(lines.contains(&"export TQ_ID04_1_ID04_04_0_ID04_04_02='Other reason'"));
+    }
+
+    #[test]
+    fn test_dotenv_lower() {
+        let env = EnvExporter::new()
+            .case(KeyCase::Lower)
+            .format(EnvFormat::DotEnv)
+            .export(&test_data::homer());
+        let lines: Vec<&str> = env.lines().collect();
+        assert_eq!("id01=\"Homer\"", lines[0]);
+        assert_eq!("id03_count=\"0\"", lines[2]);
+        assert_eq!("id04_0_id04_04_count=\"0\"", lines[lines.len() - 1]);
+    }
+
+    #[test]
+    fn test_timed_out() {
+        let env = EnvExporter::new().export(&test_data::timed_out());
+        assert_eq!("export NAME='Homer'\n\
+            export NAME_TIMED_OUT='1'\n\
+            export CITY='Springfield'\n\
+            export KIDS_COUNT='1'\n\
+            export KIDS_TIMED_OUT='1'\n\
+            export KIDS_0='Bart'\n", env);
+    }
+
+    #[test]
+    fn test_quoting() {
+        assert_eq!("'it'\\''s $HOME'", shell_quote("it's $HOME"));
+        assert_eq!("\"a \\\"b\\\" \\$c\\nd\"", dotenv_quote("a \"b\" $c\nd"));
+        let e = EnvExporter::new();
+        assert_eq!("_1A_B", e.key(&[PathSegment { id: "1a-b".to_string(), index: None }], None));
+    }
+}
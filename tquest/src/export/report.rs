@@ -0,0 +1,246 @@
+//! Readable reports of a finished questionaire as Markdown or HTML
+//!
+//! The reports walk the questionaire definition together with the answers, so
+//! they show the question texts, block headings from the `start_text` of the
+//! blocks and numbered loop iterations. Info texts are left out, entries skipped
+//! by a proceed query are marked as `(skipped)`.
+//!
+use crate::export::SKIPPED_TEXT;
+use crate::questionaire::{AnswerEntry, BlockAnswer, EntryType, QuestionAnswerInput, Questionaire, QuestionaireEntry, SubBlock};
+
+
+/// Renderer independent content of a report
+#[derive(Debug, PartialEq)]
+enum ReportNode {
+    Heading(usize, String),
+    Answer(String, Vec<String>),
+    Note(String),
+}
+
+const NOT_ANSWERED: &str = "-";
+const SKIPPED: &str = "No";
+
+/// Renders the answers of a questionaire as Markdown
+pub fn render_markdown(questionaire: &Questionaire, answer: &BlockAnswer) -> String {
+    let mut ret = String::new();
+    for n in report_nodes(questionaire, answer) {
+        match n {
+            ReportNode::Heading(level, text) => {
+                ret.push_str(&format!("{} {}\n\n", "#".repeat(level.min(6)), escape_markdown(&text)));
+            },
+            ReportNode::Answer(question, answers) => {
+                ret.push_str(&format!("**{}**  \n", escape_markdown(&question)));
+                let a: Vec<String> = answers.iter().map(|a| escape_markdown(a)).collect();
+                ret.push_str(&a.join("  \n"));
+                ret.push_str("\n\n");
+            },
+            ReportNode::Note(text) => {
+                ret.push_str(&format!("_{}_\n\n", escape_markdown(&text)));
+            },
+        }
+    }
+    ret
+}
+
+/// Renders the answers of a questionaire as self-contained HTML page
+pub fn render_html(questionaire: &Questionaire, answer: &BlockAnswer) -> String {
+    let mut ret = String::new();
+    ret.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+    ret.push_str(&format!("<title>{}</title>\n", escape_html(&title(questionaire, answer))));
+    ret.push_str("<style>\n");
+    ret.push_str("body { font-family: sans-serif; max-width: 50em; margin: 2em auto; padding: 0 1em; color: #222; }\n");
+    ret.push_str("dt { font-weight: bold; margin-top: 0.8em; }\n");
+    ret.push_str("dd { margin-left: 1.5em; }\n");
+    ret.push_str(".note { font-style: italic; color: #666; }\n");
+    ret.push_str("</style>\n</head>\n<body>\n");
+    let mut in_list = false;
+    for n in report_nodes(questionaire, answer) {
+        match n {
+            ReportNode::Answer(question, answers) => {
+                if ! in_list {
+                    ret.push_str("<dl>\n");
+                    in_list = true;
+                }
+                ret.push_str(&format!("<dt>{}</dt>\n", escape_html(&question)));
+                for a in answers {
+                    ret.push_str(&format!("<dd>{}</dd>\n", escape_html(&a)));
+                }
+            },
+            other => {
+                if in_list {
+                    ret.push_str("</dl>\n");
+                    in_list = false;
+                }
+                match other {
+                    ReportNode::Heading(level, text) => {
+                        let l = level.min(6);
+                        ret.push_str(&format!("<h{}>{}</h{}>\n", l, escape_html(&text), l));
+                    },
+                    ReportNode::Note(text) => {
+                        ret.push_str(&format!("<p class=\"note\">{}</p>\n", escape_html(&text)));
+                    },
+                    ReportNode::Answer(_, _) => {},
+                }
+            },
+        }
+    }
+    if in_list {
+        ret.push_str("</dl>\n");
+    }
+    ret.push_str("</body>\n</html>\n");
+    ret
+}
+
+fn title(questionaire: &Questionaire, answer: &BlockAnswer) -> String {
+    if questionaire.title.is_empty() {
+        answer.id.clone()
+    } else {
+        questionaire.title.clone()
+    }
+}
+
+fn report_nodes(questionaire: &Questionaire, answer: &BlockAnswer) -> Vec<ReportNode> {
+    let mut ret: Vec<ReportNode> = vec![ReportNode::Heading(1, title(questionaire, answer))];
+    walk_block(&questionaire.init_block, answer, 1, &mut ret);
+    ret
+}
+
+/// Walks the iterations of a block. `level` is the heading level of the block itself.
+fn walk_block(block: &SubBlock, answer: &BlockAnswer, level: usize, nodes: &mut Vec<ReportNode>) {
+    for (i, iteration) in answer.iterations.iter().enumerate() {
+        if block.loop_over_entries {
+            nodes.push(ReportNode::Heading(level + 1, format!("{}.", i + 1)));
+            walk_entries(&block.entries, iteration, level + 1, nodes);
+        } else {
+            walk_entries(&block.entries, iteration, level, nodes);
+        }
+    }
+}
+
+fn walk_entries(entries: &[QuestionaireEntry], answers: &[AnswerEntry], level: usize, nodes: &mut Vec<ReportNode>) {
+    // answers are in the same order as the entries, but entries can be without answer
+    let mut answer_pos = 0;
+    let mut next_answer = |id: &str| -> Option<&AnswerEntry> {
+        let a = answers.get(answer_pos)?;
+        let answer_id = match a {
+            AnswerEntry::Question(qa) => &qa.id,
+            AnswerEntry::RepeatedQuestion(ra) => &ra.id,
+            AnswerEntry::Block(ba) => &ba.id,
+            AnswerEntry::Skipped(id) => id,
+        };
+        if answer_id == id {
+            answer_pos += 1;
+            Some(a)
+        } else {
+            None
+        }
+    };
+
+    for e in entries {
+        match e {
+            QuestionaireEntry::Question(q) if q.entry_type == EntryType::InfoTxt => {},
+            QuestionaireEntry::Question(q) => {
+                let a = match next_answer(&q.id) {
+                    Some(AnswerEntry::Question(qa)) => answer_text(&qa.answer),
+                    Some(AnswerEntry::Skipped(_)) => SKIPPED_TEXT.to_string(),
+                    _ => NOT_ANSWERED.to_string(),
+                };
+                nodes.push(ReportNode::Answer(q.query_text.clone(), vec![a]));
+            },
+            QuestionaireEntry::RepeatedQuestion(rq) => {
+                let mut a: Vec<String> = match next_answer(&rq.id) {
+                    Some(AnswerEntry::RepeatedQuestion(ra)) => ra.answers.iter().map(answer_text).collect(),
+                    Some(AnswerEntry::Skipped(_)) => vec![SKIPPED_TEXT.to_string()],
+                    _ => vec![],
+                };
+                if a.is_empty() {
+                    a.push(NOT_ANSWERED.to_string());
+                }
+                nodes.push(ReportNode::Answer(rq.query_text.clone(), a));
+            },
+            QuestionaireEntry::Block(b) => {
+                nodes.push(ReportNode::Heading(level + 1, b.start_text.clone()));
+                match next_answer(&b.id) {
+                    Some(AnswerEntry::Block(ba)) if ! ba.iterations.is_empty() => {
+                        walk_block(b, ba, level + 1, nodes);
+                    },
+                    Some(AnswerEntry::Skipped(_)) => nodes.push(ReportNode::Note(SKIPPED_TEXT.to_string())),
+                    _ => nodes.push(ReportNode::Note(SKIPPED.to_string())),
+                }
+            },
+        }
+    }
+}
+
+fn answer_text(a: &QuestionAnswerInput) -> String {
+    let s = a.to_string();
+    if s.is_empty() {
+        NOT_ANSWERED.to_string()
+    } else {
+        s
+    }
+}
+
+fn escape_markdown(s: &str) -> String {
+    let mut ret = String::with_capacity(s.len());
+    for c in s.chars() {
+        if matches!(c, '\\' | '*' | '_' | '`' | '#' | '[' | ']' | '<' | '>' | '|') {
+            ret.push('\\');
+        }
+        ret.push(c);
+    }
+    ret
+}
+
+fn escape_html(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::test_data;
+    use crate::test_helper;
+
+    #[test]
+    fn test_markdown() {
+        let q = test_helper::create_complex_questionaire();
+        let md = render_markdown(&q, &test_data::marge());
+        let expected_start = concat!(
+            "# Fun Questionaire\n\n",
+            "**What's your name?**  \nMarge\n\n",
+            "**What's your date of birth?**  \n1956-10-01\n\n",
+            "## Do you have brothers or sisters?\n\n",
+            "### 1.\n\n",
+            "#### Do you have a sister?\n\n",
+            "##### 1.\n\n",
+            "**What's her name?**  \nPatty\n\n",
+        );
+        assert!(md.starts_with(expected_start), "{}", md);
+        assert!(md.contains("#### Do you have a brother?\n\n_No_\n\n"));
+        assert!(md.contains("### 2.\n\n**What was the name of the company you worked for?**  \nPretzel Wagon\n"));
+        assert!(md.contains("#### Have you finished your job there?\n\n**What was your end date there?**  \n1995\n"));
+    }
+
+    #[test]
+    fn test_html() {
+        let q = test_helper::create_complex_questionaire();
+        let html = render_html(&q, &test_data::homer());
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<title>Fun Questionaire</title>"));
+        assert!(html.contains("<dt>What&#39;s your name?</dt>\n<dd>Homer</dd>"));
+        assert!(html.contains("<h2>Do you have brothers or sisters?</h2>\n<p class=\"note\">No</p>"));
+        assert!(html.contains("<h3>1.</h3>\n<dl>\n<dt>What was the name of the company you worked for?</dt>\n<dd>Springfield Nuclear Power Plant</dd>"));
+        assert!(html.ends_with("</body>\n</html>\n"));
+    }
+
+    #[test]
+    fn test_escape() {
+        assert_eq!("a \\*b\\* \\<c\\>", escape_markdown("a *b* <c>"));
+        assert_eq!("&lt;b&gt; &amp; &quot;c&quot;", escape_html("<b> & \"c\""));
+    }
+}
@@ -0,0 +1,171 @@
+//! Rendering of the answers into files with templates (scaffolding)
+//!
+//! The templates use the Jinja2 syntax of minijinja. Every question is available
+//! by its id, blocks as lists of their iterations and repeated questions as lists
+//! of their answers, e.g.:
+//!
+//! ```text
+//! {% for job in id04 %}- {{ job.id04_01 }}
+//! {% endfor %}
+//! ```
+//!
+use crate::export::answers_to_json;
+use crate::questionaire::{BlockAnswer, OutputTemplate, Questionaire};
+use anyhow::{anyhow, Result};
+use minijinja::Environment;
+
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+
+/// One file, that was rendered from a template
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenderedFile {
+    pub path: PathBuf,
+    pub content: String,
+    /// true, if the file already exists and would be overwritten
+    pub exists: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct TemplateRenderer {
+    out_dir: PathBuf,
+    template_dir: PathBuf,
+    overwrite: bool,
+}
+
+impl TemplateRenderer {
+    /// Renderer, that creates the files below the given directory
+    pub fn new(out_dir: &str) -> Self {
+        TemplateRenderer {
+            out_dir: PathBuf::from(out_dir),
+            template_dir: PathBuf::from("."),
+            overwrite: false,
+        }
+    }
+
+    /// Directory for the `source` files of the templates
+    pub fn template_dir(&mut self, v: &str) -> &mut Self {
+        self.template_dir = PathBuf::from(v);
+        self
+    }
+
+    /// Allows to overwrite existing files
+    pub fn overwrite(&mut self, v: bool) -> &mut Self {
+        self.overwrite = v;
+        self
+    }
+
+    /// Renders all templates of the questionaire, without writing anything
+    pub fn render(&self, questionaire: &Questionaire, answer: &BlockAnswer) -> Result<Vec<RenderedFile>> {
+        let mut env = Environment::new();
+        env.set_keep_trailing_newline(true);
+        let ctx = minijinja::Value::from_serialize(answers_to_json(answer));
+        let mut ret: Vec<RenderedFile> = Vec::new();
+        for t in &questionaire.templates {
+            let text = self.template_text(t)?;
+            let target = env.render_str(&t.target, &ctx)
+                .map_err(|e| anyhow!("Error in target of template '{}': {}", t.target, e))?;
+            let path = self.out_dir.join(check_target(&target)?);
+            let content = env.render_str(&text, &ctx)
+                .map_err(|e| anyhow!("Error while rendering '{}': {}", t.target, e))?;
+            let exists = path.exists();
+            ret.push(RenderedFile { path, content, exists });
+        }
+        Ok(ret)
+    }
+
+    /// Writes the rendered files. Nothing is written, if a file already exists and
+    /// overwriting isn't allowed.
+    pub fn write(&self, files: &[RenderedFile]) -> Result<()> {
+        if ! self.overwrite {
+            if let Some(f) = files.iter().find(|f| f.exists || f.path.exists()) {
+                return Err(anyhow!("File already exists: {}", f.path.to_string_lossy()));
+            }
+        }
+        for f in files {
+            if let Some(dir) = f.path.parent() {
+                fs::create_dir_all(dir)?;
+            }
+            fs::write(&f.path, &f.content)?;
+        }
+        Ok(())
+    }
+
+    fn template_text(&self, t: &OutputTemplate) -> Result<String> {
+        if let Some(c) = t.content.as_ref() {
+            return Ok(c.clone());
+        }
+        if let Some(s) = t.source.as_ref() {
+            let p = self.template_dir.join(s);
+            return fs::read_to_string(&p)
+                .map_err(|e| anyhow!("Can't read template '{}': {}", p.to_string_lossy(), e));
+        }
+        Err(anyhow!("Template '{}' has neither content nor source", t.target))
+    }
+}
+
+/// Targets have to stay inside the output directory
+fn check_target(target: &str) -> Result<&Path> {
+    let p = Path::new(target.trim());
+    if p.as_os_str().is_empty() {
+        return Err(anyhow!("Empty target path for template '{}'", target));
+    }
+    if ! p.components().all(|c| matches!(c, Component::Normal(_) | Component::CurDir)) {
+        return Err(anyhow!("Target path has to be relative and inside the output directory: '{}'", target));
+    }
+    Ok(p)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::test_data;
+    use crate::test_helper;
+
+    fn template(target: &str, content: &str) -> OutputTemplate {
+        OutputTemplate {
+            target: target.to_string(),
+            content: Some(content.to_string()),
+            source: None,
+        }
+    }
+
+    #[test]
+    fn test_render_and_write() {
+        let out_dir = "tmp/test_render_and_write";
+        let _ = fs::remove_dir_all(out_dir);
+        let mut q = test_helper::create_complex_questionaire();
+        q.templates = vec![
+            template("{{ id01 | lower }}/jobs.txt", "{% for j in id04 %}{{ loop.index }}. {{ j.id04_01 }}\n{% endfor %}"),
+            template("README", "Name: {{ id01 }}, sisters: {{ id03[0].id03_01 | length }}\n"),
+        ];
+        let r = TemplateRenderer::new(out_dir);
+        let files = r.render(&q, &test_data::marge()).unwrap();
+        assert_eq!(2, files.len());
+        assert_eq!(Path::new(out_dir).join("marge/jobs.txt"), files[0].path);
+        assert_eq!("1. Springfield Police\n2. Pretzel Wagon\n", files[0].content);
+        assert_eq!("Name: Marge, sisters: 2\n", files[1].content);
+        assert!(! files[0].exists);
+
+        r.write(&files).unwrap();
+        assert_eq!("Name: Marge, sisters: 2\n", fs::read_to_string(Path::new(out_dir).join("README")).unwrap());
+        let again = r.render(&q, &test_data::marge()).unwrap();
+        assert!(again[1].exists);
+        assert!(r.write(&again).is_err());
+        TemplateRenderer::new(out_dir).overwrite(true).write(&again).unwrap();
+        let _ = fs::remove_dir_all(out_dir);
+    }
+
+    #[test]
+    fn test_invalid_targets() {
+        let mut q = test_helper::create_complex_questionaire();
+        let r = TemplateRenderer::new("tmp/test_invalid_targets");
+        for t in ["../x", "/etc/x", "a/../../x", "{{ unknown }}"] {
+            q.templates = vec![template(t, "x")];
+            assert!(r.render(&q, &test_data::homer()).is_err(), "{}", t);
+        }
+        q.templates = vec![OutputTemplate { target: "x".to_string(), content: None, source: None }];
+        assert!(r.render(&q, &test_data::homer()).is_err());
+    }
+}
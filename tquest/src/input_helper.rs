@@ -0,0 +1,112 @@
+//! rustyline helper for the line based UI
+//!
+//! Completes the option labels and yes/no words with TAB and shows the last
+//! input of a question as inline hint.
+//!
+use rustyline::completion::Completer;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::validate::Validator;
+use rustyline::{Context, Helper};
+
+use std::borrow::Cow;
+
+use crate::questionaire::{EntryType, QuestionEntry};
+use crate::theme::Style;
+
+
+pub(crate) const BOOL_WORDS: [&str; 2] = ["yes", "no"];
+
+#[derive(Default)]
+pub(crate) struct InputHelper {
+    /// words that TAB completes
+    candidates: Vec<String>,
+    /// last input, shown as hint while the line is a prefix of it
+    last_input: Option<String>,
+    hint_style: Style,
+}
+
+impl InputHelper {
+    pub(crate) fn new(candidates: Vec<String>, last_input: Option<String>) -> Self {
+        InputHelper { candidates, last_input, hint_style: Style::new().dimmed() }
+    }
+
+    pub(crate) fn hint_style(mut self, v: Style) -> Self {
+        self.hint_style = v;
+        self
+    }
+
+    pub(crate) fn for_question(question_entry: &QuestionEntry, last_input: Option<String>) -> Self {
+        let candidates = match &question_entry.entry_type {
+            EntryType::Option(o) => o.options.clone(),
+            EntryType::Bool(_) => BOOL_WORDS.iter().map(|s| s.to_string()).collect(),
+            _ => Vec::new(),
+        };
+        InputHelper::new(candidates, last_input)
+    }
+
+    fn matching(&self, prefix: &str) -> Vec<String> {
+        let p = prefix.to_lowercase();
+        self.candidates.iter().filter(|c| c.to_lowercase().starts_with(&p)).cloned().collect()
+    }
+}
+
+impl Completer for InputHelper {
+    type Candidate = String;
+
+    fn complete(&self, line: &str, pos: usize, _ctx: &Context<'_>) -> rustyline::Result<(usize, Vec<String>)> {
+        // the labels can contain blanks, so the whole line is completed
+        Ok((0, self.matching(&line[..pos])))
+    }
+}
+
+impl Hinter for InputHelper {
+    type Hint = String;
+
+    fn hint(&self, line: &str, pos: usize, _ctx: &Context<'_>) -> Option<String> {
+        if pos < line.len() {
+            return None;
+        }
+        match self.last_input.as_ref() {
+            Some(l) if l.len() > line.len() && l.starts_with(line) => Some(l[line.len()..].to_string()),
+            _ => None,
+        }
+    }
+}
+
+impl Highlighter for InputHelper {
+    fn highlight_hint<'h>(&self, hint: &'h str) -> Cow<'h, str> {
+        Cow::Owned(self.hint_style.paint(hint))
+    }
+}
+
+impl Validator for InputHelper {}
+
+impl Helper for InputHelper {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::questionaire::OptionEntry;
+    use rustyline::history::DefaultHistory;
+
+    #[test]
+    fn test_complete_and_hint() {
+        let e = QuestionEntry::builder()
+            .id("color")
+            .query_text("Color?")
+            .entry_type(EntryType::Option(OptionEntry::builder()
+                .options(vec!["Red".to_string(), "Green".to_string(), "Grey".to_string()])
+                .build()))
+            .build();
+        let h = InputHelper::for_question(&e, Some("Green".to_string()));
+        let history = DefaultHistory::new();
+        let ctx = Context::new(&history);
+        assert_eq!((0, vec!["Green".to_string(), "Grey".to_string()]), h.complete("gr", 2, &ctx).unwrap());
+        assert_eq!((0, vec!["Red".to_string()]), h.complete("R", 1, &ctx).unwrap());
+        assert_eq!(Some("reen".to_string()), h.hint("G", 1, &ctx));
+        assert_eq!(None, h.hint("Gr", 1, &ctx));
+        assert_eq!(None, h.hint("Green", 5, &ctx));
+        assert_eq!(None, h.hint("Re", 2, &ctx));
+    }
+}
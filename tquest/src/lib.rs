@@ -6,21 +6,73 @@ mod controller;
 
 mod persistence;
 
+#[cfg(feature = "sqlite")]
+mod sqlite_persistence;
+
+#[cfg(feature = "encryption")]
+mod encryption;
+
+mod session;
+
+mod export;
+
+mod prompt;
+
+mod menu;
+
+mod timed_input;
+
+mod input_helper;
+
+mod line_view;
+
+mod theme;
+
+mod accessible;
+
+mod text;
+
+mod progress;
+
+#[cfg(feature = "tui")]
+mod tui;
+
+#[cfg(feature = "testing")]
+pub mod testing;
+
 use controller::QuestionaireController;
 use anyhow::{anyhow, Result};
-use ui::{ProceedScreenResult, QuestionaireView};
+use std::time::Duration;
+#[cfg(feature = "tui")]
+use tui::TuiView;
 
-use std::{fs, path::Path};
 
-pub use persistence::{FileQuestionairePersistence, QuestionairePersistence};
+pub use persistence::{FileQuestionairePersistence, NoPersistence, QuestionairePersistence, read_journal};
+#[cfg(feature = "sqlite")]
+pub use sqlite_persistence::{SqliteQuestionairePersistence, SessionInfo};
+#[cfg(feature = "encryption")]
+pub use encryption::JournalKey;
 pub use questionaire::{Questionaire, QuestionaireBuilder, QuestionaireEntry, QuestionEntry, RepeatedQuestionEntry, 
-    SubBlock, EntryType, StringEntry, IntEntry, FloatEntry, BoolEntry, 
+    SubBlock, OutputTemplate, EntryType, StringEntry, IntEntry, FloatEntry, BoolEntry, 
     OptionEntry, BlockAnswer, QuestionAnswerInput, AnswerEntry, RepeatedQuestionAnswers, QuestionAnswer};
 pub use controller::QuestionaireResult;
-pub use ui::Ui;
+pub use progress::Progress;
+pub use session::{SessionStore, SessionDraft};
+pub use export::{flatten, answers_to_json, FlatAnswer, PathSegment};
+pub use export::csv::{CsvExporter, CsvLoopMode};
+pub use export::report::{render_markdown, render_html};
+pub use export::env::{EnvExporter, EnvFormat, KeyCase, shell_quote};
+#[cfg(feature = "templates")]
+pub use export::template::{TemplateRenderer, RenderedFile};
+pub use prompt::{ask, ask_in, ask_string, ask_int, ask_float, ask_bool, ask_option, confirm};
+pub use ui::{Ui, QuestionaireView, MsgLevel, ProceedScreenResult, QuestionScreenResult};
+pub use line_view::LineView;
+pub use timed_input::TimedLines;
+pub use theme::{Color, Style, Theme};
 
 const PERSISTENCE_FILE_NAME: &str = "tquest.tmp";
 const TITLE: &str = "A short questionaire";
+const NEW_SESSION: &str = "Start a new session";
 
 pub struct QuestionaireRunner {
     persistence_file: String,
@@ -28,6 +80,27 @@ pub struct QuestionaireRunner {
     title: String,
     autofil: bool,
     questionaire: Questionaire,
+    use_sessions: bool,
+    session: Option<String>,
+    #[cfg(feature = "encryption")]
+    encrypted: bool,
+    #[cfg(feature = "encryption")]
+    passphrase: Option<String>,
+    #[cfg(feature = "encryption")]
+    key_file: Option<String>,
+    #[cfg(feature = "templates")]
+    output_dir: String,
+    #[cfg(feature = "templates")]
+    dry_run: bool,
+    #[cfg(feature = "templates")]
+    overwrite: bool,
+    #[cfg(feature = "templates")]
+    template_dir: Option<String>,
+    #[cfg(feature = "tui")]
+    tui: bool,
+    theme: Option<Theme>,
+    accessible: bool,
+    timeout: Option<u64>,
 }
 
 impl QuestionaireRunner {
@@ -35,20 +108,8 @@ impl QuestionaireRunner {
         QuestionaireRunnerBuilder::default()
     }
 
-    fn check_for_old_persistence_file(&self) -> bool {
-        let p = Path::new(&self.persistence_file);
-        p.is_file()
-    }
-    
-    fn remove_persistence_file(&self) {
-        let p = Path::new(&self.persistence_file);
-        if p.is_file() {
-            let _ = fs::remove_file(p);
-        }
-    }
-
-    fn handle_persistence_file(&self, persistence: &mut FileQuestionairePersistence, ui: &mut Ui) -> Result<bool> {
-        if self.check_for_old_persistence_file() {
+    fn handle_persistence_file<V: QuestionaireView>(&self, persistence: &mut FileQuestionairePersistence, ui: &mut V) -> Result<bool> {
+        if persistence.has_journal() {
             let r = ui.show_proceed_screen("00", "Found persistence file, for a questionaire. Do you want to load it to proceed where you stopped last time?", None, 0, 0, None);
             match r {
                 Ok(res) => {
@@ -56,9 +117,12 @@ impl QuestionaireRunner {
                         ProceedScreenResult::Canceled => {
                             return Err(anyhow!("Canceled by user"));
                         },
-                        ProceedScreenResult::Proceeded(p) => {
+                        ProceedScreenResult::Proceeded(p) | ProceedScreenResult::TimedOut(p) => {
                             if p {
-                                let _ = persistence.load(Some(&self.persistence_file));
+                                self.load_persistence_file(persistence, ui)?;
+                                if persistence.was_truncated() {
+                                    ui.show_msg("The last entry of the persistence file was incomplete and is skipped.", MsgLevel::Urgent);
+                                }
                             }
                         },
                     }
@@ -73,24 +137,243 @@ impl QuestionaireRunner {
         }
     }
 
+    #[cfg(not(feature = "encryption"))]
+    fn load_persistence_file<V: QuestionaireView>(&self, persistence: &mut FileQuestionairePersistence, _ui: &mut V) -> Result<()> {
+        let file = persistence.file().to_string();
+        persistence.load(Some(&file))
+    }
+
+    #[cfg(feature = "encryption")]
+    fn load_persistence_file<V: QuestionaireView>(&self, persistence: &mut FileQuestionairePersistence, ui: &mut V) -> Result<()> {
+        let file = persistence.file().to_string();
+        if ! persistence.is_encrypted() || persistence.has_key() {
+            return persistence.load(Some(&file));
+        }
+        for _ in 0..3 {
+            if let Some(p) = ui.ask_secret("The persistence file is encrypted, please enter the passphrase")? {
+                persistence.set_key(JournalKey::Passphrase(p));
+                match persistence.load(Some(&file)) {
+                    Ok(()) => return Ok(()),
+                    Err(e) => ui.show_msg(&e.to_string(), MsgLevel::Critical),
+                }
+            } else {
+                return Err(anyhow!("Canceled by user"));
+            }
+        }
+        Err(anyhow!("Can't decrypt the persistence file"))
+    }
+
+    /// Sets the key for the journal from the builder options
+    #[cfg(feature = "encryption")]
+    fn init_encryption(&self, persistence: &mut FileQuestionairePersistence) -> Result<()> {
+        if let Some(key_file) = self.key_file.as_ref() {
+            persistence.set_key(JournalKey::from_key_file(key_file)?);
+        } else if let Some(p) = self.passphrase.as_ref() {
+            persistence.set_key(JournalKey::Passphrase(p.clone()));
+        }
+        persistence.require_key(self.encrypted);
+        Ok(())
+    }
+
+    /// Asks for a new passphrase, if the journal has to be encrypted and no key
+    /// was given or loaded. E.g. a not resumed journal is replaced by a new one.
+    #[cfg(feature = "encryption")]
+    fn ask_new_passphrase<V: QuestionaireView>(&self, persistence: &mut FileQuestionairePersistence, ui: &mut V) -> Result<()> {
+        if self.encrypted && ! persistence.has_key() {
+            loop {
+                let p1 = ui.ask_secret("The answers are stored encrypted, please enter a passphrase")?;
+                let p2 = ui.ask_secret("Please repeat the passphrase")?;
+                match (p1, p2) {
+                    (Some(p1), Some(p2)) => {
+                        if p1.is_empty() {
+                            ui.show_msg("The passphrase must not be empty", MsgLevel::Critical);
+                        } else if p1 != p2 {
+                            ui.show_msg("The passphrases are different", MsgLevel::Critical);
+                        } else {
+                            persistence.set_key(JournalKey::Passphrase(p1));
+                            break;
+                        }
+                    },
+                    _ => return Err(anyhow!("Canceled by user")),
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Shows the drafts of the questionaire and lets the user pick one to resume or
+    /// start a new session. Returns the persistence file and true, if a draft
+    /// should be resumed.
+    fn pick_session<V: QuestionaireView>(&self, store: &SessionStore, ui: &mut V) -> Result<(String, bool)> {
+        if let Some(name) = self.session.as_ref() {
+            let resume = store.exists(name);
+            return Ok((store.persistence_file(name)?, resume));
+        }
+        let drafts = store.list()?;
+        if ! drafts.is_empty() {
+            let now = store.now();
+            let mut options: Vec<String> = drafts.iter().map(|d| d.description_at(now)).collect();
+            options.push(NEW_SESSION.to_string());
+            let q = QuestionEntry::builder()
+                .id("00")
+                .query_text("Found drafts of this questionaire. Which one do you want to resume?")
+                .entry_type(EntryType::Option(OptionEntry::builder()
+                    .options(options.clone())
+                    .default_value(0)
+                    .build()))
+                .build();
+            match ui.show_question_screen(&q, 0, None)? {
+                QuestionScreenResult::Canceled => return Err(anyhow!("Canceled by user")),
+                QuestionScreenResult::Proceeded(a) | QuestionScreenResult::TimedOut(a) => {
+                    // the descriptions contain the age, so they are compared with the
+                    // shown options and not built again
+                    let chosen = a.to_string();
+                    if let Some(d) = options.iter().position(|o| *o == chosen).and_then(|i| drafts.get(i)) {
+                        return Ok((d.file.clone(), true));
+                    }
+                },
+            }
+        }
+        let default_name = (1..).map(|i| format!("draft-{}", i)).find(|n| ! store.exists(n)).unwrap();
+        let q = QuestionEntry::builder()
+            .id("00")
+            .query_text("What should be the name of the new session?")
+            .entry_type(EntryType::String(StringEntry::builder()
+                .default_value(&default_name)
+                .regexp("^[\\w-][\\w .-]*$")
+                .max_length(100)
+                .build()))
+            .build();
+        loop {
+            match ui.show_question_screen(&q, 0, None)? {
+                QuestionScreenResult::Canceled => return Err(anyhow!("Canceled by user")),
+                QuestionScreenResult::Proceeded(a) | QuestionScreenResult::TimedOut(a) => {
+                    let name = a.to_string();
+                    if store.exists(&name) {
+                        ui.show_msg("A session with this name already exists", MsgLevel::Critical);
+                    } else {
+                        return Ok((store.persistence_file(&name)?, false));
+                    }
+                },
+            }
+        }
+    }
+
     pub fn run(&self) -> Result<QuestionaireResult> {    
-        let mut ui: Ui = Ui::new()?;
+        #[cfg(feature = "tui")]
+        if self.tui {
+            return self.run_view(TuiView::new(&self.questionaire)?);
+        }
+        self.run_view(self.new_ui()?)
+    }
+
+    fn new_ui(&self) -> Result<Ui> {
+        let mut ui = Ui::new()?;
+        if let Some(t) = self.theme.as_ref() {
+            ui.set_theme(t.clone());
+        }
+        ui.set_accessible(self.accessible);
+        Ok(ui)
+    }
+
+    /// Runs the questionaire with the given view, e.g. a `LineView`
+    pub fn run_view<V: QuestionaireView>(&self, mut ui: V) -> Result<QuestionaireResult> {
         ui.print_title(&self.title);
-        let mut persistence = FileQuestionairePersistence::new(&self.persistence_file)?;
+        ui.set_timeout(self.timeout.map(Duration::from_secs));
+        let (persistence_file, resume_session) = if self.use_sessions {
+            let store = SessionStore::new(&self.questionaire.init_block.id)?;
+            let (f, resume) = self.pick_session(&store, &mut ui)?;
+            (f, Some(resume))
+        } else {
+            (self.persistence_file.clone(), None)
+        };
+        let mut persistence = FileQuestionairePersistence::new(&persistence_file)?;
+        // fails early if another process works on the same persistence file
+        persistence.lock()?;
+        #[cfg(feature = "encryption")]
+        self.init_encryption(&mut persistence)?;
     
-        let mut persistence_file_exists: bool = false;
         if self.imported_data.is_some() {
             persistence.import(self.imported_data.as_ref().unwrap());
+        } else if let Some(resume) = resume_session {
+            if resume {
+                self.load_persistence_file(&mut persistence, &mut ui)?;
+            }
         } else {
-            persistence_file_exists = self.handle_persistence_file(&mut persistence, &mut ui)?;
+            self.handle_persistence_file(&mut persistence, &mut ui)?;
         }
+        // the key is needed before the first answer is written
+        #[cfg(feature = "encryption")]
+        self.ask_new_passphrase(&mut persistence, &mut ui)?;
     
-        ui.fast_forward = self.autofil;
-        let mut c: QuestionaireController<Ui, FileQuestionairePersistence> = QuestionaireController::new(&self.questionaire, ui, persistence);
-        if persistence_file_exists {
-            self.remove_persistence_file();
+        ui.set_fast_forward(self.autofil);
+        // the old persistence file isn't removed here, the new journal replaces it
+        // atomically with the first answer and a backup is kept until the questionaire is finished
+        let mut c: QuestionaireController<V, FileQuestionairePersistence> = QuestionaireController::new(&self.questionaire, ui, persistence);
+        let result = c.run()?;
+        #[cfg(feature = "templates")]
+        self.render_templates(&result, c.view_mut());
+        Ok(result)
+    }
+
+    /// Runs the questionaire with a different persistence backend. Answers to resume
+    /// from have to be loaded into the persistence before.
+    pub fn run_with<P: QuestionairePersistence>(&self, persistence: P) -> Result<QuestionaireResult> {
+        #[cfg(feature = "tui")]
+        if self.tui {
+            return self.run_view_with(TuiView::new(&self.questionaire)?, persistence);
+        }
+        self.run_view_with(self.new_ui()?, persistence)
+    }
+
+    /// Runs the questionaire with the given view and persistence backend
+    pub fn run_view_with<V: QuestionaireView, P: QuestionairePersistence>(&self, mut ui: V, mut persistence: P) -> Result<QuestionaireResult> {
+        ui.print_title(&self.title);
+        ui.set_timeout(self.timeout.map(Duration::from_secs));
+        if let Some(data) = self.imported_data.as_ref() {
+            persistence.import(data);
+        }
+        ui.set_fast_forward(self.autofil);
+        let mut c: QuestionaireController<V, P> = QuestionaireController::new(&self.questionaire, ui, persistence);
+        let result = c.run()?;
+        #[cfg(feature = "templates")]
+        self.render_templates(&result, c.view_mut());
+        Ok(result)
+    }
+
+    /// Renders the templates of the questionaire, after it was finished. Errors are
+    /// shown by the view, the answers are returned anyway.
+    #[cfg(feature = "templates")]
+    fn render_templates<V: QuestionaireView>(&self, result: &QuestionaireResult, ui: &mut V) {
+        let answer = match result {
+            QuestionaireResult::Finished(a) if ! self.questionaire.templates.is_empty() => a,
+            _ => return,
+        };
+        if let Err(e) = self.write_templates(answer, ui) {
+            ui.show_msg(&format!("Can't create the files of the templates: {}", e), MsgLevel::Critical);
         }
-        c.run()
+    }
+
+    #[cfg(feature = "templates")]
+    fn write_templates<V: QuestionaireView>(&self, answer: &BlockAnswer, ui: &mut V) -> Result<()> {
+        let mut renderer = TemplateRenderer::new(&self.output_dir);
+        renderer.overwrite(self.overwrite);
+        if let Some(d) = self.template_dir.as_ref() {
+            renderer.template_dir(d);
+        }
+        let files = renderer.render(&self.questionaire, answer)?;
+        let mut msg = String::new();
+        for f in &files {
+            let state = if f.exists { " (exists)" } else { "" };
+            msg.push_str(&format!("\n  {}{}", f.path.to_string_lossy(), state));
+        }
+        if self.dry_run {
+            ui.show_msg(&format!("Files that would be created:{}", msg), MsgLevel::Normal);
+        } else {
+            renderer.write(&files)?;
+            ui.show_msg(&format!("Created files:{}", msg), MsgLevel::Normal);
+        }
+        Ok(())
     }
 }
 
@@ -100,6 +383,27 @@ pub struct QuestionaireRunnerBuilder {
     title: Option<String>,
     autofil: bool,
     imported_data: Option<Vec<QuestionAnswer>>,
+    use_sessions: bool,
+    session: Option<String>,
+    #[cfg(feature = "encryption")]
+    encrypted: bool,
+    #[cfg(feature = "encryption")]
+    passphrase: Option<String>,
+    #[cfg(feature = "encryption")]
+    key_file: Option<String>,
+    #[cfg(feature = "templates")]
+    output_dir: Option<String>,
+    #[cfg(feature = "templates")]
+    dry_run: bool,
+    #[cfg(feature = "templates")]
+    overwrite: bool,
+    #[cfg(feature = "templates")]
+    template_dir: Option<String>,
+    #[cfg(feature = "tui")]
+    tui: bool,
+    theme: Option<Theme>,
+    accessible: bool,
+    timeout: Option<u64>,
 }
 
 impl QuestionaireRunnerBuilder {
@@ -119,6 +423,81 @@ impl QuestionaireRunnerBuilder {
         self.autofil = v;
         self
     }
+    /// Stores the answers as named drafts in the data directory of the user, instead
+    /// of the persistence file. At the start a picker for the drafts is shown.
+    pub fn use_sessions(&mut self, v: bool) -> &mut Self {
+        self.use_sessions = v;
+        self
+    }
+    /// Uses the named draft without showing the picker. Implies `use_sessions`.
+    pub fn session(&mut self, v: &str) -> &mut Self {
+        self.use_sessions = true;
+        self.session = Some(v.to_string());
+        self
+    }
+    /// Encrypts the persistence file. Without passphrase or key file, the
+    /// passphrase is asked at the start.
+    #[cfg(feature = "encryption")]
+    pub fn encrypted(&mut self, v: bool) -> &mut Self {
+        self.encrypted = v;
+        self
+    }
+    #[cfg(feature = "encryption")]
+    pub fn passphrase(&mut self, v: &str) -> &mut Self {
+        self.passphrase = Some(v.to_string());
+        self
+    }
+    #[cfg(feature = "encryption")]
+    pub fn key_file(&mut self, v: &str) -> &mut Self {
+        self.key_file = Some(v.to_string());
+        self
+    }
+    /// Directory for the files, that are rendered from the templates of the questionaire
+    #[cfg(feature = "templates")]
+    pub fn output_dir(&mut self, v: &str) -> &mut Self {
+        self.output_dir = Some(v.to_string());
+        self
+    }
+    /// Only shows the files, that would be rendered from the templates
+    #[cfg(feature = "templates")]
+    pub fn dry_run(&mut self, v: bool) -> &mut Self {
+        self.dry_run = v;
+        self
+    }
+    /// Allows to overwrite existing files with the rendered templates
+    #[cfg(feature = "templates")]
+    pub fn overwrite(&mut self, v: bool) -> &mut Self {
+        self.overwrite = v;
+        self
+    }
+    /// Directory for the `source` files of the templates, default is the current
+    /// directory
+    #[cfg(feature = "templates")]
+    pub fn template_dir(&mut self, v: &str) -> &mut Self {
+        self.template_dir = Some(v.to_string());
+        self
+    }
+    /// Runs the questionaire in a full-screen terminal UI
+    #[cfg(feature = "tui")]
+    pub fn tui(&mut self, v: bool) -> &mut Self {
+        self.tui = v;
+        self
+    }
+    /// Theme of the line based UI, default is chosen from the environment
+    pub fn theme(&mut self, v: Theme) -> &mut Self {
+        self.theme = Some(v);
+        self
+    }
+    /// Accessibility mode for screen readers
+    pub fn accessible(&mut self, v: bool) -> &mut Self {
+        self.accessible = v;
+        self
+    }
+    /// Seconds to answer each screen, then the default is taken. For unattended runs.
+    pub fn timeout(&mut self, v: u64) -> &mut Self {
+        self.timeout = Some(v);
+        self
+    }
     pub fn build(&self, questionaire: Questionaire) -> Result<QuestionaireRunner> {
         let persistence_file = if let Some (pf) = self.persistence_file.as_ref() {
             pf.to_string()
@@ -141,6 +520,27 @@ impl QuestionaireRunnerBuilder {
             autofil: self.autofil,
             imported_data,
             questionaire,
+            use_sessions: self.use_sessions,
+            session: self.session.clone(),
+            #[cfg(feature = "encryption")]
+            encrypted: self.encrypted,
+            #[cfg(feature = "encryption")]
+            passphrase: self.passphrase.clone(),
+            #[cfg(feature = "encryption")]
+            key_file: self.key_file.clone(),
+            #[cfg(feature = "templates")]
+            output_dir: self.output_dir.clone().unwrap_or(".".to_string()),
+            #[cfg(feature = "templates")]
+            dry_run: self.dry_run,
+            #[cfg(feature = "templates")]
+            overwrite: self.overwrite,
+            #[cfg(feature = "templates")]
+            template_dir: self.template_dir.clone(),
+            #[cfg(feature = "tui")]
+            tui: self.tui,
+            theme: self.theme.clone(),
+            accessible: self.accessible,
+            timeout: self.timeout,
         })
     }
 
@@ -150,6 +550,7 @@ impl QuestionaireRunnerBuilder {
 #[cfg(test)]
 mod tests {
     use crate::*;
+    use std::{fs, path::Path};
 
     #[test]
     #[ignore]
@@ -196,6 +597,138 @@ mod tests {
         
     }
 
+    #[test]
+    fn test_pick_session_while_age_changes() {
+        use std::sync::atomic::{AtomicU64, Ordering};
+        use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+        static CLOCK_CALLS: AtomicU64 = AtomicU64::new(0);
+        const MODIFIED: u64 = 1_000_000;
+        /// Every call is a minute later, so a description built again would
+        /// show "1 min ago" instead of "just now"
+        fn clock() -> SystemTime {
+            UNIX_EPOCH + Duration::from_secs(MODIFIED + 60 * CLOCK_CALLS.fetch_add(1, Ordering::SeqCst))
+        }
+
+        /// Takes the first option
+        struct View;
+        impl QuestionaireView for View {
+            fn show_proceed_screen<'a, T: Into<Option<&'a str>>>(&mut self, _id: &str, _text: &str, _help_text: T, _question_count: usize, _current: usize, _p: Option<bool>) -> Result<ProceedScreenResult> {
+                Ok(ProceedScreenResult::Proceeded(true))
+            }
+            fn show_question_screen(&mut self, question_entry: &QuestionEntry, _question_count: usize, _p: Option<QuestionAnswerInput>) -> Result<QuestionScreenResult> {
+                match &question_entry.entry_type {
+                    EntryType::Option(o) => Ok(QuestionScreenResult::Proceeded(QuestionAnswerInput::Option(o.options.first().cloned()))),
+                    _ => Ok(QuestionScreenResult::Canceled),
+                }
+            }
+        }
+
+        let mut store = SessionStore::with_base_dir(Path::new("tmp/sessions"), "pick").unwrap();
+        store.clock(clock);
+        let _ = fs::remove_dir_all(store.dir());
+        let file = store.persistence_file("homer").unwrap();
+        fs::write(&file, "id01={\"String\":\"Homer\"}\n").unwrap();
+        let f = fs::File::options().write(true).open(&file).unwrap();
+        f.set_modified(UNIX_EPOCH + Duration::from_secs(MODIFIED)).unwrap();
+        drop(f);
+
+        let runner = QuestionaireRunner::builder().build(test_helper::create_small_questionaire()).unwrap();
+        assert_eq!((file, true), runner.pick_session(&store, &mut View).unwrap());
+        let _ = fs::remove_dir_all(store.dir());
+    }
+
+    #[cfg(feature = "templates")]
+    #[test]
+    fn test_templates_with_existing_files() {
+        use persistence::NoPersistence;
+
+        #[derive(Default)]
+        struct View {
+            messages: Vec<(String, MsgLevel)>,
+        }
+        impl QuestionaireView for View {
+            fn show_msg(&mut self, msg: &str, level: MsgLevel) {
+                self.messages.push((msg.to_string(), level));
+            }
+            fn show_proceed_screen<'a, T: Into<Option<&'a str>>>(&mut self, _id: &str, _text: &str, _help_text: T, _question_count: usize, _current: usize, _p: Option<bool>) -> Result<ProceedScreenResult> {
+                Ok(ProceedScreenResult::Proceeded(true))
+            }
+            fn show_question_screen(&mut self, _question_entry: &QuestionEntry, _question_count: usize, _p: Option<QuestionAnswerInput>) -> Result<QuestionScreenResult> {
+                Ok(QuestionScreenResult::Proceeded(QuestionAnswerInput::String(Some("1956-03-12".to_string()))))
+            }
+        }
+
+        let dir = "tmp/templates_runner";
+        let _ = fs::remove_dir_all(dir);
+        fs::create_dir_all(format!("{}/tpl", dir)).unwrap();
+        fs::write(format!("{}/tpl/born.j2", dir), "{{ id01 }}").unwrap();
+        fs::write(format!("{}/born.txt", dir), "old").unwrap();
+        let questionaire = || {
+            let mut q = test_helper::create_small_questionaire();
+            q.templates = vec![OutputTemplate { target: "born.txt".to_string(), content: None, source: Some("born.j2".to_string()) }];
+            q
+        };
+        let mut builder = QuestionaireRunner::builder();
+        builder.output_dir(dir).template_dir(&format!("{}/tpl", dir));
+
+        // the existing file is reported, the answers are returned anyway
+        let mut view = View::default();
+        let result = builder.build(questionaire()).unwrap().run_view_with(&mut view, NoPersistence::new()).unwrap();
+        assert!(matches!(result, QuestionaireResult::Finished(_)));
+        assert!(view.messages.iter().any(|(m, l)| m.contains("already exists") && matches!(l, MsgLevel::Critical)));
+        assert_eq!("old", fs::read_to_string(format!("{}/born.txt", dir)).unwrap());
+
+        let mut view = View::default();
+        builder.overwrite(true).build(questionaire()).unwrap().run_view_with(&mut view, NoPersistence::new()).unwrap();
+        assert_eq!("1956-03-12", fs::read_to_string(format!("{}/born.txt", dir)).unwrap());
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[cfg(feature = "encryption")]
+    #[test]
+    fn test_encrypted_after_declined_resume() {
+        use encryption::is_encrypted;
+
+        /// Declines the resume, enters a new passphrase and cancels after the first answer
+        struct View;
+        impl QuestionaireView for View {
+            fn show_proceed_screen<'a, T: Into<Option<&'a str>>>(&mut self, id: &str, _text: &str, _help_text: T, _question_count: usize, _current: usize, _p: Option<bool>) -> Result<ProceedScreenResult> {
+                Ok(ProceedScreenResult::Proceeded(id != "00"))
+            }
+            fn show_question_screen(&mut self, question_entry: &QuestionEntry, _question_count: usize, _p: Option<QuestionAnswerInput>) -> Result<QuestionScreenResult> {
+                if question_entry.query_text.contains("name") {
+                    Ok(QuestionScreenResult::Proceeded(QuestionAnswerInput::String(Some("Homer".to_string()))))
+                } else {
+                    Ok(QuestionScreenResult::Canceled)
+                }
+            }
+            fn ask_secret(&mut self, _text: &str) -> Result<Option<String>> {
+                Ok(Some("new secret".to_string()))
+            }
+        }
+
+        let file = "tmp/test_declined_resume.tmp";
+        let _ = fs::create_dir_all("tmp");
+        let mut old = FileQuestionairePersistence::new(file).unwrap();
+        old.set_key(JournalKey::Passphrase("old secret".to_string()));
+        old.store_question(&QuestionEntry::builder().id("id01").build(), &QuestionAnswerInput::String(Some("Marge".to_string()))).unwrap();
+        drop(old);
+
+        let runner = QuestionaireRunner::builder()
+            .persistence_file(file)
+            .encrypted(true)
+            .build(test_helper::create_small_questionaire())
+            .unwrap();
+        assert!(matches!(runner.run_view(View).unwrap(), QuestionaireResult::Canceled));
+        let raw = fs::read(file).unwrap();
+        assert!(is_encrypted(&raw));
+        assert!(! raw.windows(5).any(|w| w == b"Homer"));
+        for f in [file.to_string(), format!("{}.bak", file), format!("{}.lock", file)] {
+            let _ = fs::remove_file(f);
+        }
+    }
+
 
 }
 
@@ -0,0 +1,520 @@
+//! Line based view over any input and output stream
+//!
+//! `LineView` shows the same prompts, help texts and validation messages as `Ui`,
+//! but reads the answers line by line from a `BufRead` and writes to a `Write`.
+//! It can be driven from tests, pipes, sockets or a PTY. The end of the input
+//! cancels the questionaire. Questions only time out in views of `LineView::timed`,
+//! that read the input in a thread.
+//!
+//! ```
+//! use tquest::{EntryType, LineView, NoPersistence, Questionaire, QuestionaireEntry,
+//!     QuestionaireResult, QuestionaireRunner, QuestionEntry, StringEntry};
+//!
+//! let questionaire = Questionaire::builder()
+//!     .id("q")
+//!     .start_text("Start?")
+//!     .end_text("Finish?")
+//!     .questions(vec![QuestionaireEntry::Question(QuestionEntry::builder()
+//!         .id("name")
+//!         .query_text("What's your name?")
+//!         .entry_type(EntryType::String(StringEntry::builder().build()))
+//!         .build())])
+//!     .build();
+//! let mut output: Vec<u8> = Vec::new();
+//! let view = LineView::new("y\nHomer\ny\n".as_bytes(), &mut output);
+//! let runner = QuestionaireRunner::builder().build(questionaire).unwrap();
+//! let result = runner.run_view_with(view, NoPersistence::new()).unwrap();
+//! assert!(matches!(result, QuestionaireResult::Finished(_)));
+//! assert!(String::from_utf8(output).unwrap().contains(">>> Homer"));
+//! ```
+//!
+use anyhow::Result;
+use std::io::{self, BufRead, Write};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::questionaire::{QuestionAnswerInput, QuestionEntry};
+use crate::text::{help_text, render_text, DEFAULT_WIDTH};
+use crate::theme::{Style, Theme};
+use crate::progress::Progress;
+use crate::accessible::{self, Reply};
+use crate::timed_input::TimedLines;
+use crate::ui::{get_position, get_proceed_input_hint, get_progress_bar, get_proceed_text, get_question_text, get_question_timeout,
+    get_timeout_answer, get_timeout_text, get_valid_input_hint, validate_input, MsgLevel, ProceedScreenResult, QuestionScreenResult,
+    QuestionaireView};
+
+
+const YES: &str = "yes";
+const NO: &str = "no";
+
+/// Result of reading an input line
+enum Input {
+    Line(String),
+    TimedOut,
+    /// the end of the input
+    End,
+}
+
+pub struct LineView<R: BufRead, W: Write> {
+    input: R,
+    output: W,
+    theme: Theme,
+    echo: bool,
+    accessible: bool,
+    width: usize,
+    confirm_info: bool,
+    progress: Option<Progress>,
+    pub fast_forward: bool,
+    /// time to answer, set by the runner
+    timeout: Option<Duration>,
+    /// timeout of the next read, only for the input of `timed`
+    read_timeout: Option<Arc<Mutex<Option<Duration>>>>,
+}
+
+impl<W: Write> LineView<TimedLines, W> {
+    /// Creates a view, that reads the input lines in a thread. So questions with a
+    /// timeout take their default, if no line comes in time, e.g. from a pipe.
+    pub fn timed<R: BufRead + Send + 'static>(input: R, output: W) -> Self {
+        let lines = TimedLines::new(input);
+        let read_timeout = lines.timeout_handle();
+        let mut ret = LineView::new(lines, output);
+        ret.read_timeout = Some(read_timeout);
+        ret
+    }
+}
+
+impl<R: BufRead, W: Write> LineView<R, W> {
+    /// Creates a view without colours
+    pub fn new(input: R, output: W) -> Self {
+        LineView {
+            input,
+            output,
+            theme: Theme::no_color(),
+            echo: false,
+            accessible: false,
+            width: DEFAULT_WIDTH,
+            confirm_info: false,
+            progress: None,
+            fast_forward: false,
+            timeout: None,
+            read_timeout: None,
+        }
+    }
+
+    /// Writes ANSI colour codes to the output, with the default theme
+    pub fn color(&mut self, v: bool) -> &mut Self {
+        self.theme = if v { Theme::colored() } else { Theme::no_color() };
+        self
+    }
+
+    pub fn theme(&mut self, v: Theme) -> &mut Self {
+        self.theme = v;
+        self
+    }
+
+    /// Writes the read input lines to the output, e.g. for transcripts of piped input
+    pub fn echo(&mut self, v: bool) -> &mut Self {
+        self.echo = v;
+        self
+    }
+
+    /// Texts for screen readers, like `Ui::set_accessible`
+    pub fn accessible(&mut self, v: bool) -> &mut Self {
+        self.accessible = v;
+        if v {
+            self.theme = Theme::plain();
+        }
+        self
+    }
+
+    /// Width for the word wrapping of the texts, default is 80
+    pub fn width(&mut self, v: usize) -> &mut Self {
+        self.width = v;
+        self
+    }
+
+    /// Waits for an input line after info texts, by default the view goes on at once
+    pub fn confirm_info(&mut self, v: bool) -> &mut Self {
+        self.confirm_info = v;
+        self
+    }
+
+    /// Returns the output stream, e.g. to inspect what was written
+    pub fn into_output(self) -> W {
+        self.output
+    }
+
+    /// Reads the next input line without the line break. The timeout only works
+    /// with the input of `timed`.
+    fn read_line(&mut self, timeout: Option<Duration>) -> Result<Input> {
+        write!(self.output, ">> ")?;
+        self.output.flush()?;
+        if let Some(t) = &self.read_timeout {
+            *t.lock().unwrap() = timeout;
+        }
+        let mut line = String::new();
+        match self.input.read_line(&mut line) {
+            Ok(0) => {
+                writeln!(self.output)?;
+                return Ok(Input::End);
+            },
+            Err(e) if e.kind() == io::ErrorKind::TimedOut => {
+                writeln!(self.output)?;
+                return Ok(Input::TimedOut);
+            },
+            Err(e) => return Err(e.into()),
+            Ok(_) => {},
+        }
+        let line = line.trim().to_string();
+        if self.echo {
+            writeln!(self.output, "{}", line)?;
+        }
+        Ok(Input::Line(line))
+    }
+
+    /// Takes the answer, that ENTER would take
+    fn proceed_timed_out(&mut self, v: bool) -> Result<ProceedScreenResult> {
+        let msg = get_timeout_text(Some(if v { YES } else { NO }));
+        writeln!(self.output, "{}", self.theme.error.paint(&msg))?;
+        Ok(ProceedScreenResult::TimedOut(v))
+    }
+
+    fn question_timed_out(&mut self, question_entry: &QuestionEntry) -> Result<QuestionScreenResult> {
+        let answer = get_timeout_answer(question_entry);
+        let msg = get_timeout_text(answer.as_ref().map(|a| a.to_string()).as_deref());
+        writeln!(self.output, "{}", self.theme.error.paint(&msg))?;
+        Ok(match answer {
+            Some(a) => QuestionScreenResult::TimedOut(a),
+            None => QuestionScreenResult::Canceled,
+        })
+    }
+
+    fn print_result(&mut self, text: &str) -> Result<()> {
+        let s = self.theme.echo.paint(text);
+        writeln!(self.output, ">>> {}", s)?;
+        Ok(())
+    }
+
+    fn show_proceed_screen_accessible(&mut self, text: &str, help_text: Option<&str>, question_count: usize, current: usize, preferred: Option<bool>) -> Result<ProceedScreenResult> {
+        let yes_no = |v: bool| if v { YES } else { NO };
+        writeln!(self.output, "\n{}", accessible::proceed_announcement(text, question_count, current))?;
+        if let Some(a) = preferred {
+            writeln!(self.output, "Last answer: {}.", yes_no(a))?;
+            if self.fast_forward {
+                writeln!(self.output, "Answer: {}", yes_no(a))?;
+                return Ok(ProceedScreenResult::Proceeded(a));
+            }
+        }
+        writeln!(self.output, "{}", accessible::proceed_hint(help_text.is_some(), preferred))?;
+        loop {
+            let line = match self.read_line(self.timeout)? {
+                Input::Line(l) => l,
+                Input::TimedOut => return self.proceed_timed_out(preferred.unwrap_or(true)),
+                Input::End => return Ok(ProceedScreenResult::Canceled),
+            };
+            match accessible::proceed_reply(text, help_text, question_count, current, &line, preferred) {
+                Ok(v) => {
+                    writeln!(self.output, "Answer: {}", yes_no(v))?;
+                    return Ok(ProceedScreenResult::Proceeded(v));
+                },
+                Err(lines) => {
+                    for l in lines {
+                        writeln!(self.output, "{}", l)?;
+                    }
+                },
+            }
+        }
+    }
+
+    fn show_question_screen_accessible(&mut self, question_entry: &QuestionEntry, question_count: usize, preferred: Option<QuestionAnswerInput>) -> Result<QuestionScreenResult> {
+        let preferred_txt = preferred.as_ref().map(|a| a.to_string()).unwrap_or_default();
+        if let Some(a) = preferred.filter(|_| self.fast_forward) {
+            if validate_input(&preferred_txt, &question_entry.entry_type, question_entry.required).is_ok() {
+                writeln!(self.output, "\n{}", accessible::question_announcement(question_entry, question_count))?;
+                writeln!(self.output, "{}", accessible::answer_text(&a))?;
+                return Ok(QuestionScreenResult::Proceeded(a));
+            }
+        }
+        writeln!(self.output)?;
+        for l in accessible::question_intro(question_entry, question_count, &preferred_txt) {
+            writeln!(self.output, "{}", l)?;
+        }
+        let timeout = get_question_timeout(question_entry, self.timeout);
+        loop {
+            let line = match self.read_line(timeout)? {
+                Input::Line(l) => l,
+                Input::TimedOut => return self.question_timed_out(question_entry),
+                Input::End => return Ok(QuestionScreenResult::Canceled),
+            };
+            match accessible::question_reply(question_entry, question_count, &line, &preferred_txt) {
+                Reply::Answer(a) => {
+                    writeln!(self.output, "{}", accessible::answer_text(&a))?;
+                    return Ok(QuestionScreenResult::Proceeded(a));
+                },
+                Reply::Say(lines) => {
+                    for l in lines {
+                        writeln!(self.output, "{}", l)?;
+                    }
+                },
+            }
+        }
+    }
+
+    fn print_question_result(&mut self, ret: &QuestionAnswerInput) -> Result<()> {
+        match ret {
+            QuestionAnswerInput::None => writeln!(self.output, ">>> ???")?,
+            QuestionAnswerInput::String(None) | QuestionAnswerInput::Int(None) | QuestionAnswerInput::Float(None)
+                | QuestionAnswerInput::Bool(None) | QuestionAnswerInput::Option(None) => {},
+            _ => self.print_result(&ret.to_string())?,
+        }
+        Ok(())
+    }
+}
+
+impl<R: BufRead, W: Write> QuestionaireView for LineView<R, W> {
+    fn print_title(&mut self, title: &str) {
+        let s = self.theme.title.paint(title);
+        if let Some(sep) = self.theme.separator() {
+            let _ = writeln!(self.output, "\n{}", sep);
+        }
+        let _ = writeln!(self.output, "\n{}\n", s);
+    }
+
+    fn set_fast_forward(&mut self, v: bool) {
+        self.fast_forward = v;
+    }
+
+    fn set_progress(&mut self, progress: &Progress) {
+        self.progress = Some(*progress);
+    }
+
+    fn set_timeout(&mut self, timeout: Option<Duration>) {
+        self.timeout = timeout;
+    }
+
+    fn show_msg(&mut self, msg: &str, level: MsgLevel) {
+        let s = match level {
+            MsgLevel::Normal => self.theme.message.paint(msg),
+            _ => self.theme.error.paint(msg),
+        };
+        let _ = writeln!(self.output, "\n{}\n", s);
+    }
+
+    fn show_info_screen(&mut self, question_entry: &QuestionEntry) -> Result<ProceedScreenResult> {
+        let text = if self.accessible {
+            accessible::info_announcement(&question_entry.query_text)
+        } else {
+            render_text(&question_entry.query_text, self.width, &Style::new(), &self.theme)
+        };
+        writeln!(self.output, "\n{}", text)?;
+        if self.confirm_info && ! self.fast_forward {
+            let hint = if self.accessible { accessible::INFO_HINT.to_string() } else { self.theme.text("continue with ⏎") };
+            writeln!(self.output, "({})", self.theme.hint.paint(&hint))?;
+            if let Input::End = self.read_line(self.timeout)? {
+                return Ok(ProceedScreenResult::Canceled);
+            }
+        }
+        Ok(ProceedScreenResult::Proceeded(true))
+    }
+
+    fn show_proceed_screen<'a, T: Into<Option<&'a str>>>(&mut self, _id: &str, text: &str, help_text: T, question_count: usize, current: usize, preferred: Option<bool>) -> Result<ProceedScreenResult> {
+        let ht = help_text.into();
+        let (current, question_count) = get_position(self.progress.as_ref(), question_count, current);
+        if self.accessible {
+            return self.show_proceed_screen_accessible(text, ht, question_count, current, preferred);
+        }
+        let hint = self.theme.text(get_proceed_input_hint(ht.is_some(), preferred.unwrap_or(true)));
+        let text_to_display = get_proceed_text(text, question_count, current);
+        if let Some(bar) = get_progress_bar(self.progress.as_ref(), current, &self.theme) {
+            write!(self.output, "\n{}", bar)?;
+        }
+        writeln!(self.output, "\n{}\n({})", render_text(&text_to_display, self.width, &self.theme.question, &self.theme), self.theme.hint.paint(&hint))?;
+        if let Some(a) = preferred {
+            writeln!(self.output, "{}: {}", self.theme.text("last input, take it with ⏎"), self.theme.preferred.paint(if a { YES } else { NO }))?;
+            if self.fast_forward {
+                self.print_result(if a { YES } else { NO })?;
+                return Ok(ProceedScreenResult::Proceeded(a));
+            }
+        }
+        loop {
+            let line = match self.read_line(self.timeout)? {
+                Input::Line(l) => l.to_lowercase(),
+                Input::TimedOut => return self.proceed_timed_out(preferred.unwrap_or(true)),
+                Input::End => return Ok(ProceedScreenResult::Canceled),
+            };
+            let ret = match line.as_str() {
+                "y" | "yes" => Some(true),
+                "n" | "no" => Some(false),
+                "" => Some(preferred.unwrap_or(true)),
+                "h" | "help" | "?" if ht.is_some() => {
+                    writeln!(self.output, "\n{}\n", render_text(ht.unwrap(), self.width, &Style::new(), &self.theme))?;
+                    writeln!(self.output, "\n{}\n", self.theme.hint.paint(&hint))?;
+                    None
+                },
+                _ => {
+                    let msg = format!("Wrong input! {}", hint);
+                    writeln!(self.output, "\n{}\n", self.theme.error.paint(&msg))?;
+                    None
+                },
+            };
+            if let Some(r) = ret {
+                self.print_result(if r { YES } else { NO })?;
+                return Ok(ProceedScreenResult::Proceeded(r));
+            }
+        }
+    }
+
+    fn show_question_screen(&mut self, question_entry: &QuestionEntry, question_count: usize, preferred: Option<QuestionAnswerInput>) -> Result<QuestionScreenResult> {
+        let (pos, question_count) = get_position(self.progress.as_ref(), question_count, question_entry.pos);
+        let question_entry = &QuestionEntry { pos, ..question_entry.clone() };
+        if self.accessible {
+            return self.show_question_screen_accessible(question_entry, question_count, preferred);
+        }
+        let hint = self.theme.text(&get_valid_input_hint(question_entry));
+        let text_to_display = get_question_text(question_entry, question_count);
+        if let Some(bar) = get_progress_bar(self.progress.as_ref(), pos, &self.theme) {
+            write!(self.output, "\n{}", bar)?;
+        }
+        writeln!(self.output, "\n{}\n({})", render_text(&text_to_display, self.width, &self.theme.question, &self.theme), self.theme.hint.paint(&hint))?;
+        let preferred_txt = if let Some(a) = preferred {
+            if self.fast_forward && validate_input(&a.to_string(), &question_entry.entry_type, question_entry.required).is_ok() {
+                self.print_question_result(&a)?;
+                return Ok(QuestionScreenResult::Proceeded(a));
+            }
+            writeln!(self.output, "{}: {}", self.theme.text("last input, take it w/ ⏎"), self.theme.preferred.paint(&a.to_string()))?;
+            a.to_string()
+        } else {
+            String::new()
+        };
+        let timeout = get_question_timeout(question_entry, self.timeout);
+        loop {
+            let mut line = match self.read_line(timeout)? {
+                Input::Line(l) => l,
+                Input::TimedOut => return self.question_timed_out(question_entry),
+                Input::End => return Ok(QuestionScreenResult::Canceled),
+            };
+            if line.is_empty() {
+                line = preferred_txt.clone();
+            }
+            let help = question_entry.help_text.as_ref().filter(|_| line == "h" || line == "?");
+            if let Some(h) = help {
+                writeln!(self.output, "\n{}\n", render_text(&help_text(h), self.width, &self.theme.message, &self.theme))?;
+            } else if let Ok(ret) = validate_input(&line, &question_entry.entry_type, question_entry.required) {
+                self.print_question_result(&ret)?;
+                return Ok(QuestionScreenResult::Proceeded(ret));
+            } else {
+                let msg = format!("Wrong input! {}", hint);
+                writeln!(self.output, "{}", self.theme.error.paint(&msg))?;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::questionaire::{EntryType, IntEntry, StringEntry};
+
+    fn int_question() -> QuestionEntry {
+        QuestionEntry::builder()
+            .id("workers")
+            .query_text("How many workers?")
+            .help_text("Number of worker threads")
+            .entry_type(EntryType::Int(IntEntry::builder().min(1).max(10).build()))
+            .build()
+    }
+
+    #[test]
+    fn test_question_screen() {
+        let mut output: Vec<u8> = Vec::new();
+        let mut view = LineView::new("h\n42\n7\n".as_bytes(), &mut output);
+        match view.show_question_screen(&int_question(), 3, None).unwrap() {
+            QuestionScreenResult::Proceeded(a) => assert_eq!(QuestionAnswerInput::Int(Some(7)), a),
+            _ => panic!("unexpected cancel"),
+        }
+        let text = String::from_utf8(output).unwrap();
+        assert!(text.starts_with("\nHow many workers?\n("));
+        assert!(text.contains("\nHelp: Number of worker threads\n"));
+        assert!(text.contains(">> Wrong input! "));
+        assert!(text.ends_with(">> >>> 7\n"));
+        assert!(! text.contains('\x1b'));
+    }
+
+    #[test]
+    fn test_proceed_screen() {
+        let mut output: Vec<u8> = Vec::new();
+        let mut view = LineView::new("x\n\n".as_bytes(), &mut output);
+        view.color(true);
+        match view.show_proceed_screen("p", "Proceed?", None, 0, 0, Some(false)).unwrap() {
+            ProceedScreenResult::Proceeded(p) => assert!(! p),
+            _ => panic!("unexpected cancel"),
+        }
+        let text = String::from_utf8(output).unwrap();
+        assert!(text.starts_with("\n\x1b[1mProceed?\x1b[0m\n("));
+        assert!(text.ends_with(">>> \x1b[32mno\x1b[0m\n"));
+
+        // the accessibility mode
+        let mut output: Vec<u8> = Vec::new();
+        let mut view = LineView::new("maybe\nn\n".as_bytes(), &mut output);
+        view.accessible(true);
+        assert!(matches!(view.show_proceed_screen("p", "Proceed?", None, 5, 2, None).unwrap(), ProceedScreenResult::Proceeded(false)));
+        assert_eq!("\nQuestion 2 of 5, yes or no question: Proceed?\n\
+            Type yes or no and press Enter. Press Enter alone for yes.\n\
+            >> Invalid answer: maybe is not yes or no.\n\
+            Question 2 of 5, yes or no question: Proceed?\n\
+            >> Answer: no\n", String::from_utf8(output).unwrap());
+
+        // the end of the input cancels
+        let mut view = LineView::new("".as_bytes(), Vec::new());
+        assert!(matches!(view.show_question_screen(&int_question(), 0, None).unwrap(), QuestionScreenResult::Canceled));
+        assert!(matches!(view.show_proceed_screen("p", "Proceed?", None, 0, 0, None).unwrap(), ProceedScreenResult::Canceled));
+    }
+
+    #[test]
+    fn test_info_screen() {
+        let info = QuestionEntry::builder()
+            .id("info")
+            .query_text("The next questions are about *your* job.")
+            .entry_type(EntryType::InfoTxt)
+            .build();
+        let mut output: Vec<u8> = Vec::new();
+        let mut view = LineView::new("".as_bytes(), &mut output);
+        assert!(matches!(view.show_info_screen(&info).unwrap(), ProceedScreenResult::Proceeded(true)));
+        assert_eq!("\nThe next questions are about your job.\n", String::from_utf8(output).unwrap());
+
+        let mut output: Vec<u8> = Vec::new();
+        let mut view = LineView::new("\n".as_bytes(), &mut output);
+        view.confirm_info(true);
+        assert!(matches!(view.show_info_screen(&info).unwrap(), ProceedScreenResult::Proceeded(true)));
+        assert!(matches!(view.show_info_screen(&info).unwrap(), ProceedScreenResult::Canceled));
+        assert!(String::from_utf8(output).unwrap().contains("job.\n(continue with ⏎)\n>> "));
+    }
+    #[test]
+    fn test_timeout() {
+        let (reader, mut writer) = std::io::pipe().unwrap();
+        let mut view = LineView::timed(std::io::BufReader::new(reader), Vec::new());
+        view.set_timeout(Some(Duration::from_millis(20)));
+        let name = QuestionEntry::builder()
+            .id("name")
+            .query_text("What's your name?")
+            .entry_type(EntryType::String(StringEntry::builder().default_value("Homer").build()))
+            .build();
+        match view.show_question_screen(&name, 0, None).unwrap() {
+            QuestionScreenResult::TimedOut(a) => assert_eq!(QuestionAnswerInput::String(Some("Homer".to_string())), a),
+            _ => panic!("no timeout"),
+        }
+        assert!(matches!(view.show_proceed_screen("p", "Proceed?", None, 0, 0, Some(false)).unwrap(), ProceedScreenResult::TimedOut(false)));
+
+        // a late answer is taken by the next question
+        writer.write_all(b"Bart\n").unwrap();
+        view.set_timeout(None);
+        match view.show_question_screen(&name, 0, None).unwrap() {
+            QuestionScreenResult::Proceeded(a) => assert_eq!(QuestionAnswerInput::String(Some("Bart".to_string())), a),
+            _ => panic!("unexpected result"),
+        }
+        drop(writer);
+        assert!(matches!(view.show_question_screen(&name, 0, None).unwrap(), QuestionScreenResult::Canceled));
+        let text = String::from_utf8(view.into_output()).unwrap();
+        assert!(text.contains("Timeout, the default is taken: Homer"), "{}", text);
+    }
+}
@@ -0,0 +1,204 @@
+//! Inline selection menu for the line based UI
+//!
+//! The arrow keys move the highlight, Enter selects and typed characters filter
+//! the list. The menu is drawn below the question and removed after the selection.
+//!
+use crossterm::cursor::{Hide, MoveToColumn, MoveToPreviousLine, Show};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::style::Print;
+use crossterm::terminal::{self, Clear, ClearType};
+use crossterm::queue;
+
+use std::io::{self, Write};
+
+use crate::theme::Theme;
+
+
+/// Maximum number of shown items, longer lists scroll
+const MAX_VISIBLE: usize = 10;
+
+#[derive(Debug, PartialEq)]
+pub(crate) enum MenuAction {
+    /// index of the selected item
+    Select(usize),
+    Help,
+    Cancel,
+}
+
+pub(crate) struct MenuState {
+    items: Vec<String>,
+    filter: String,
+    /// index in `items`
+    selected: usize,
+}
+
+impl MenuState {
+    pub(crate) fn new(items: Vec<String>, preselected: usize) -> Self {
+        let selected = if preselected < items.len() { preselected } else { 0 };
+        MenuState { items, filter: String::new(), selected }
+    }
+
+    /// Indexes of the items, that match the filter
+    fn visible(&self) -> Vec<usize> {
+        let f = self.filter.to_lowercase();
+        (0..self.items.len()).filter(|i| self.items[*i].to_lowercase().contains(&f)).collect()
+    }
+
+    pub(crate) fn handle_key(&mut self, key: &KeyEvent) -> Option<MenuAction> {
+        if key.modifiers.contains(KeyModifiers::CONTROL) {
+            return match key.code {
+                KeyCode::Char('c') | KeyCode::Char('d') => Some(MenuAction::Cancel),
+                _ => None,
+            };
+        }
+        let visible = self.visible();
+        let pos = visible.iter().position(|i| *i == self.selected);
+        match key.code {
+            KeyCode::Enter if pos.is_some() => return Some(MenuAction::Select(self.selected)),
+            KeyCode::Up if ! visible.is_empty() => {
+                let p = pos.unwrap_or(0);
+                self.selected = visible[if p == 0 { visible.len() - 1 } else { p - 1 }];
+            },
+            KeyCode::Down if ! visible.is_empty() => {
+                let p = pos.map(|p| (p + 1) % visible.len()).unwrap_or(0);
+                self.selected = visible[p];
+            },
+            KeyCode::Char('?') if self.filter.is_empty() => return Some(MenuAction::Help),
+            KeyCode::Char(c) => {
+                self.filter.push(c);
+                self.select_first_visible();
+            },
+            KeyCode::Backspace => {
+                self.filter.pop();
+                self.select_first_visible();
+            },
+            KeyCode::Esc => {
+                self.filter.clear();
+            },
+            _ => {},
+        }
+        None
+    }
+
+    fn select_first_visible(&mut self) {
+        let visible = self.visible();
+        if ! visible.contains(&self.selected) {
+            if let Some(first) = visible.first() {
+                self.selected = *first;
+            }
+        }
+    }
+
+    /// Lines of the menu, with a flag for the highlighted one
+    fn lines(&self) -> Vec<(String, bool)> {
+        let visible = self.visible();
+        let mut ret: Vec<(String, bool)> = Vec::new();
+        if ! self.filter.is_empty() {
+            ret.push((format!("  filter: {}", self.filter), false));
+        }
+        if visible.is_empty() {
+            ret.push(("  (no match)".to_string(), false));
+            return ret;
+        }
+        let pos = visible.iter().position(|i| *i == self.selected).unwrap_or(0);
+        let start = pos.saturating_sub(MAX_VISIBLE - 1).min(visible.len().saturating_sub(MAX_VISIBLE));
+        for i in visible.iter().skip(start).take(MAX_VISIBLE) {
+            let highlighted = *i == self.selected;
+            let marker = if highlighted { ">" } else { " " };
+            ret.push((format!("{} {}", marker, self.items[*i]), highlighted));
+        }
+        ret
+    }
+}
+
+/// Shows the menu until an item is selected, help is requested or the user cancels
+pub(crate) fn select(items: &[String], preselected: usize, theme: &Theme) -> io::Result<MenuAction> {
+    let mut state = MenuState::new(items.to_vec(), preselected);
+    let mut out = io::stderr();
+    terminal::enable_raw_mode()?;
+    queue!(out, Hide)?;
+    let ret = run_menu(&mut state, &mut out, theme);
+    // the menu is removed, the caller prints the result
+    let _ = queue!(out, MoveToColumn(0), Clear(ClearType::FromCursorDown), Show);
+    let _ = out.flush();
+    terminal::disable_raw_mode()?;
+    ret
+}
+
+fn run_menu<W: Write>(state: &mut MenuState, out: &mut W, theme: &Theme) -> io::Result<MenuAction> {
+    let mut drawn: u16 = 0;
+    loop {
+        if drawn > 0 {
+            queue!(out, MoveToPreviousLine(drawn))?;
+        }
+        queue!(out, MoveToColumn(0), Clear(ClearType::FromCursorDown))?;
+        let lines = state.lines();
+        for (l, highlighted) in &lines {
+            if *highlighted {
+                queue!(out, Print(theme.echo.paint(l)))?;
+            } else {
+                queue!(out, Print(l))?;
+            }
+            queue!(out, Print("\r\n"))?;
+        }
+        out.flush()?;
+        drawn = lines.len() as u16;
+
+        if let Event::Key(k) = event::read()? {
+            if k.kind != KeyEventKind::Press {
+                continue;
+            }
+            if let Some(a) = state.handle_key(&k) {
+                if drawn > 0 {
+                    queue!(out, MoveToPreviousLine(drawn))?;
+                }
+                return Ok(a);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state() -> MenuState {
+        MenuState::new(vec!["red".to_string(), "green".to_string(), "blue".to_string(), "black".to_string()], 1)
+    }
+
+    fn key(c: KeyCode) -> KeyEvent {
+        KeyEvent::from(c)
+    }
+
+    #[test]
+    fn test_navigation() {
+        let mut s = state();
+        assert_eq!(("> green".to_string(), true), s.lines()[1]);
+        assert_eq!(None, s.handle_key(&key(KeyCode::Up)));
+        assert_eq!(None, s.handle_key(&key(KeyCode::Up)));
+        assert_eq!(Some(MenuAction::Select(3)), s.handle_key(&key(KeyCode::Enter)));
+        assert_eq!(None, s.handle_key(&key(KeyCode::Down)));
+        assert_eq!(Some(MenuAction::Select(0)), s.handle_key(&key(KeyCode::Enter)));
+        assert_eq!(Some(MenuAction::Help), s.handle_key(&key(KeyCode::Char('?'))));
+        assert_eq!(Some(MenuAction::Cancel), s.handle_key(&KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL)));
+    }
+
+    #[test]
+    fn test_filter() {
+        let mut s = state();
+        s.handle_key(&key(KeyCode::Char('b')));
+        assert_eq!(vec![2, 3], s.visible());
+        assert_eq!("  filter: b", s.lines()[0].0);
+        // the selection moves to the first match and stays inside the matches
+        assert_eq!(None, s.handle_key(&key(KeyCode::Down)));
+        assert_eq!(Some(MenuAction::Select(3)), s.handle_key(&key(KeyCode::Enter)));
+        s.handle_key(&key(KeyCode::Char('x')));
+        assert!(s.visible().is_empty());
+        assert_eq!("  (no match)", s.lines()[1].0);
+        assert_eq!(None, s.handle_key(&key(KeyCode::Enter)));
+        s.handle_key(&key(KeyCode::Backspace));
+        s.handle_key(&key(KeyCode::Esc));
+        assert_eq!(4, s.visible().len());
+        assert_eq!(Some(MenuAction::Select(3)), s.handle_key(&key(KeyCode::Enter)));
+    }
+}
@@ -1,27 +1,66 @@
 use crate::questionaire::{QuestionAnswerInput, QuestionEntry, QuestionAnswer} ;
+#[cfg(feature = "encryption")]
+use crate::encryption::{self, JournalCipher, JournalKey};
 use anyhow::{anyhow, Result};
 use colored::Colorize;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
-use std::fs::{File, OpenOptions};
-use std::io::Write;
+use std::fs::{self, File, OpenOptions, TryLockError};
+use std::io::{Read, Write};
 use std::path::Path;
-use std::io::{BufRead, BufReader};
 
 
 pub trait QuestionairePersistence {
     fn store_question(&mut self, entry: &QuestionEntry, data: &QuestionAnswerInput) -> Result<()>;
+    /// Is called instead of `store_question` for an answer, that was taken after a timeout,
+    /// and with `QuestionAnswerInput::String(None)` for a repeated question, that a timeout
+    /// ended. Without an own implementation it's stored like the other answers.
+    fn store_timed_out_question(&mut self, entry: &QuestionEntry, data: &QuestionAnswerInput) -> Result<()> {
+        self.store_question(entry, data)
+    }
     fn load(&mut self, source: Option<&str>) -> Result<()>;
     fn import(&mut self, data_to_import: &Vec<QuestionAnswer>);
     fn next_answer(&mut self) -> Option<QuestionAnswer>;
     fn next_answer_id(&mut self) -> Option<String>;
+    /// Is called with the decision of the user, to enter (or repeat) a block or not.
+    /// The answer journal doesn't need it, because it's derived from the ids of the answers.
+    fn store_block_decision(&mut self, _block_id: &str, _decision: bool) -> Result<()> {
+        Ok(())
+    }
+    /// Returns the next stored decision of a resumed run, if it belongs to the
+    /// given block. Without stored decisions they are derived from the answers.
+    fn next_block_decision(&mut self, _block_id: &str) -> Option<bool> {
+        None
+    }
+    /// Is called when the questionaire is finished, e.g. to remove backups
+    fn finish(&mut self) -> Result<()> {
+        Ok(())
+    }
 }
 
+/// Journal based persistence. Every stored answer rewrites the whole journal
+/// into a temp file, that is synced and then renamed over the journal file. So
+/// the journal on disk is always complete, also when the process crashes
+/// in the middle of writing.
 pub struct FileQuestionairePersistence  {
     file: String,
     data: Vec<QuestionAnswer>,
     pub debug: bool,
     current_pos: usize,
+    /// lines of the journal, that is written by the current run
+    journal: Vec<String>,
+    /// number of entries of a previous journal in the same file. If there was one,
+    /// a backup of it is kept until the questionaire is finished.
+    previous_len: usize,
+    /// true if the loaded journal ended with an incomplete or corrupted line
+    truncated: bool,
+    lock: Option<File>,
+    /// if set, the journal is encrypted at rest
+    #[cfg(feature = "encryption")]
+    cipher: Option<JournalCipher>,
+    /// if set, the journal is never written without a key
+    #[cfg(feature = "encryption")]
+    key_required: bool,
 }
 
 impl FileQuestionairePersistence  {
@@ -31,29 +70,169 @@ impl FileQuestionairePersistence  {
             data: vec![],
             debug: false,
             current_pos: 0,
+            journal: vec![],
+            previous_len: 0,
+            truncated: false,
+            lock: None,
+            #[cfg(feature = "encryption")]
+            cipher: None,
+            #[cfg(feature = "encryption")]
+            key_required: false,
         };
         Ok(ret)
     }
 
+    pub fn file(&self) -> &str {
+        &self.file
+    }
+
+    /// Path of the backup, that holds the previous journal until the questionaire
+    /// is finished
+    pub fn backup_file(&self) -> String {
+        format!("{}.bak", self.file)
+    }
+
+    fn lock_file(&self) -> String {
+        format!("{}.lock", self.file)
+    }
+
+    fn temp_file(&self) -> String {
+        format!("{}.new", self.file)
+    }
+
+    /// Returns true if there is a journal (or the backup of a not completed one) to resume from
+    pub fn has_journal(&self) -> bool {
+        Path::new(&self.file).is_file() || Path::new(&self.backup_file()).is_file()
+    }
+
+    /// Returns true if the last loaded journal ended with an incomplete or corrupted
+    /// entry, that was skipped
+    pub fn was_truncated(&self) -> bool {
+        self.truncated
+    }
+
+    /// Sets the key to encrypt the journal. It's also needed to load an encrypted journal.
+    #[cfg(feature = "encryption")]
+    pub fn set_key(&mut self, key: JournalKey) {
+        self.cipher = Some(JournalCipher::new(key));
+    }
+
+    #[cfg(feature = "encryption")]
+    pub fn has_key(&self) -> bool {
+        self.cipher.is_some()
+    }
+
+    /// Makes writes fail as long as no key is set, so that the journal is never
+    /// stored in plain text
+    #[cfg(feature = "encryption")]
+    pub fn require_key(&mut self, v: bool) {
+        self.key_required = v;
+    }
+
+    /// Returns true if the existing journal (or its backup) is encrypted
+    #[cfg(feature = "encryption")]
+    pub fn is_encrypted(&self) -> bool {
+        let f = if Path::new(&self.file).is_file() {
+            self.file.clone()
+        } else {
+            self.backup_file()
+        };
+        let mut magic = [0u8; 32];
+        if let Ok(mut file) = File::open(f) {
+            if let Ok(n) = file.read(&mut magic) {
+                return encryption::is_encrypted(&magic[..n]);
+            }
+        }
+        false
+    }
+
+    /// Takes an exclusive lock for the journal file. The lock is hold until the
+    /// persistence object is dropped. It fails if another process already uses
+    /// the same journal.
+    pub fn lock(&mut self) -> Result<()> {
+        if self.lock.is_some() {
+            return Ok(());
+        }
+        let f = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(self.lock_file())?;
+        match f.try_lock() {
+            Ok(()) => {
+                self.lock = Some(f);
+                Ok(())
+            },
+            Err(TryLockError::WouldBlock) => {
+                Err(anyhow!("Persistence file '{}' is used by another process", self.file))
+            },
+            Err(TryLockError::Error(e)) => Err(e.into()),
+        }
+    }
+
     fn store<T: Serialize>(&mut self, id: &str, answer: &T) -> Result<()> {
         let json_string = serde_json::to_string(answer).unwrap();
         let txt = format!("{}={}",id, json_string);
         if self.debug {
-            println!("{}", txt.blue().italic());
+            eprintln!("{}", txt.blue().italic());
         }
-        self.write_to_file(&txt)
+        self.journal.push(txt);
+        self.write_journal()
     }
 
-
-    fn write_to_file(&mut self, txt: &str) -> Result<()> {
+    fn write_journal(&mut self) -> Result<()> {
+        self.lock()?;
+        #[cfg(feature = "encryption")]
+        if self.key_required && self.cipher.is_none() {
+            return Err(anyhow!("The persistence file has to be encrypted, but no passphrase or key is set"));
+        }
+        let tmp = self.temp_file();
+        let mut content: Vec<u8> = Vec::new();
+        for l in &self.journal {
+            writeln!(content, "{}", l)?;
+        }
+        #[cfg(feature = "encryption")]
+        if let Some(cipher) = self.cipher.as_mut() {
+            content = cipher.encrypt(&content)?;
+        }
+        {
+            let mut file = File::create(&tmp)?;
+            file.write_all(&content)?;
+            file.sync_all()?;
+        }
         let p = Path::new(&self.file);
-        let mut file = OpenOptions::new()
-            .append(true)
-            .create(true)
-            .open(p)?;
-        write!(file, "{}\n", txt)?;
+        let backup = self.backup_file();
+        if self.previous_len > 0 && p.is_file() && ! Path::new(&backup).is_file() {
+            fs::copy(p, &backup)?;
+        }
+        fs::rename(&tmp, p)?;
         Ok(())
     }
+
+    /// Reads and parses a journal file, encrypted ones are decrypted with the key
+    fn read_file(&mut self, file_path: &str) -> Result<(Vec<QuestionAnswer>, bool)> {
+        let mut raw: Vec<u8> = Vec::new();
+        File::open(file_path)?.read_to_end(&mut raw)?;
+        #[cfg(feature = "encryption")]
+        if encryption::is_encrypted(&raw) {
+            if let Some(cipher) = self.cipher.as_mut() {
+                raw = cipher.decrypt(&raw)?;
+            } else {
+                return Err(anyhow!("Persistence file is encrypted, a passphrase or key is needed"));
+            }
+        }
+        parse_journal(&raw, file_path)
+    }
+}
+
+impl Drop for FileQuestionairePersistence {
+    fn drop(&mut self) {
+        // the lock file stays, removing it while it's locked would allow a second
+        // lock on a new file
+        if let Some(f) = self.lock.take() {
+            let _ = f.unlock();
+        }
+    }
 }
 
 impl QuestionairePersistence for FileQuestionairePersistence {
@@ -63,15 +242,43 @@ impl QuestionairePersistence for FileQuestionairePersistence {
         self.store(&entry.id, data)
     }
 
+    fn store_timed_out_question(&mut self, entry: &QuestionEntry, data: &QuestionAnswerInput) -> Result<()> {
+        self.store(&entry.id, &TimedOutAnswer { answer: data.clone(), timed_out: true })
+    }
+
     fn load(&mut self, source: Option<&str>) -> Result<()> {
         if let Some(file_path) = source {
-            self.data = load_tmp_file(&file_path)?;
+            let own_journal = file_path == self.file;
+            let backup = self.backup_file();
+            // the journal is always newer than its backup, the backup is only
+            // taken if the journal is missing or can't be read
+            let (data, truncated) = match self.read_file(file_path) {
+                Err(e) if own_journal && Path::new(&backup).is_file() => {
+                    self.read_file(&backup).map_err(|_| e)?
+                },
+                r => r?,
+            };
+            if own_journal {
+                self.previous_len = data.len();
+            }
+            self.data = data;
+            self.truncated = truncated;
             Ok(())
         } else {
             Err(anyhow!("No source for loading given"))
         }
     }
 
+    /// The questionaire is complete, so the previous journal isn't needed anymore
+    fn finish(&mut self) -> Result<()> {
+        let backup = self.backup_file();
+        let b = Path::new(&backup);
+        if b.is_file() {
+            fs::remove_file(b)?;
+        }
+        Ok(())
+    }
+
     fn import (&mut self, data_to_import: &Vec<QuestionAnswer>) {
         for i in data_to_import {
             self.data.push(i.clone());
@@ -106,6 +313,7 @@ impl QuestionairePersistence for FileQuestionairePersistence {
 
 }
 
+#[derive(Default)]
 pub struct NoPersistence {
 }
 
@@ -137,38 +345,80 @@ impl QuestionairePersistence for NoPersistence {
 
 }
 
-pub fn load_tmp_file(file_path: &str) -> Result<Vec<QuestionAnswer>> {
-    let file = File::open(file_path)?;
-    let reader = BufReader::new(file);
+/// Journal value of an answer, that was taken after a timeout. The other answers
+/// are stored as plain `QuestionAnswerInput`, like in older journals.
+#[derive(Deserialize, Serialize)]
+struct TimedOutAnswer {
+    answer: QuestionAnswerInput,
+    timed_out: bool,
+}
 
-    let mut ret: Vec<QuestionAnswer> = Vec::new();
+/// Reads the answers of a persistence file, e.g. to import them into another run
+pub fn read_journal(file_path: &str) -> Result<Vec<QuestionAnswer>> {
+    let raw = fs::read(file_path)?;
+    let (ret, _) = parse_journal(&raw, file_path)?;
+    Ok(ret)
+}
 
-    for line in reader.lines() {
-        let line = line.unwrap();
+/// Parses the content of a journal file. An incomplete or corrupted last line, e.g. from
+/// a crash while writing, is skipped and reported by the returned flag. Corrupted lines
+/// in the middle of the file are an error.
+pub(crate) fn parse_journal(raw: &[u8], file_path: &str) -> Result<(Vec<QuestionAnswer>, bool)> {
+    fn parse_line(line: &str) -> Option<QuestionAnswer> {
+        let index = line.find("=")?;
+        let (id, json_str) = (&line[..index], &line[index+1..]);
+        if id.is_empty() {
+            return None;
+        }
+        let (answer, timed_out) = match serde_json::from_str::<QuestionAnswerInput>(json_str) {
+            Ok(a) => (a, false),
+            Err(_) => serde_json::from_str::<TimedOutAnswer>(json_str).ok().map(|t| (t.answer, t.timed_out))?,
+        };
+        Some(QuestionAnswer {
+            id: id.to_string(),
+            answer,
+            timed_out,
+        })
+    }
 
-        let (id, json_str) = if let Some(index) = line.find("=") {
-            (&line[..index], &line[index+1..])
-        } else {
+    // a crash can cut a multi byte character, that must not fail the whole load
+    let content = String::from_utf8_lossy(raw);
+
+    let mut lines: Vec<&str> = content.lines().collect();
+    while lines.last().is_some_and(|l| l.trim().is_empty()) {
+        lines.pop();
+    }
+
+    let mut truncated = false;
+    let mut ret: Vec<QuestionAnswer> = Vec::new();
+    let line_count = lines.len();
+    for (index, line) in lines.into_iter().enumerate() {
+        if line.trim().is_empty() {
             continue;
-        };
-        if let Ok(o) = serde_json::from_str::<QuestionAnswerInput>(&json_str) {
-            let qa = QuestionAnswer {
-                id: id.to_string(),
-                answer: o,
-            };
+        }
+        if let Some(qa) = parse_line(line) {
             ret.push(qa);
+        } else if index + 1 == line_count {
+            truncated = true;
+        } else {
+            return Err(anyhow!("Corrupted persistence file '{}', line {}", file_path, index + 1));
         }
     }
-    Ok(ret)
+    Ok((ret, truncated))
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
 
+    fn load_tmp_file(file_path: &str) -> Result<(Vec<QuestionAnswer>, bool)> {
+        let raw = fs::read(file_path)?;
+        parse_journal(&raw, file_path)
+    }
+
     #[test]
     fn test_load_tmp_file() {
-        if let Ok(v) = load_tmp_file("res/tquest.tmp") {
+        if let Ok((v, _)) = load_tmp_file("res/tquest.tmp") {
             assert_eq!(14, v.len());
             v.iter().enumerate().for_each(|(index, a)| {
                 match index {
@@ -231,4 +481,151 @@ mod test {
         assert_eq!(None, persistence.next_answer_id());
         assert_eq!(None, persistence.next_answer());
     }
-}
\ No newline at end of file
+
+    fn write_test_file(file: &str, content: &str) {
+        let p = Path::new("tmp");
+        if ! p.is_dir() {
+            let _ = fs::create_dir(p);
+        }
+        fs::write(file, content).expect("error while writing test file");
+    }
+
+    fn remove_test_files(file: &str) {
+        for f in [file.to_string(), format!("{}.bak", file), format!("{}.new", file), format!("{}.lock", file)] {
+            let _ = fs::remove_file(f);
+        }
+    }
+
+    #[test]
+    fn test_load_truncated_last_line() {
+        let file = "tmp/test_truncated.tmp";
+        write_test_file(file, "id01={\"String\":\"Test Name\"}\nid02={\"String\":\"1999-0");
+        let (v, truncated) = load_tmp_file(file).unwrap();
+        assert_eq!(1, v.len());
+        assert!(truncated);
+        remove_test_files(file);
+    }
+
+    #[test]
+    fn test_load_corrupted_middle_line() {
+        let file = "tmp/test_corrupted.tmp";
+        write_test_file(file, "id01={\"String\":\"Test Name\"}\nid02={\"Str\nid03={\"String\":\"x\"}\n");
+        assert!(load_tmp_file(file).is_err());
+        remove_test_files(file);
+    }
+
+    #[test]
+    fn test_store_timed_out_answer() {
+        let file = "tmp/test_timed_out.tmp";
+        remove_test_files(file);
+        let mut persistence = FileQuestionairePersistence::new(file).unwrap();
+        let q1 = QuestionEntry::builder().id("id01").build();
+        let q2 = QuestionEntry::builder().id("id02").build();
+        persistence.store_question(&q1, &QuestionAnswerInput::String(Some("Homer".to_string()))).unwrap();
+        persistence.store_timed_out_question(&q2, &QuestionAnswerInput::Int(Some(42))).unwrap();
+        drop(persistence);
+        assert_eq!("id01={\"String\":\"Homer\"}\nid02={\"answer\":{\"Int\":42},\"timed_out\":true}\n",
+            fs::read_to_string(file).unwrap());
+
+        let v = read_journal(file).unwrap();
+        assert!(! v[0].timed_out);
+        assert_eq!(QuestionAnswer { id: "id02".to_string(), answer: QuestionAnswerInput::Int(Some(42)), timed_out: true }, v[1]);
+        remove_test_files(file);
+    }
+
+    #[test]
+    fn test_lock() {
+        let file = "tmp/test_lock.tmp";
+        remove_test_files(file);
+        let mut p1 = FileQuestionairePersistence::new(file).unwrap();
+        let mut p2 = FileQuestionairePersistence::new(file).unwrap();
+        p1.lock().unwrap();
+        assert!(p2.lock().is_err());
+        drop(p1);
+        // the lock file is kept, only the lock is released
+        assert!(Path::new(&format!("{}.lock", file)).is_file());
+        assert!(p2.lock().is_ok());
+        drop(p2);
+        remove_test_files(file);
+    }
+
+    #[test]
+    fn test_keep_previous_journal() {
+        let file = "tmp/test_previous.tmp";
+        remove_test_files(file);
+        write_test_file(file, "id01={\"String\":\"Test Name\"}\nid02={\"String\":\"1999-01-01\"}\n");
+        let mut persistence = FileQuestionairePersistence::new(file).unwrap();
+        persistence.load(Some(file)).unwrap();
+        let q1 = QuestionEntry::builder().id("id01").build();
+        let q2 = QuestionEntry::builder().id("id02").build();
+
+        persistence.store_question(&q1, &QuestionAnswerInput::String(Some("Other Name".to_string()))).unwrap();
+        // the backup holds the previous journal
+        assert!(Path::new(&persistence.backup_file()).is_file());
+        assert_eq!(2, load_tmp_file(&persistence.backup_file()).unwrap().0.len());
+        assert_eq!(1, load_tmp_file(file).unwrap().0.len());
+
+        // a new load in this state takes the newer journal
+        let mut p2 = FileQuestionairePersistence::new(file).unwrap();
+        p2.load(Some(file)).unwrap();
+        assert_eq!(Some("Other Name".to_string()), (&p2.next_answer().unwrap()).into());
+        assert_eq!(None, p2.next_answer_id());
+
+        // the backup is kept until the questionaire is finished
+        persistence.store_question(&q2, &QuestionAnswerInput::String(Some("1999-01-02".to_string()))).unwrap();
+        assert!(Path::new(&persistence.backup_file()).is_file());
+        persistence.finish().unwrap();
+        assert!(! Path::new(&persistence.backup_file()).is_file());
+        let (v, _) = load_tmp_file(file).unwrap();
+        assert_eq!(2, v.len());
+        assert_eq!(Some("Other Name".to_string()), (&v[0]).into());
+        drop(persistence);
+        remove_test_files(file);
+    }
+
+    #[test]
+    fn test_load_backup_of_corrupted_journal() {
+        let file = "tmp/test_corrupted_journal.tmp";
+        remove_test_files(file);
+        write_test_file(file, "id01={\"Str\nid02={\"String\":\"x\"}\n");
+        write_test_file(&format!("{}.bak", file), "id01={\"String\":\"Test Name\"}\n");
+        let mut persistence = FileQuestionairePersistence::new(file).unwrap();
+        persistence.load(Some(file)).unwrap();
+        assert_eq!(Some("Test Name".to_string()), (&persistence.next_answer().unwrap()).into());
+        drop(persistence);
+        remove_test_files(file);
+    }
+
+    #[cfg(feature = "encryption")]
+    #[test]
+    fn test_encrypted_journal() {
+        let file = "tmp/test_encrypted.tmp";
+        remove_test_files(file);
+        let mut persistence = FileQuestionairePersistence::new(file).unwrap();
+        persistence.set_key(JournalKey::Passphrase("secret".to_string()));
+        let q1 = QuestionEntry::builder().id("id01").build();
+        persistence.store_question(&q1, &QuestionAnswerInput::String(Some("Test Name".to_string()))).unwrap();
+        assert!(persistence.is_encrypted());
+        drop(persistence);
+
+        let raw = fs::read(file).unwrap();
+        assert!(! raw.windows(9).any(|w| w == b"Test Name"));
+
+        let mut p2 = FileQuestionairePersistence::new(file).unwrap();
+        assert!(p2.load(Some(file)).is_err());
+        p2.set_key(JournalKey::Passphrase("wrong".to_string()));
+        assert!(p2.load(Some(file)).is_err());
+        p2.set_key(JournalKey::Passphrase("secret".to_string()));
+        p2.load(Some(file)).unwrap();
+        assert_eq!(Some("Test Name".to_string()), (&p2.next_answer().unwrap()).into());
+        drop(p2);
+        remove_test_files(file);
+
+        let mut p3 = FileQuestionairePersistence::new(file).unwrap();
+        p3.require_key(true);
+        assert!(p3.store_question(&q1, &QuestionAnswerInput::String(Some("Test Name".to_string()))).is_err());
+        assert!(! Path::new(file).is_file());
+        drop(p3);
+        remove_test_files(file);
+    }
+}
@@ -0,0 +1,106 @@
+//! Progress of a running questionaire
+//!
+//! The controller counts the answered screens and estimates the remaining ones
+//! along the current path: a declined block drops its entries, a block that is
+//! looped again adds them and every answer of a repeated question is a step of
+//! its own. So the total can change while the questionaire runs. Info texts
+//! aren't steps, a proceed query answered with 'no' drops the skipped entries.
+//!
+use crate::questionaire::{EntryType, QuestionaireEntry, SubBlock};
+
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Progress {
+    /// answered questions and block decisions
+    pub answered: usize,
+    /// questions and block decisions, that are still expected on the current path
+    pub remaining: usize,
+}
+
+impl Progress {
+    /// Progress of a questionaire, that isn't started yet
+    pub fn new(init_block: &SubBlock) -> Self {
+        Progress { answered: 0, remaining: entries_count(&init_block.entries) }
+    }
+
+    pub fn total(&self) -> usize {
+        self.answered + self.remaining
+    }
+
+    /// Number of the current step, starts at 1 and is at most the total
+    pub fn current(&self) -> usize {
+        (self.answered + 1).min(self.total())
+    }
+
+    pub fn ratio(&self) -> f64 {
+        if self.total() == 0 {
+            1.0
+        } else {
+            self.answered as f64 / self.total() as f64
+        }
+    }
+
+    pub fn percent(&self) -> usize {
+        (self.ratio() * 100.0).round() as usize
+    }
+
+    /// Bar with the percentage, e.g. `[#####-----]  50%` for ASCII
+    pub fn bar(&self, width: usize, ascii: bool) -> String {
+        let done = ((self.ratio() * width as f64).round() as usize).min(width);
+        let (full, empty) = if ascii { ("#", "-") } else { ("█", "░") };
+        let bar = format!("{}{}", full.repeat(done), empty.repeat(width - done));
+        if ascii {
+            format!("[{}] {:>3}%", bar, self.percent())
+        } else {
+            format!("{} {:>3}%", bar, self.percent())
+        }
+    }
+
+    pub(crate) fn step_done(&mut self) {
+        self.answered += 1;
+        self.remaining = self.remaining.saturating_sub(1);
+    }
+
+    /// Answer of a repeated question, that isn't the last one
+    pub(crate) fn repeated_step_done(&mut self) {
+        self.answered += 1;
+    }
+
+    pub(crate) fn skip(&mut self, count: usize) {
+        self.remaining = self.remaining.saturating_sub(count);
+    }
+
+    pub(crate) fn add(&mut self, count: usize) {
+        self.remaining += count;
+    }
+}
+
+/// Steps of the entries: one per question and per block decision
+pub(crate) fn entries_count(entries: &[QuestionaireEntry]) -> usize {
+    entries.iter().map(|e| match e {
+        QuestionaireEntry::Block(b) => 1 + entries_count(&b.entries),
+        QuestionaireEntry::Question(q) if q.entry_type == EntryType::InfoTxt => 0,
+        _ => 1,
+    }).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bar() {
+        let mut p = Progress { answered: 0, remaining: 4 };
+        assert_eq!("[----]   0%", p.bar(4, true));
+        assert_eq!(1, p.current());
+        p.step_done();
+        p.repeated_step_done();
+        assert_eq!("██░░  40%", p.bar(4, false));
+        assert_eq!(3, p.current());
+        p.skip(3);
+        assert_eq!(2, p.total());
+        assert_eq!(2, p.current());
+        assert_eq!(100, p.percent());
+        assert_eq!(100, Progress::default().percent());
+    }
+}
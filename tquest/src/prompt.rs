@@ -0,0 +1,142 @@
+//! Single prompts without a questionaire
+//!
+//! The prompts are the question screens of the terminal UI, with the same hints,
+//! validation, menus and history. The texts are written to stderr and the input is
+//! read from the terminal, so stdout stays free for the answer, e.g. in shell
+//! scripts: `name=$(tquest ask string "Name?")`
+//!
+//! ```no_run
+//! use tquest::{ask_int, confirm, IntEntry};
+//!
+//! let workers = ask_int(&IntEntry::builder().min(1).max(10).default_value(3).build(), "How many workers?").unwrap();
+//! if confirm("Start now?").unwrap() {
+//!     println!("starting {:?} workers", workers);
+//! }
+//! ```
+//!
+use crate::questionaire::{BoolEntry, EntryType, FloatEntry, IntEntry, OptionEntry, QuestionAnswerInput, QuestionEntry, StringEntry};
+use crate::ui::{ProceedScreenResult, QuestionScreenResult, QuestionaireView, Ui};
+use anyhow::{anyhow, Result};
+
+
+/// Asks a single question until a valid answer is given. Returns None, if the
+/// user canceled with CTRL-C or CTRL-D.
+pub fn ask(question_entry: &QuestionEntry) -> Result<Option<QuestionAnswerInput>> {
+    ask_in(&mut Ui::new()?, question_entry)
+}
+
+/// Asks a single question with the screen of the given terminal UI, e.g. one
+/// with another theme or in the accessible mode. Returns None, if the user
+/// canceled with CTRL-C or CTRL-D.
+pub fn ask_in(ui: &mut Ui, question_entry: &QuestionEntry) -> Result<Option<QuestionAnswerInput>> {
+    match question_entry.entry_type {
+        EntryType::ProceedQuery(_) | EntryType::InfoTxt => {
+            return Err(anyhow!("Only questions with an input can be asked"));
+        },
+        _ => {},
+    }
+    ui.set_exit_on_cancel(false);
+    let ret = if let EntryType::Bool(b) = &question_entry.entry_type {
+        // the same screen as the block decisions, only ENTER takes the default or yes
+        let text = &question_entry.query_text;
+        let help_text = question_entry.help_text.as_deref();
+        ui.proceed_screen(&question_entry.id, text, help_text, (0, 0), None, b.default_value.unwrap_or(true))
+            .map(|r| match r {
+                ProceedScreenResult::Proceeded(v) => QuestionScreenResult::Proceeded(QuestionAnswerInput::Bool(Some(v))),
+                ProceedScreenResult::TimedOut(v) => QuestionScreenResult::TimedOut(QuestionAnswerInput::Bool(Some(v))),
+                ProceedScreenResult::Canceled => QuestionScreenResult::Canceled,
+            })
+    } else {
+        ui.show_question_screen(question_entry, 0, None)
+    };
+    ui.set_exit_on_cancel(true);
+    match ret? {
+        QuestionScreenResult::Proceeded(a) | QuestionScreenResult::TimedOut(a) => Ok(Some(a)),
+        QuestionScreenResult::Canceled => Ok(None),
+    }
+}
+
+fn ask_entry(text: &str, entry_type: EntryType) -> Result<Option<QuestionAnswerInput>> {
+    let question_entry = QuestionEntry {
+        id: String::new(),
+        pos: 0,
+        required: true,
+        query_text: text.to_string(),
+        help_text: None,
+        entry_type,
+        timeout: None,
+    };
+    ask(&question_entry)
+}
+
+fn unexpected(a: QuestionAnswerInput) -> anyhow::Error {
+    anyhow!("Unexpected answer type: {:?}", a)
+}
+
+/// Asks for a string. Returns None, if the user canceled.
+pub fn ask_string(entry: &StringEntry, text: &str) -> Result<Option<String>> {
+    match ask_entry(text, EntryType::String(entry.clone()))? {
+        None => Ok(None),
+        Some(QuestionAnswerInput::String(v)) => Ok(v),
+        Some(a) => Err(unexpected(a)),
+    }
+}
+
+/// Asks for an integer. Returns None, if the user canceled.
+pub fn ask_int(entry: &IntEntry, text: &str) -> Result<Option<i32>> {
+    match ask_entry(text, EntryType::Int(entry.clone()))? {
+        None => Ok(None),
+        Some(QuestionAnswerInput::Int(v)) => Ok(v),
+        Some(a) => Err(unexpected(a)),
+    }
+}
+
+/// Asks for a floating point number. Returns None, if the user canceled.
+pub fn ask_float(entry: &FloatEntry, text: &str) -> Result<Option<f32>> {
+    match ask_entry(text, EntryType::Float(entry.clone()))? {
+        None => Ok(None),
+        Some(QuestionAnswerInput::Float(v)) => Ok(v),
+        Some(a) => Err(unexpected(a)),
+    }
+}
+
+/// Asks a yes/no question with the hint of the block decisions. 'y' and 'yes'
+/// mean yes, 'n' and 'no' mean no and only ENTER takes the default or yes.
+/// Returns None, if the user canceled.
+pub fn ask_bool(entry: &BoolEntry, text: &str) -> Result<Option<bool>> {
+    match ask_entry(text, EntryType::Bool(entry.clone()))? {
+        None => Ok(None),
+        Some(QuestionAnswerInput::Bool(v)) => Ok(v),
+        Some(a) => Err(unexpected(a)),
+    }
+}
+
+/// Asks to choose one of the options and returns the chosen option. Returns None,
+/// if the user canceled.
+pub fn ask_option(entry: &OptionEntry, text: &str) -> Result<Option<String>> {
+    match ask_entry(text, EntryType::Option(entry.clone()))? {
+        None => Ok(None),
+        Some(QuestionAnswerInput::Option(v)) => Ok(v),
+        Some(a) => Err(unexpected(a)),
+    }
+}
+
+/// Asks a yes/no question, where 'y', 'yes' and only ENTER mean yes. Canceling means no.
+pub fn confirm(text: &str) -> Result<bool> {
+    let entry = BoolEntry { default_value: Some(true) };
+    Ok(ask_bool(&entry, text)?.unwrap_or(false))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_no_input_entry() {
+        let e = QuestionEntry::builder()
+            .query_text("Info")
+            .entry_type(EntryType::InfoTxt)
+            .build();
+        assert!(ask(&e).is_err());
+    }
+}
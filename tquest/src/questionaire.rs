@@ -10,6 +10,7 @@ use serde::{Deserialize, Serialize};
 
 /// Expected string entry
 #[derive(Debug, BuilderFromDefault, Clone, Default, Deserialize, Serialize, PartialEq)]
+#[serde(default)]
 pub struct StringEntry {
     pub default_value: Option<String>,
     pub regexp: Option<String>,
@@ -54,6 +55,7 @@ impl StringEntry {
 
 /// Expected int entry
 #[derive(Debug, BuilderFromDefault, Default, Clone, Deserialize, Serialize, PartialEq)]
+#[serde(default)]
 pub struct IntEntry {
     pub default_value: Option<i32>,
     pub max: Option<i32>,
@@ -94,6 +96,7 @@ impl IntEntry {
 
 /// Expected floating point entry
 #[derive(Debug, BuilderFromDefault, Clone, Default, Deserialize, Serialize, PartialEq)]
+#[serde(default)]
 pub struct FloatEntry {
     pub default_value: Option<f32>,
     pub max: Option<f32>,
@@ -134,6 +137,7 @@ impl FloatEntry {
 
 /// Expected bool entry
 #[derive(Debug, BuilderFromDefault, Clone, Default, Deserialize, Serialize, PartialEq)]
+#[serde(default)]
 pub struct BoolEntry {
     pub default_value: Option<bool>,
 }
@@ -161,6 +165,7 @@ impl BoolEntry {
 
 /// Expected String entry based on a number of predefined options
 #[derive(Debug, BuilderFromDefault, Clone, Default, Deserialize, Serialize, PartialEq)]
+#[serde(default)]
 pub struct OptionEntry {
     /// represents the index of the array, used as default value
     pub default_value: Option<u32>,
@@ -231,6 +236,7 @@ pub enum QuestionaireEntry {
 
 
 #[derive(Debug, Clone, Default, BuilderFromDefault, Deserialize, Serialize, PartialEq)]
+#[serde(default)]
 pub struct SubBlock {
     pub id: String,
     pub pos: Option<usize>,
@@ -242,6 +248,7 @@ pub struct SubBlock {
 }
 
 #[derive(BuilderFromDefault, Debug, Clone, Default, Deserialize, Serialize, PartialEq)]
+#[serde(default)]
 pub struct QuestionEntry {
     pub id: String,
     pub pos: usize,
@@ -249,9 +256,13 @@ pub struct QuestionEntry {
     pub query_text: String,
     pub help_text: Option<String>,
     pub entry_type: EntryType,
+    /// seconds until the default value is taken, overrides the timeout of the runner
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<u64>,
 }
 
 #[derive(BuilderFromDefault, Debug, Clone, Default, Deserialize, Serialize, PartialEq)]
+#[serde(default)]
 pub struct RepeatedQuestionEntry {
     pub id: String,
     pub pos: usize,
@@ -266,9 +277,25 @@ pub struct RepeatedQuestionEntry {
 #[derive(Debug, Deserialize, Serialize, PartialEq)]
 pub struct Questionaire {
     /// Hashmap of level to list of questions per level
+    #[serde(default)]
     pub title: String,
     pub pos_count: Option<usize>,
     pub init_block: SubBlock,
+    /// Files, that are rendered from the answers after the questionaire is finished
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub templates: Vec<OutputTemplate>,
+}
+
+/// Template for a file, that is generated from the answers. The target path
+/// is a template too, so it can contain answers.
+#[derive(Debug, Clone, Default, BuilderFromDefault, Deserialize, Serialize, PartialEq)]
+pub struct OutputTemplate {
+    /// path of the generated file, relative to the output directory
+    pub target: String,
+    /// template text
+    pub content: Option<String>,
+    /// file with the template text, used if no content is given
+    pub source: Option<String>,
 }
 
 impl Questionaire {
@@ -287,6 +314,7 @@ pub struct QuestionaireBuilder<'a> {
     help_text: Option<&'a str>,
     question_count: usize,
     questions: Option<Vec<QuestionaireEntry>>,
+    templates: Vec<OutputTemplate>,
 }
 
 impl <'a> QuestionaireBuilder<'a> {
@@ -358,6 +386,11 @@ impl <'a> QuestionaireBuilder<'a> {
         }
     }
 
+    pub fn templates(&mut self, t: Vec<OutputTemplate>) -> &mut Self {
+        self.templates = t;
+        self
+    }
+
     pub fn questions(&mut self, q: Vec<QuestionaireEntry>) -> &mut Self {
         self.questions = Some(q);
         self.init_positions();
@@ -383,6 +416,7 @@ impl <'a> QuestionaireBuilder<'a> {
             title: self.title.to_string(),
             pos_count: Some(self.question_count),
             init_block,
+            templates: self.templates.clone(),
         }
     }
 }
@@ -393,6 +427,8 @@ pub enum AnswerEntry {
     Block(BlockAnswer),
     Question(QuestionAnswer),
     RepeatedQuestion(RepeatedQuestionAnswers),
+    /// Id of an entry, that was skipped because a proceed query was answered with 'no'
+    Skipped(String),
 }
 
 
@@ -465,6 +501,9 @@ pub struct QuestionAnswer {
     pub id: String,
 
     pub answer: QuestionAnswerInput,
+    /// true, if the answer was taken after a timeout and not entered by a person
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub timed_out: bool,
 }
 
 impl Into<Option<String>> for &QuestionAnswer {
@@ -503,6 +542,9 @@ pub struct RepeatedQuestionAnswers {
     pub id: String,
 
     pub answers: Vec<QuestionAnswerInput>,
+    /// true, if a timeout ended the list of answers and not a person
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub timed_out: bool,
 }
 
 impl Into<Option<Vec<String>>> for &RepeatedQuestionAnswers {
@@ -0,0 +1,285 @@
+//! Named sessions, to keep multiple drafts per questionaire
+//!
+//! Every draft is a persistence file in a per-user data directory:
+//! `<data-dir>/tquest/<questionaire-id>/<session-name>.tmp`
+//!
+use crate::persistence;
+use anyhow::{anyhow, Result};
+
+use std::cmp::Reverse;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+
+const DRAFT_EXTENSION: &str = "tmp";
+/// id of the journal entry, that marks a finished questionaire
+const FINISHED_MARKER: &str = "00000000";
+
+/// Meta data of one stored draft
+#[derive(Debug, Clone, PartialEq)]
+pub struct SessionDraft {
+    pub name: String,
+    pub file: String,
+    /// number of stored answers, None if the draft is encrypted
+    pub answer_count: Option<usize>,
+    /// true, if the questionaire was already completed in this draft
+    pub finished: bool,
+    pub modified: SystemTime,
+}
+
+impl SessionDraft {
+    /// Short description for session pickers, e.g. `homer (5 answers, 2 hours ago)`
+    pub fn description(&self) -> String {
+        self.description_at(SystemTime::now())
+    }
+
+    /// Description with the age relative to `now`
+    pub fn description_at(&self, now: SystemTime) -> String {
+        let progress = match self.answer_count {
+            Some(_) if self.finished => "finished".to_string(),
+            Some(1) => "1 answer".to_string(),
+            Some(c) => format!("{} answers", c),
+            None => "encrypted".to_string(),
+        };
+        format!("{} ({}, {})", self.name, progress, format_age(self.modified, now))
+    }
+}
+
+/// Access to the drafts of one questionaire
+pub struct SessionStore {
+    dir: PathBuf,
+    clock: fn() -> SystemTime,
+}
+
+impl SessionStore {
+    /// Store in the data directory of the current user
+    pub fn new(questionaire_id: &str) -> Result<SessionStore> {
+        let base = if let Some(d) = dirs::data_dir() {
+            d
+        } else {
+            return Err(anyhow!("Can't find the data directory of the user"));
+        };
+        SessionStore::with_base_dir(&base.join("tquest"), questionaire_id)
+    }
+
+    /// Store below a custom base directory
+    pub fn with_base_dir(base_dir: &Path, questionaire_id: &str) -> Result<SessionStore> {
+        check_name(questionaire_id)?;
+        Ok(SessionStore {
+            dir: base_dir.join(questionaire_id),
+            clock: SystemTime::now,
+        })
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Clock for the age of the drafts, `SystemTime::now` by default
+    pub fn clock(&mut self, v: fn() -> SystemTime) -> &mut Self {
+        self.clock = v;
+        self
+    }
+
+    pub fn now(&self) -> SystemTime {
+        (self.clock)()
+    }
+
+    /// Path of the persistence file for the given session. The directory is created
+    /// if needed.
+    pub fn persistence_file(&self, name: &str) -> Result<String> {
+        check_name(name)?;
+        fs::create_dir_all(&self.dir)?;
+        Ok(self.draft_path(name).to_string_lossy().to_string())
+    }
+
+    fn draft_path(&self, name: &str) -> PathBuf {
+        self.dir.join(format!("{}.{}", name, DRAFT_EXTENSION))
+    }
+
+    pub fn exists(&self, name: &str) -> bool {
+        self.draft_path(name).is_file()
+    }
+
+    /// Lists all drafts, the last modified first
+    pub fn list(&self) -> Result<Vec<SessionDraft>> {
+        let mut ret: Vec<SessionDraft> = Vec::new();
+        if ! self.dir.is_dir() {
+            return Ok(ret);
+        }
+        for e in fs::read_dir(&self.dir)? {
+            let path = e?.path();
+            if ! path.is_file() || path.extension().and_then(|e| e.to_str()) != Some(DRAFT_EXTENSION) {
+                continue;
+            }
+            let name = if let Some(n) = path.file_stem().and_then(|n| n.to_str()) {
+                n.to_string()
+            } else {
+                continue;
+            };
+            let modified = fs::metadata(&path)?.modified()?;
+            let raw = fs::read(&path)?;
+            let (answer_count, finished) = match persistence::parse_journal(&raw, &path.to_string_lossy()) {
+                Ok((answers, _)) => {
+                    let finished = answers.iter().any(|a| a.id == FINISHED_MARKER);
+                    let count = answers.iter().filter(|a| a.id != FINISHED_MARKER).count();
+                    (Some(count), finished)
+                },
+                Err(_) => (None, false),
+            };
+            ret.push(SessionDraft {
+                name,
+                file: path.to_string_lossy().to_string(),
+                answer_count,
+                finished,
+                modified,
+            });
+        }
+        ret.sort_by_key(|d| Reverse(d.modified));
+        Ok(ret)
+    }
+
+    pub fn rename(&self, old_name: &str, new_name: &str) -> Result<()> {
+        check_name(new_name)?;
+        let old = self.draft_path(old_name);
+        let new = self.draft_path(new_name);
+        if ! old.is_file() {
+            return Err(anyhow!("Unknown session: {}", old_name));
+        }
+        if new.exists() {
+            return Err(anyhow!("Session already exists: {}", new_name));
+        }
+        fs::rename(&old, &new)?;
+        let old_backup = backup_path(&old);
+        if old_backup.is_file() {
+            fs::rename(old_backup, backup_path(&new))?;
+        }
+        Ok(())
+    }
+
+    pub fn delete(&self, name: &str) -> Result<()> {
+        let p = self.draft_path(name);
+        if ! p.is_file() {
+            return Err(anyhow!("Unknown session: {}", name));
+        }
+        fs::remove_file(&p)?;
+        let backup = backup_path(&p);
+        if backup.is_file() {
+            fs::remove_file(backup)?;
+        }
+        Ok(())
+    }
+
+    /// Removes all drafts, that weren't modified for the given time. Returns the
+    /// names of the removed drafts.
+    pub fn expire(&self, max_age: Duration) -> Result<Vec<String>> {
+        let now = self.now();
+        let mut ret: Vec<String> = Vec::new();
+        for d in self.list()? {
+            let age = now.duration_since(d.modified).unwrap_or_default();
+            if age > max_age {
+                self.delete(&d.name)?;
+                ret.push(d.name);
+            }
+        }
+        Ok(ret)
+    }
+}
+
+fn backup_path(p: &Path) -> PathBuf {
+    PathBuf::from(format!("{}.bak", p.to_string_lossy()))
+}
+
+/// Names are used as file names, so only a safe subset of characters is allowed
+fn check_name(name: &str) -> Result<()> {
+    if name.is_empty() || name.starts_with('.') || name.len() > 100 {
+        return Err(anyhow!("Invalid session name: '{}'", name));
+    }
+    if ! name.chars().all(|c| c.is_alphanumeric() || c == '_' || c == '-' || c == '.' || c == ' ') {
+        return Err(anyhow!("Invalid session name: '{}', only letters, digits, ' ', '.', '_' and '-' are allowed", name));
+    }
+    Ok(())
+}
+
+fn format_age(modified: SystemTime, now: SystemTime) -> String {
+    let secs = now.duration_since(modified).unwrap_or_default().as_secs();
+    match secs {
+        0..=59 => "just now".to_string(),
+        60..=3599 => format!("{} min ago", secs / 60),
+        3600..=86399 => format!("{} hours ago", secs / 3600),
+        _ => format!("{} days ago", secs / 86400),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_store(name: &str) -> SessionStore {
+        let store = SessionStore::with_base_dir(Path::new("tmp/sessions"), name).unwrap();
+        let _ = fs::remove_dir_all(store.dir());
+        store
+    }
+
+    #[test]
+    fn test_list_rename_delete() {
+        let store = test_store("test_list_rename_delete");
+        assert!(store.list().unwrap().is_empty());
+        let f = store.persistence_file("homer").unwrap();
+        fs::write(&f, "id01={\"String\":\"Homer\"}\nid02={\"String\":\"1956-03-12\"}\n").unwrap();
+        let f2 = store.persistence_file("marge").unwrap();
+        fs::write(&f2, "id01={\"String\":\"Marge\"}\n00000000={\"String\":\"done\"}\n").unwrap();
+
+        let drafts = store.list().unwrap();
+        assert_eq!(2, drafts.len());
+        let homer = drafts.iter().find(|d| d.name == "homer").unwrap();
+        assert_eq!(Some(2), homer.answer_count);
+        assert!(! homer.finished);
+        let marge = drafts.iter().find(|d| d.name == "marge").unwrap();
+        assert_eq!(Some(1), marge.answer_count);
+        assert!(marge.finished);
+        assert!(marge.description().starts_with("marge (finished, "));
+        assert_eq!("homer (2 answers, 2 hours ago)", homer.description_at(homer.modified + Duration::from_secs(7300)));
+
+        assert!(store.rename("homer", "marge").is_err());
+        assert!(store.rename("homer", "../homer").is_err());
+        store.rename("homer", "homer simpson").unwrap();
+        assert!(! store.exists("homer"));
+        assert!(store.exists("homer simpson"));
+
+        store.delete("homer simpson").unwrap();
+        assert!(store.delete("homer simpson").is_err());
+        assert_eq!(1, store.list().unwrap().len());
+        let _ = fs::remove_dir_all(store.dir());
+    }
+
+    #[test]
+    fn test_expire() {
+        let store = test_store("test_expire");
+        let f = store.persistence_file("old").unwrap();
+        fs::write(&f, "id01={\"String\":\"Homer\"}\n").unwrap();
+        let old = SystemTime::now() - Duration::from_secs(3 * 86400);
+        fs::File::options().write(true).open(&f).unwrap().set_modified(old).unwrap();
+        let f2 = store.persistence_file("new").unwrap();
+        fs::write(&f2, "id01={\"String\":\"Marge\"}\n").unwrap();
+
+        let removed = store.expire(Duration::from_secs(86400)).unwrap();
+        assert_eq!(vec!["old".to_string()], removed);
+        let drafts = store.list().unwrap();
+        assert_eq!(1, drafts.len());
+        assert_eq!("new", drafts[0].name);
+        let _ = fs::remove_dir_all(store.dir());
+    }
+
+    #[test]
+    fn test_invalid_names() {
+        assert!(SessionStore::with_base_dir(Path::new("tmp"), "../x").is_err());
+        let store = test_store("test_invalid_names");
+        assert!(store.persistence_file("").is_err());
+        assert!(store.persistence_file("a/b").is_err());
+        assert!(store.persistence_file(".hidden").is_err());
+        assert!(store.persistence_file("draft 1").is_ok());
+        let _ = fs::remove_dir_all(store.dir());
+    }
+}
@@ -0,0 +1,412 @@
+//! SQLite based persistence, that holds the sessions of many questionaires
+//! and respondents in one database file.
+//!
+use crate::persistence::QuestionairePersistence;
+use crate::questionaire::{QuestionAnswer, QuestionAnswerInput, QuestionEntry};
+use anyhow::{anyhow, Result};
+use rusqlite::{params, Connection, OptionalExtension};
+
+use std::collections::VecDeque;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS sessions (
+    id INTEGER PRIMARY KEY,
+    questionaire_id TEXT NOT NULL,
+    session_id TEXT NOT NULL,
+    created INTEGER NOT NULL,
+    updated INTEGER NOT NULL,
+    UNIQUE (questionaire_id, session_id)
+);
+CREATE TABLE IF NOT EXISTS answers (
+    session INTEGER NOT NULL REFERENCES sessions(id) ON DELETE CASCADE,
+    seq INTEGER NOT NULL,
+    question_id TEXT NOT NULL,
+    answer TEXT NOT NULL,
+    timed_out INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY (session, seq)
+);
+CREATE TABLE IF NOT EXISTS block_decisions (
+    session INTEGER NOT NULL REFERENCES sessions(id) ON DELETE CASCADE,
+    seq INTEGER NOT NULL,
+    block_id TEXT NOT NULL,
+    decision INTEGER NOT NULL,
+    PRIMARY KEY (session, seq)
+);
+";
+
+/// Overview over one stored session
+#[derive(Debug, Clone, PartialEq)]
+pub struct SessionInfo {
+    pub id: String,
+    pub questionaire_id: String,
+    pub answer_count: usize,
+    /// seconds since the unix epoch
+    pub created: u64,
+    /// seconds since the unix epoch
+    pub updated: u64,
+}
+
+/// Persistence, that stores the answers and block decisions of a session
+/// in a SQLite database. A session is identified by its id and belongs to
+/// one questionaire.
+pub struct SqliteQuestionairePersistence {
+    conn: Connection,
+    questionaire_id: String,
+    session_id: String,
+    data: Vec<QuestionAnswer>,
+    current_pos: usize,
+    /// block decisions of the loaded session, that are not taken yet
+    decisions: VecDeque<(String, bool)>,
+    /// next sequence number of stored answers and block decisions
+    seq: i64,
+    /// true, as long as nothing is written in the current run
+    fresh_run: bool,
+}
+
+impl SqliteQuestionairePersistence {
+    /// Opens (or creates) the database file and uses the session with the given id.
+    /// The session is created with the first stored answer.
+    pub fn new(db_file: &str, questionaire_id: &str, session_id: &str) -> Result<SqliteQuestionairePersistence> {
+        let conn = Connection::open(db_file)?;
+        conn.execute_batch("PRAGMA foreign_keys = ON;")?;
+        conn.execute_batch(SCHEMA)?;
+        // databases of older versions have no timed_out column
+        let has_timed_out: bool = conn.query_row(
+            "SELECT COUNT(*) > 0 FROM pragma_table_info('answers') WHERE name = 'timed_out'", [], |row| row.get(0))?;
+        if ! has_timed_out {
+            conn.execute_batch("ALTER TABLE answers ADD COLUMN timed_out INTEGER NOT NULL DEFAULT 0;")?;
+        }
+        Ok(SqliteQuestionairePersistence {
+            conn,
+            questionaire_id: questionaire_id.to_string(),
+            session_id: session_id.to_string(),
+            data: vec![],
+            current_pos: 0,
+            decisions: VecDeque::new(),
+            seq: 0,
+            fresh_run: true,
+        })
+    }
+
+    pub fn session_id(&self) -> &str {
+        &self.session_id
+    }
+
+    /// Lists all sessions of the questionaire, the last updated first
+    pub fn list_sessions(&self) -> Result<Vec<SessionInfo>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT s.session_id, s.questionaire_id, s.created, s.updated,
+                (SELECT COUNT(*) FROM answers a WHERE a.session = s.id)
+            FROM sessions s WHERE s.questionaire_id = ?1 ORDER BY s.updated DESC, s.session_id")?;
+        let rows = stmt.query_map(params![self.questionaire_id], |row| {
+            Ok(SessionInfo {
+                id: row.get(0)?,
+                questionaire_id: row.get(1)?,
+                created: row.get::<_, i64>(2)? as u64,
+                updated: row.get::<_, i64>(3)? as u64,
+                answer_count: row.get::<_, i64>(4)? as usize,
+            })
+        })?;
+        let mut ret: Vec<SessionInfo> = Vec::new();
+        for r in rows {
+            ret.push(r?);
+        }
+        Ok(ret)
+    }
+
+    pub fn has_session(&self, session_id: &str) -> Result<bool> {
+        Ok(self.session_key(session_id)?.is_some())
+    }
+
+    /// internal key of the session in the database
+    fn session_key(&self, session_id: &str) -> Result<Option<i64>> {
+        let ret = self.conn.query_row(
+            "SELECT id FROM sessions WHERE session_id = ?1 AND questionaire_id = ?2",
+            params![session_id, self.questionaire_id],
+            |row| row.get(0)).optional()?;
+        Ok(ret)
+    }
+
+    /// Removes the session with all its answers and block decisions
+    pub fn delete_session(&mut self, session_id: &str) -> Result<()> {
+        let count = self.conn.execute(
+            "DELETE FROM sessions WHERE session_id = ?1 AND questionaire_id = ?2",
+            params![session_id, self.questionaire_id])?;
+        if count == 0 {
+            return Err(anyhow!("Unknown session: {}", session_id));
+        }
+        if session_id == self.session_id {
+            self.data.clear();
+            self.current_pos = 0;
+            self.decisions.clear();
+            self.seq = 0;
+            self.fresh_run = true;
+        }
+        Ok(())
+    }
+
+    /// Returns the block decisions of the current session in the order they were taken
+    pub fn block_decisions(&self) -> Result<Vec<(String, bool)>> {
+        self.load_block_decisions(&self.session_id)
+    }
+
+    fn load_block_decisions(&self, session_id: &str) -> Result<Vec<(String, bool)>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT b.block_id, b.decision FROM block_decisions b JOIN sessions s ON b.session = s.id
+            WHERE s.session_id = ?1 AND s.questionaire_id = ?2 ORDER BY b.seq")?;
+        let rows = stmt.query_map(params![session_id, self.questionaire_id], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, bool>(1)?))
+        })?;
+        let mut ret: Vec<(String, bool)> = Vec::new();
+        for r in rows {
+            ret.push(r?);
+        }
+        Ok(ret)
+    }
+
+    fn load_answers(&self, session_id: &str) -> Result<Vec<QuestionAnswer>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT a.question_id, a.answer, a.timed_out FROM answers a JOIN sessions s ON a.session = s.id
+            WHERE s.session_id = ?1 AND s.questionaire_id = ?2 ORDER BY a.seq")?;
+        let rows = stmt.query_map(params![session_id, self.questionaire_id], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, bool>(2)?))
+        })?;
+        let mut ret: Vec<QuestionAnswer> = Vec::new();
+        for r in rows {
+            let (id, json_str, timed_out) = r?;
+            let answer = serde_json::from_str::<QuestionAnswerInput>(&json_str)?;
+            ret.push(QuestionAnswer { id, answer, timed_out });
+        }
+        Ok(ret)
+    }
+
+    /// Runs the insert of one entry in a transaction. With the first write of a run
+    /// the old entries of the session are replaced, so that a resumed session
+    /// never mixes the old and the new run.
+    fn write<F>(&mut self, insert: F) -> Result<()>
+        where F: FnOnce(&rusqlite::Transaction, i64, i64) -> rusqlite::Result<usize> {
+        let now = now();
+        let tx = self.conn.transaction()?;
+        tx.execute(
+            "INSERT INTO sessions (questionaire_id, session_id, created, updated) VALUES (?1, ?2, ?3, ?3)
+            ON CONFLICT(questionaire_id, session_id) DO UPDATE SET updated = ?3",
+            params![self.questionaire_id, self.session_id, now])?;
+        let session: i64 = tx.query_row(
+            "SELECT id FROM sessions WHERE session_id = ?1 AND questionaire_id = ?2",
+            params![self.session_id, self.questionaire_id],
+            |row| row.get(0))?;
+        if self.fresh_run {
+            tx.execute("DELETE FROM answers WHERE session = ?1", params![session])?;
+            tx.execute("DELETE FROM block_decisions WHERE session = ?1", params![session])?;
+        }
+        insert(&tx, session, self.seq)?;
+        tx.commit()?;
+        self.fresh_run = false;
+        self.seq += 1;
+        Ok(())
+    }
+
+    fn insert_answer(&mut self, entry: &QuestionEntry, data: &QuestionAnswerInput, timed_out: bool) -> Result<()> {
+        let json_string = serde_json::to_string(data)?;
+        self.write(|tx, session, seq| {
+            tx.execute(
+                "INSERT INTO answers (session, seq, question_id, answer, timed_out) VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![session, seq, entry.id, json_string, timed_out])
+        })
+    }
+}
+
+impl QuestionairePersistence for SqliteQuestionairePersistence {
+    fn store_question(&mut self, entry: &QuestionEntry, data: &QuestionAnswerInput) -> Result<()> {
+        self.insert_answer(entry, data, false)
+    }
+
+    fn store_timed_out_question(&mut self, entry: &QuestionEntry, data: &QuestionAnswerInput) -> Result<()> {
+        self.insert_answer(entry, data, true)
+    }
+
+    fn store_block_decision(&mut self, block_id: &str, decision: bool) -> Result<()> {
+        self.write(|tx, session, seq| {
+            tx.execute(
+                "INSERT INTO block_decisions (session, seq, block_id, decision) VALUES (?1, ?2, ?3, ?4)",
+                params![session, seq, block_id, decision])
+        })
+    }
+
+    /// Loads the answers and block decisions of the session with the given id, to
+    /// resume it. Without a given id, the current session is loaded.
+    fn load(&mut self, source: Option<&str>) -> Result<()> {
+        let session_id = source.unwrap_or(&self.session_id).to_string();
+        if ! self.has_session(&session_id)? {
+            return Err(anyhow!("Unknown session: {}", session_id));
+        }
+        self.data = self.load_answers(&session_id)?;
+        self.decisions = self.load_block_decisions(&session_id)?.into();
+        self.current_pos = 0;
+        self.session_id = session_id;
+        self.seq = 0;
+        self.fresh_run = true;
+        Ok(())
+    }
+
+    fn import(&mut self, data_to_import: &Vec<QuestionAnswer>) {
+        for i in data_to_import {
+            self.data.push(i.clone());
+        }
+    }
+
+    /// The decisions are taken in the stored order. Stale decisions before the
+    /// next one of the block, e.g. of a block that the resumed run skipped, are
+    /// dropped. Without a stored decision it's derived from the answers.
+    fn next_block_decision(&mut self, block_id: &str) -> Option<bool> {
+        let pos = self.decisions.iter().position(|(id, _)| id == block_id)?;
+        self.decisions.drain(..pos);
+        self.decisions.pop_front().map(|(_, d)| d)
+    }
+
+    fn next_answer(&mut self) -> Option<QuestionAnswer> {
+        let ret = self.data.get(self.current_pos).cloned();
+        if ret.is_some() {
+            self.current_pos += 1;
+        }
+        ret
+    }
+
+    fn next_answer_id(&mut self) -> Option<String> {
+        self.data.get(self.current_pos).map(|a| a.id.to_string())
+    }
+}
+
+fn now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn string_answer(v: &str) -> QuestionAnswerInput {
+        QuestionAnswerInput::String(Some(v.to_string()))
+    }
+
+    fn store_two_answers(p: &mut SqliteQuestionairePersistence) {
+        let q1 = QuestionEntry::builder().id("id01").build();
+        let q2 = QuestionEntry::builder().id("id02").build();
+        p.store_question(&q1, &string_answer("Homer")).unwrap();
+        p.store_block_decision("id03", false).unwrap();
+        p.store_question(&q2, &string_answer("1956-03-12")).unwrap();
+    }
+
+    #[test]
+    fn test_store_and_resume() {
+        let mut p = SqliteQuestionairePersistence::new(":memory:", "id00", "homer").unwrap();
+        store_two_answers(&mut p);
+        assert_eq!(vec![("id03".to_string(), false)], p.block_decisions().unwrap());
+
+        p.load(Some("homer")).unwrap();
+        assert_eq!(Some("id01".to_string()), p.next_answer_id());
+        assert_eq!(Some("Homer".to_string()), (&p.next_answer().unwrap()).into());
+        assert_eq!(Some("id02".to_string()), p.next_answer_id());
+        assert_eq!(Some("1956-03-12".to_string()), (&p.next_answer().unwrap()).into());
+        assert_eq!(None, p.next_answer_id());
+        assert_eq!(None, p.next_answer());
+        assert_eq!(None, p.next_block_decision("id04"));
+        assert_eq!(Some(false), p.next_block_decision("id03"));
+        assert_eq!(None, p.next_block_decision("id03"));
+
+        // the resumed run replaces the old answers
+        let q1 = QuestionEntry::builder().id("id01").build();
+        p.store_question(&q1, &string_answer("Marge")).unwrap();
+        p.load(None).unwrap();
+        assert_eq!(Some("Marge".to_string()), (&p.next_answer().unwrap()).into());
+        assert_eq!(None, p.next_answer());
+        assert!(p.block_decisions().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_store_timed_out_answer() {
+        let mut p = SqliteQuestionairePersistence::new(":memory:", "id00", "homer").unwrap();
+        store_two_answers(&mut p);
+        let q3 = QuestionEntry::builder().id("id03").build();
+        p.store_timed_out_question(&q3, &string_answer("Springfield")).unwrap();
+
+        p.load(None).unwrap();
+        assert!(! p.next_answer().unwrap().timed_out);
+        assert!(! p.next_answer().unwrap().timed_out);
+        let a = p.next_answer().unwrap();
+        assert_eq!(QuestionAnswer { id: "id03".to_string(), answer: string_answer("Springfield"), timed_out: true }, a);
+    }
+
+    #[test]
+    fn test_add_timed_out_column() {
+        let db_file = "tmp/test_old_schema.db";
+        let _ = std::fs::remove_file(db_file);
+        {
+            // the answers table of older versions
+            let conn = Connection::open(db_file).unwrap();
+            conn.execute_batch(&SCHEMA.replace("    timed_out INTEGER NOT NULL DEFAULT 0,\n", "")).unwrap();
+        }
+        let mut p = SqliteQuestionairePersistence::new(db_file, "id00", "homer").unwrap();
+        store_two_answers(&mut p);
+        p.load(None).unwrap();
+        assert!(! p.next_answer().unwrap().timed_out);
+        drop(p);
+        // a second open doesn't add the column again
+        assert!(SqliteQuestionairePersistence::new(db_file, "id00", "homer").is_ok());
+        let _ = std::fs::remove_file(db_file);
+    }
+
+    #[test]
+    fn test_skip_stale_block_decisions() {
+        let mut p = SqliteQuestionairePersistence::new(":memory:", "id00", "homer").unwrap();
+        p.store_block_decision("id03", true).unwrap();
+        p.store_block_decision("id04", false).unwrap();
+        p.store_block_decision("id05", true).unwrap();
+        p.store_block_decision("id05", false).unwrap();
+
+        p.load(Some("homer")).unwrap();
+        assert_eq!(Some(true), p.next_block_decision("id03"));
+        // a block, that has no stored decision, doesn't drop the others
+        assert_eq!(None, p.next_block_decision("id06"));
+        // the resumed run doesn't ask for id04 any longer
+        assert_eq!(Some(true), p.next_block_decision("id05"));
+        assert_eq!(Some(false), p.next_block_decision("id05"));
+        assert_eq!(None, p.next_block_decision("id04"));
+    }
+
+    #[test]
+    fn test_list_and_delete_sessions() {
+        let db_file = "tmp/test_sessions.db";
+        let _ = std::fs::remove_file(db_file);
+        let mut p = SqliteQuestionairePersistence::new(db_file, "id00", "homer").unwrap();
+        store_two_answers(&mut p);
+        assert!(p.load(Some("bart")).is_err());
+
+        // sessions of other questionaires are not visible
+        let mut other = SqliteQuestionairePersistence::new(db_file, "other", "homer").unwrap();
+        assert!(other.list_sessions().unwrap().is_empty());
+        assert!(other.load(Some("homer")).is_err());
+        let q1 = QuestionEntry::builder().id("id01").build();
+        other.store_question(&q1, &string_answer("Other Homer")).unwrap();
+        assert_eq!(1, other.list_sessions().unwrap()[0].answer_count);
+
+        let sessions = p.list_sessions().unwrap();
+        assert_eq!(1, sessions.len());
+        assert_eq!("homer", sessions[0].id);
+        assert_eq!("id00", sessions[0].questionaire_id);
+        assert_eq!(2, sessions[0].answer_count);
+
+        p.delete_session("homer").unwrap();
+        assert!(p.list_sessions().unwrap().is_empty());
+        assert!(p.block_decisions().unwrap().is_empty());
+        assert!(p.delete_session("homer").is_err());
+        drop(p);
+        drop(other);
+        let _ = std::fs::remove_file(db_file);
+    }
+}
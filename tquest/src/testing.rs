@@ -0,0 +1,376 @@
+//! Helpers to test questionaires without a terminal
+//!
+//! A `ScriptedView` gets the expected prompts in order, each with the answer to
+//! give. A prompt is matched by its id or its text. The answers are typed input,
+//! like in the terminal, and are validated by the question. Unexpected prompts
+//! panic with the expected and the actual prompt and the transcript so far.
+//!
+//! ```
+//! use tquest::testing::{assert_answers, run_scripted, ScriptedView};
+//! use tquest::{EntryType, Questionaire, QuestionaireEntry, QuestionaireResult, QuestionEntry, StringEntry};
+//!
+//! let questionaire = Questionaire::builder()
+//!     .id("q")
+//!     .start_text("Start?")
+//!     .end_text("Finish?")
+//!     .questions(vec![QuestionaireEntry::Question(QuestionEntry::builder()
+//!         .id("name")
+//!         .query_text("What's your name?")
+//!         .entry_type(EntryType::String(StringEntry::builder().build()))
+//!         .build())])
+//!     .build();
+//! let mut view = ScriptedView::new();
+//! view.answer("Start?", "y")
+//!     .answer("name", "Homer")
+//!     .answer("Finish?", "");
+//! match run_scripted(&questionaire, &mut view).unwrap() {
+//!     QuestionaireResult::Finished(a) => assert_answers(&a, &[("name", "Homer")]),
+//!     QuestionaireResult::Canceled => panic!("canceled"),
+//! }
+//! ```
+//!
+//! For golden-file tests `render_transcript` runs a questionaire with a `LineView`
+//! and returns the plain text, that the user would see, together with the inputs.
+//! `assert_golden` compares it with a stored file. With the environment variable
+//! `TQUEST_UPDATE_GOLDEN=1` the files are written instead.
+//!
+use anyhow::Result;
+
+use std::collections::VecDeque;
+use std::fs;
+use std::path::Path;
+
+use crate::controller::{QuestionaireController, QuestionaireResult};
+use crate::line_view::LineView;
+use crate::TITLE;
+use crate::export::flatten;
+use crate::persistence::NoPersistence;
+use crate::questionaire::{BlockAnswer, QuestionAnswerInput, QuestionEntry, Questionaire};
+use crate::ui::{get_timeout_answer, validate_input, MsgLevel, ProceedScreenResult, QuestionScreenResult, QuestionaireView};
+
+
+enum ScriptAction {
+    Input(String),
+    Cancel,
+    Timeout,
+}
+
+struct ScriptStep {
+    /// id or text of the prompt
+    prompt: String,
+    action: ScriptAction,
+}
+
+#[derive(Default)]
+pub struct ScriptedView {
+    steps: VecDeque<ScriptStep>,
+    transcript: Vec<String>,
+    fast_forward: bool,
+}
+
+impl ScriptedView {
+    pub fn new() -> Self {
+        ScriptedView::default()
+    }
+
+    /// Expects the prompt with the given id or text next and answers it with the input
+    pub fn answer(&mut self, prompt: &str, input: &str) -> &mut Self {
+        self.steps.push_back(ScriptStep { prompt: prompt.to_string(), action: ScriptAction::Input(input.to_string()) });
+        self
+    }
+
+    /// Expects the prompt with the given id or text next and cancels the questionaire
+    pub fn cancel(&mut self, prompt: &str) -> &mut Self {
+        self.steps.push_back(ScriptStep { prompt: prompt.to_string(), action: ScriptAction::Cancel });
+        self
+    }
+
+    /// Expects the prompt with the given id or text next and lets its time run out
+    pub fn timeout(&mut self, prompt: &str) -> &mut Self {
+        self.steps.push_back(ScriptStep { prompt: prompt.to_string(), action: ScriptAction::Timeout });
+        self
+    }
+
+    /// Lines of the shown titles, prompts, answers and messages
+    pub fn transcript(&self) -> &[String] {
+        &self.transcript
+    }
+
+    /// Panics, if not all steps of the script were used
+    pub fn assert_finished(&self) {
+        if ! self.steps.is_empty() {
+            let left: Vec<&str> = self.steps.iter().map(|s| s.prompt.as_str()).collect();
+            panic!("scripted view: the script has {} unused steps: {:?}\n{}", left.len(), left, self.transcript_text());
+        }
+    }
+
+    fn transcript_text(&self) -> String {
+        let mut s = "transcript:".to_string();
+        for l in &self.transcript {
+            s.push_str("\n  ");
+            s.push_str(l);
+        }
+        s
+    }
+
+    /// Takes the next step, that has to match the prompt
+    fn next_step(&mut self, id: &str, text: &str) -> ScriptAction {
+        self.transcript.push(format!("? [{}] {}", id, text));
+        let step = match self.steps.pop_front() {
+            Some(s) => s,
+            None => panic!("scripted view: no step left for the prompt\n  actual:   [{}] {}\n{}", id, text, self.transcript_text()),
+        };
+        if step.prompt != id && step.prompt != text {
+            panic!("scripted view: unexpected prompt\n  expected: {}\n  actual:   [{}] {}\n{}",
+                step.prompt, id, text, self.transcript_text());
+        }
+        match &step.action {
+            ScriptAction::Input(i) => self.transcript.push(format!("> {}", i)),
+            ScriptAction::Cancel => self.transcript.push("> (canceled)".to_string()),
+            ScriptAction::Timeout => self.transcript.push("> (timeout)".to_string()),
+        }
+        step.action
+    }
+
+    fn wrong_input(&self, input: &str) -> ! {
+        panic!("scripted view: wrong input `{}` for the last prompt\n{}", input, self.transcript_text());
+    }
+}
+
+impl QuestionaireView for ScriptedView {
+    fn print_title(&mut self, title: &str) {
+        self.transcript.push(format!("# {}", title));
+    }
+
+    fn set_fast_forward(&mut self, v: bool) {
+        self.fast_forward = v;
+    }
+
+    fn show_msg(&mut self, msg: &str, _level: MsgLevel) {
+        self.transcript.push(format!("! {}", msg));
+    }
+
+    /// Info texts don't need a step of the script
+    fn show_info_screen(&mut self, question_entry: &QuestionEntry) -> Result<ProceedScreenResult> {
+        self.transcript.push(format!("i [{}] {}", question_entry.id, question_entry.query_text));
+        Ok(ProceedScreenResult::Proceeded(true))
+    }
+
+    fn show_proceed_screen<'a, T: Into<Option<&'a str>>>(&mut self, id: &str, text: &str, _help_text: T, _question_count: usize, _current: usize, preferred: Option<bool>) -> Result<ProceedScreenResult> {
+        if let Some(p) = preferred.filter(|_| self.fast_forward) {
+            self.transcript.push(format!("? [{}] {}", id, text));
+            self.transcript.push(format!("> {} (autofilled)", if p { "y" } else { "n" }));
+            return Ok(ProceedScreenResult::Proceeded(p));
+        }
+        let input = match self.next_step(id, text) {
+            ScriptAction::Input(i) => i,
+            ScriptAction::Cancel => return Ok(ProceedScreenResult::Canceled),
+            ScriptAction::Timeout => return Ok(ProceedScreenResult::TimedOut(preferred.unwrap_or(true))),
+        };
+        match input.to_lowercase().trim() {
+            "y" | "yes" => Ok(ProceedScreenResult::Proceeded(true)),
+            "n" | "no" => Ok(ProceedScreenResult::Proceeded(false)),
+            "" => Ok(ProceedScreenResult::Proceeded(preferred.unwrap_or(true))),
+            _ => self.wrong_input(&input),
+        }
+    }
+
+    fn show_question_screen(&mut self, question_entry: &QuestionEntry, _question_count: usize, preferred: Option<QuestionAnswerInput>) -> Result<QuestionScreenResult> {
+        let id = question_entry.id.as_str();
+        let text = question_entry.query_text.as_str();
+        if let Some(p) = preferred.as_ref().filter(|_| self.fast_forward) {
+            if validate_input(&p.to_string(), &question_entry.entry_type, question_entry.required).is_ok() {
+                self.transcript.push(format!("? [{}] {}", id, text));
+                self.transcript.push(format!("> {} (autofilled)", p));
+                return Ok(QuestionScreenResult::Proceeded(p.clone()));
+            }
+        }
+        let mut input = match self.next_step(id, text) {
+            ScriptAction::Input(i) => i,
+            ScriptAction::Cancel => return Ok(QuestionScreenResult::Canceled),
+            ScriptAction::Timeout => return Ok(match get_timeout_answer(question_entry) {
+                Some(a) => QuestionScreenResult::TimedOut(a),
+                None => QuestionScreenResult::Canceled,
+            }),
+        };
+        if input.is_empty() {
+            if let Some(p) = preferred {
+                input = p.to_string();
+            }
+        }
+        match validate_input(input.trim(), &question_entry.entry_type, question_entry.required) {
+            Ok(a) => Ok(QuestionScreenResult::Proceeded(a)),
+            Err(_) => self.wrong_input(&input),
+        }
+    }
+}
+
+/// Runs the questionaire with the scripted view and without persistence. Panics,
+/// if the script isn't used completely.
+pub fn run_scripted(questionaire: &Questionaire, view: &mut ScriptedView) -> Result<QuestionaireResult> {
+    let result = QuestionaireController::new(questionaire, &mut *view, NoPersistence::new()).run()?;
+    view.assert_finished();
+    Ok(result)
+}
+
+/// Environment variable, that makes `assert_golden` write the files
+pub const UPDATE_GOLDEN_ENV: &str = "TQUEST_UPDATE_GOLDEN";
+
+/// Runs the questionaire with the input lines and returns the plain text output,
+/// with the inputs after the prompts. When the inputs are used up, the questionaire
+/// is canceled.
+pub fn render_transcript(questionaire: &Questionaire, inputs: &[&str]) -> Result<String> {
+    let mut input = inputs.join("\n");
+    input.push('\n');
+    let mut output: Vec<u8> = Vec::new();
+    let mut view = LineView::new(input.as_bytes(), &mut output);
+    view.echo(true);
+    view.print_title(if questionaire.title.is_empty() { TITLE } else { &questionaire.title });
+    QuestionaireController::new(questionaire, view, NoPersistence::new()).run()?;
+    Ok(String::from_utf8(output)?)
+}
+
+/// Compares the text with the content of the golden file and panics with the first
+/// differing line. The file is written, if `TQUEST_UPDATE_GOLDEN` is set.
+pub fn assert_golden<P: AsRef<Path>>(file: P, actual: &str) {
+    let file = file.as_ref();
+    if std::env::var(UPDATE_GOLDEN_ENV).is_ok_and(|v| ! v.is_empty() && v != "0") {
+        if let Some(dir) = file.parent() {
+            fs::create_dir_all(dir).expect("error while creating the golden file directory");
+        }
+        fs::write(file, actual).expect("error while writing the golden file");
+        return;
+    }
+    let expected = match fs::read_to_string(file) {
+        Ok(s) => s,
+        Err(e) => panic!("can't read golden file {}: {}, set {}=1 to create it", file.display(), e, UPDATE_GOLDEN_ENV),
+    };
+    if expected == actual {
+        return;
+    }
+    let expected_lines: Vec<&str> = expected.lines().collect();
+    let actual_lines: Vec<&str> = actual.lines().collect();
+    let i = expected_lines.iter().zip(actual_lines.iter())
+        .position(|(e, a)| e != a)
+        .unwrap_or(expected_lines.len().min(actual_lines.len()));
+    panic!("output differs from golden file {} in line {}\n  expected: {}\n  actual:   {}\nset {}=1 to update it",
+        file.display(), i + 1,
+        expected_lines.get(i).unwrap_or(&"(end of file)"),
+        actual_lines.get(i).unwrap_or(&"(end of output)"),
+        UPDATE_GOLDEN_ENV);
+}
+
+/// Answer with the qualified id, e.g. `id04[0].id04_01`
+pub fn answer_at(answer: &BlockAnswer, qualified_id: &str) -> Option<QuestionAnswerInput> {
+    flatten(answer).into_iter().find(|a| a.qualified_id() == qualified_id).map(|a| a.answer)
+}
+
+/// Panics, if the answer with the qualified id is missing or differs
+pub fn assert_answer(answer: &BlockAnswer, qualified_id: &str, expected: &str) {
+    match answer_at(answer, qualified_id) {
+        Some(a) if a.to_string() == expected => {},
+        Some(a) => panic!("answer of `{}` differs\n  expected: {}\n  actual:   {}", qualified_id, expected, a),
+        None => panic!("no answer for `{}`, the answers are:\n{}", qualified_id, answers_text(answer)),
+    }
+}
+
+/// Panics, if the answers differ from the expected pairs of qualified id and answer.
+/// The message lists the missing (`-`) and unexpected (`+`) answers.
+pub fn assert_answers(answer: &BlockAnswer, expected: &[(&str, &str)]) {
+    let actual: Vec<String> = answer_lines(answer);
+    let expected: Vec<String> = expected.iter().map(|(id, v)| format!("{}: {}", id, v)).collect();
+    if actual == expected {
+        return;
+    }
+    let mut diff = String::new();
+    for e in &expected {
+        let prefix = if actual.contains(e) { ' ' } else { '-' };
+        diff.push_str(&format!("\n  {} {}", prefix, e));
+    }
+    for a in actual.iter().filter(|a| ! expected.contains(a)) {
+        diff.push_str(&format!("\n  + {}", a));
+    }
+    panic!("the answers differ (- expected, + actual):{}", diff);
+}
+
+fn answer_lines(answer: &BlockAnswer) -> Vec<String> {
+    flatten(answer).iter().map(|a| format!("{}: {}", a.qualified_id(), a.answer_text())).collect()
+}
+
+fn answers_text(answer: &BlockAnswer) -> String {
+    answer_lines(answer).iter().map(|l| format!("  {}", l)).collect::<Vec<String>>().join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helper::create_small_questionaire;
+
+    fn small_script() -> ScriptedView {
+        let mut view = ScriptedView::new();
+        view.answer("id00", "y")
+            .answer("What's your name?", "Homer")
+            .answer("What's your date of birth?", "1956-05-12")
+            .answer("id00", "");
+        view
+    }
+
+    #[test]
+    fn test_run_scripted() {
+        let q = create_small_questionaire();
+        let mut view = small_script();
+        let answer = match run_scripted(&q, &mut view).unwrap() {
+            QuestionaireResult::Finished(a) => a,
+            QuestionaireResult::Canceled => panic!("canceled"),
+        };
+        assert_answers(&answer, &[("id01", "Homer"), ("id01", "1956-05-12")]);
+        assert_answer(&answer, "id01", "Homer");
+        assert_eq!("? [id01] What's your name?", view.transcript()[2]);
+        assert_eq!("> Homer", view.transcript()[3]);
+
+        let mut view = ScriptedView::new();
+        view.answer("id00", "y").cancel("id01");
+        assert!(matches!(run_scripted(&q, &mut view).unwrap(), QuestionaireResult::Canceled));
+    }
+
+    #[test]
+    fn test_golden_transcript() {
+        let q = create_small_questionaire();
+        let t = render_transcript(&q, &["y", "H", "Homer", "?", "1956-05-12", ""]).unwrap();
+        assert_golden("res/golden/small_questionaire.txt", &t);
+    }
+
+    #[test]
+    #[should_panic(expected = "in line 2\n  expected: b\n  actual:   c")]
+    fn test_golden_diff() {
+        let file = "tmp/test_golden_diff.txt";
+        fs::create_dir_all("tmp").unwrap();
+        fs::write(file, "a\nb\n").unwrap();
+        assert_golden(file, "a\nc\n");
+    }
+
+    #[test]
+    #[should_panic(expected = "unexpected prompt\n  expected: Where do you live?\n  actual:   [id01] What's your name?")]
+    fn test_unexpected_prompt() {
+        let mut view = ScriptedView::new();
+        view.answer("id00", "y").answer("Where do you live?", "Springfield");
+        let _ = run_scripted(&create_small_questionaire(), &mut view);
+    }
+
+    #[test]
+    #[should_panic(expected = "wrong input `12.05.1956`")]
+    fn test_wrong_input() {
+        let mut view = ScriptedView::new();
+        view.answer("id00", "y").answer("id01", "Homer").answer("id01", "12.05.1956");
+        let _ = run_scripted(&create_small_questionaire(), &mut view);
+    }
+
+    #[test]
+    #[should_panic(expected = "the answers differ (- expected, + actual):\n    id01: Homer\n  - id01: 1956-05-13\n  + id01: 1956-05-12")]
+    fn test_assert_answers() {
+        let mut view = small_script();
+        if let QuestionaireResult::Finished(a) = run_scripted(&create_small_questionaire(), &mut view).unwrap() {
+            assert_answers(&a, &[("id01", "Homer"), ("id01", "1956-05-13")]);
+        }
+    }
+}
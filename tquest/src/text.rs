@@ -0,0 +1,338 @@
+//! Rendering of question, help and block texts
+//!
+//! The texts are word wrapped to the width of the output and can use a small
+//! Markdown subset: `*emphasis*`, `**strong**`, `` `inline code` ``, links
+//! `[label](url)` and bullet (`-`, `*`, `+`) or numbered (`1.`) lists. Lines of a
+//! paragraph are joined, blank lines separate paragraphs.
+//!
+use crossterm::terminal;
+
+use std::env;
+use std::io::{self, IsTerminal, Write};
+use std::process::{Command, Stdio};
+
+use crate::theme::{Style, Theme};
+
+
+/// Width, if the output isn't a terminal
+pub(crate) const DEFAULT_WIDTH: usize = 80;
+
+/// Width of the terminal, the texts are written to stderr
+pub(crate) fn terminal_width() -> usize {
+    if io::stderr().is_terminal() {
+        if let Ok((cols, _)) = terminal::size() {
+            if cols > 10 {
+                return cols as usize;
+            }
+        }
+    }
+    DEFAULT_WIDTH
+}
+
+/// Height of the terminal, None if stderr isn't one
+pub(crate) fn terminal_height() -> Option<usize> {
+    if io::stderr().is_terminal() {
+        terminal::size().ok().map(|(_, rows)| rows as usize)
+    } else {
+        None
+    }
+}
+
+/// Help text with its label. Longer help gets the label on an own line.
+pub(crate) fn help_text(help: &str) -> String {
+    if help.contains('\n') {
+        format!("Help:\n\n{}", help)
+    } else {
+        format!("Help: {}", help)
+    }
+}
+
+/// Prints the text, if it doesn't fit on the terminal it's shown in the pager
+/// from `PAGER` (default `less -R`)
+pub(crate) fn print_paged(text: &str) {
+    let fits = match terminal_height() {
+        Some(h) => text.lines().count() + 2 < h,
+        None => true,
+    };
+    if fits || ! page(text) {
+        eprintln!("{}", text);
+    }
+}
+
+fn page(text: &str) -> bool {
+    let pager = env::var("PAGER").ok().filter(|p| ! p.trim().is_empty()).unwrap_or("less -R".to_string());
+    let mut parts = pager.split_whitespace();
+    let cmd = match parts.next() {
+        Some(c) => c,
+        None => return false,
+    };
+    match Command::new(cmd).args(parts).stdin(Stdio::piped()).stdout(io::stderr()).spawn() {
+        Ok(mut child) => {
+            if let Some(mut stdin) = child.stdin.take() {
+                let _ = stdin.write_all(text.as_bytes());
+            }
+            child.wait().is_ok()
+        },
+        Err(_) => false,
+    }
+}
+
+/// Piece of text with its style
+type Span = (String, Style);
+
+#[derive(Default, Clone, Copy)]
+struct InlineState {
+    emphasis: bool,
+    strong: bool,
+}
+
+fn inline_style(state: InlineState, theme: &Theme) -> Style {
+    let mut s = Style::new();
+    if state.emphasis {
+        s = s.combine(&theme.emphasis);
+    }
+    if state.strong {
+        s = s.combine(&theme.strong);
+    }
+    s
+}
+
+fn find(chars: &[char], from: usize, pattern: &[char]) -> Option<usize> {
+    if pattern.is_empty() || chars.len() < pattern.len() {
+        return None;
+    }
+    (from..=chars.len() - pattern.len()).find(|i| chars[*i..*i + pattern.len()] == *pattern)
+}
+
+/// Splits the inline markup of a paragraph into styled spans
+fn parse_inline(text: &str, theme: &Theme) -> Vec<Span> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut ret: Vec<Span> = Vec::new();
+    let mut state = InlineState::default();
+    let mut buf = String::new();
+    let flush = |buf: &mut String, ret: &mut Vec<Span>, state: InlineState| {
+        if ! buf.is_empty() {
+            ret.push((std::mem::take(buf), inline_style(state, theme)));
+        }
+    };
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            '\\' if i + 1 < chars.len() => {
+                buf.push(chars[i + 1]);
+                i += 2;
+                continue;
+            },
+            '`' => {
+                if let Some(end) = find(&chars, i + 1, &['`']) {
+                    flush(&mut buf, &mut ret, state);
+                    let code: String = chars[i + 1..end].iter().collect();
+                    let code = if theme.code == Style::default() { format!("`{}`", code) } else { code };
+                    ret.push((code, inline_style(state, theme).combine(&theme.code)));
+                    i = end + 1;
+                    continue;
+                }
+            },
+            '*' if i + 1 < chars.len() && chars[i + 1] == '*'
+                && (state.strong || find(&chars, i + 2, &['*', '*']).is_some()) => {
+                flush(&mut buf, &mut ret, state);
+                state.strong = ! state.strong;
+                i += 2;
+                continue;
+            },
+            // an unclosed `**` is kept as it is
+            '*' if i + 1 < chars.len() && chars[i + 1] == '*' => {},
+            '*' => {
+                let opens = ! state.emphasis
+                    && i + 1 < chars.len() && ! chars[i + 1].is_whitespace()
+                    && find(&chars, i + 1, &['*']).is_some();
+                if opens || state.emphasis {
+                    flush(&mut buf, &mut ret, state);
+                    state.emphasis = ! state.emphasis;
+                    i += 1;
+                    continue;
+                }
+            },
+            '[' => {
+                if let Some(mid) = find(&chars, i + 1, &[']', '(']) {
+                    if let Some(end) = find(&chars, mid + 2, &[')']) {
+                        flush(&mut buf, &mut ret, state);
+                        let label: String = chars[i + 1..mid].iter().collect();
+                        let url: String = chars[mid + 2..end].iter().collect();
+                        ret.push((label.clone(), inline_style(state, theme).combine(&theme.link)));
+                        if label != url {
+                            ret.push((format!(" ({})", url), inline_style(state, theme)));
+                        }
+                        i = end + 1;
+                        continue;
+                    }
+                }
+            },
+            _ => {},
+        }
+        buf.push(c);
+        i += 1;
+    }
+    flush(&mut buf, &mut ret, state);
+    ret
+}
+
+/// Splits the spans into words, a word can have pieces with different styles
+fn words(spans: &[Span]) -> Vec<Vec<Span>> {
+    let mut ret: Vec<Vec<Span>> = Vec::new();
+    let mut current: Vec<Span> = Vec::new();
+    for (text, style) in spans {
+        let mut piece = String::new();
+        for c in text.chars() {
+            if c.is_whitespace() {
+                if ! piece.is_empty() {
+                    current.push((std::mem::take(&mut piece), *style));
+                }
+                if ! current.is_empty() {
+                    ret.push(std::mem::take(&mut current));
+                }
+            } else {
+                piece.push(c);
+            }
+        }
+        if ! piece.is_empty() {
+            current.push((piece, *style));
+        }
+    }
+    if ! current.is_empty() {
+        ret.push(current);
+    }
+    ret
+}
+
+fn word_len(word: &[Span]) -> usize {
+    word.iter().map(|(t, _)| t.chars().count()).sum()
+}
+
+/// Wraps the words, the first line starts with the prefix, the others with the indent
+fn wrap(words: &[Vec<Span>], width: usize, prefix: &str, indent: &str, base: &Style, out: &mut Vec<String>) {
+    let mut line = prefix.to_string();
+    let mut len = prefix.chars().count();
+    let mut empty = true;
+    for w in words {
+        let wl = word_len(w);
+        if ! empty && len + 1 + wl > width {
+            out.push(std::mem::take(&mut line));
+            line.push_str(indent);
+            len = indent.chars().count();
+            empty = true;
+        }
+        if ! empty {
+            line.push(' ');
+            len += 1;
+        }
+        for (t, s) in w {
+            line.push_str(&base.combine(s).paint(t));
+        }
+        len += wl;
+        empty = false;
+    }
+    out.push(line);
+}
+
+enum Block {
+    Paragraph(String),
+    /// marker, e.g. `•` or `1.`, and the text
+    Item(String, String),
+}
+
+fn list_item(line: &str, theme: &Theme) -> Option<(String, String)> {
+    let t = line.trim_start();
+    for m in ["- ", "* ", "+ "] {
+        if let Some(rest) = t.strip_prefix(m) {
+            let bullet = if theme.ascii { "-" } else { "•" };
+            return Some((bullet.to_string(), rest.to_string()));
+        }
+    }
+    let digits: String = t.chars().take_while(|c| c.is_ascii_digit()).collect();
+    if ! digits.is_empty() {
+        if let Some(rest) = t[digits.len()..].strip_prefix(". ") {
+            return Some((format!("{}.", digits), rest.to_string()));
+        }
+    }
+    None
+}
+
+fn blocks(text: &str, theme: &Theme) -> Vec<Option<Block>> {
+    // None separates paragraphs
+    let mut ret: Vec<Option<Block>> = Vec::new();
+    for line in text.lines() {
+        if line.trim().is_empty() {
+            if matches!(ret.last(), Some(Some(_))) {
+                ret.push(None);
+            }
+            continue;
+        }
+        if let Some((marker, rest)) = list_item(line, theme) {
+            ret.push(Some(Block::Item(marker, rest)));
+            continue;
+        }
+        match ret.last_mut() {
+            Some(Some(Block::Paragraph(p))) => {
+                p.push(' ');
+                p.push_str(line.trim());
+            },
+            Some(Some(Block::Item(_, t))) if line.starts_with([' ', '\t']) => {
+                t.push(' ');
+                t.push_str(line.trim());
+            },
+            _ => ret.push(Some(Block::Paragraph(line.trim().to_string()))),
+        }
+    }
+    if matches!(ret.last(), Some(None)) {
+        ret.pop();
+    }
+    ret
+}
+
+/// Renders the text with the theme and wraps it to the width. The base style is
+/// used for the whole text, e.g. for questions.
+pub(crate) fn render_text(text: &str, width: usize, base: &Style, theme: &Theme) -> String {
+    let mut out: Vec<String> = Vec::new();
+    for b in blocks(text, theme) {
+        match b {
+            None => out.push(String::new()),
+            Some(Block::Paragraph(p)) => wrap(&words(&parse_inline(&p, theme)), width, "", "", base, &mut out),
+            Some(Block::Item(marker, t)) => {
+                let prefix = format!("  {} ", marker);
+                let indent = " ".repeat(prefix.chars().count());
+                wrap(&words(&parse_inline(&t, theme)), width, &prefix, &indent, base, &mut out);
+            },
+        }
+    }
+    out.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wrap_and_lists() {
+        let t = Theme::no_color();
+        let text = "The answers are stored\nin a file.\n\nPossible values:\n- `red` for a color that is quite warm\n- *green*\n  or olive\n1. first";
+        assert_eq!("The answers are stored in a\nfile.\n\nPossible values:\n  • `red` for a color that is\n    quite warm\n  • green or olive\n  1. first",
+            render_text(text, 30, &Style::new(), &t));
+        assert_eq!("  - item", render_text("* item", 30, &Style::new(), &Theme::plain()));
+        assert_eq!("averyveryverylongword\nnext", render_text("averyveryverylongword next", 10, &Style::new(), &t));
+    }
+
+    #[test]
+    fn test_inline() {
+        let t = Theme::colored();
+        assert_eq!("a \x1b[3mb\x1b[0m \x1b[1mc\x1b[0m \x1b[36md\x1b[0m",
+            render_text("a *b* **c** `d`", 80, &Style::new(), &t));
+        assert_eq!("see \x1b[4;34mdocs\x1b[0m (https://example.com)",
+            render_text("see [docs](https://example.com)", 80, &Style::new(), &t));
+        // the base style is kept for the marked text
+        assert_eq!("\x1b[1mis\x1b[0m \x1b[1;3mit\x1b[0m\x1b[1m?\x1b[0m",
+            render_text("is *it*?", 80, &Style::new().bold(), &t));
+        assert_eq!("2 * 3 = 6 and a\\b", render_text("2 * 3 = 6 and a\\\\b", 80, &Style::new(), &Theme::no_color()));
+    }
+}
@@ -0,0 +1,289 @@
+//! Styles of the line based views
+//!
+//! A `Theme` has a named style for each kind of text. The built-in themes are
+//! `colored` (the default look), `monochrome`, `no_color` and `plain`. The plain
+//! theme uses only ASCII characters and no decorations, e.g. for log-captured runs
+//! and limited terminals. `Theme::from_env` honours `NO_COLOR`, `CLICOLOR`,
+//! `CLICOLOR_FORCE` and `TERM=dumb`.
+//!
+use std::env;
+use std::io::{self, IsTerminal};
+
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Color {
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+}
+
+impl Color {
+    fn ansi_code(&self) -> &'static str {
+        match self {
+            Color::Red => "31",
+            Color::Green => "32",
+            Color::Yellow => "33",
+            Color::Blue => "34",
+            Color::Magenta => "35",
+            Color::Cyan => "36",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Style {
+    pub color: Option<Color>,
+    pub bold: bool,
+    pub dimmed: bool,
+    pub italic: bool,
+    pub underline: bool,
+}
+
+impl Style {
+    pub fn new() -> Self {
+        Style::default()
+    }
+
+    pub fn color(mut self, v: Color) -> Self {
+        self.color = Some(v);
+        self
+    }
+
+    pub fn bold(mut self) -> Self {
+        self.bold = true;
+        self
+    }
+
+    pub fn dimmed(mut self) -> Self {
+        self.dimmed = true;
+        self
+    }
+
+    pub fn italic(mut self) -> Self {
+        self.italic = true;
+        self
+    }
+
+    pub fn underline(mut self) -> Self {
+        self.underline = true;
+        self
+    }
+
+    /// Style with the attributes of both, the colour of the other one wins
+    pub fn combine(&self, other: &Style) -> Self {
+        Style {
+            color: other.color.or(self.color),
+            bold: self.bold || other.bold,
+            dimmed: self.dimmed || other.dimmed,
+            italic: self.italic || other.italic,
+            underline: self.underline || other.underline,
+        }
+    }
+
+    /// Text with the ANSI codes of the style, an empty style returns the text unchanged
+    pub fn paint(&self, text: &str) -> String {
+        let mut codes: Vec<&str> = Vec::new();
+        if self.bold {
+            codes.push("1");
+        }
+        if self.dimmed {
+            codes.push("2");
+        }
+        if self.italic {
+            codes.push("3");
+        }
+        if self.underline {
+            codes.push("4");
+        }
+        if let Some(c) = self.color {
+            codes.push(c.ansi_code());
+        }
+        if codes.is_empty() {
+            text.to_string()
+        } else {
+            format!("\x1b[{}m{}\x1b[0m", codes.join(";"), text)
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Theme {
+    pub title: Style,
+    pub question: Style,
+    pub hint: Style,
+    /// wrong input and urgent messages
+    pub error: Style,
+    /// the taken answer
+    pub echo: Style,
+    /// last input of a resumed questionaire
+    pub preferred: Style,
+    /// help texts and normal messages
+    pub message: Style,
+    /// `*text*` in the Markdown of the texts
+    pub emphasis: Style,
+    /// `**text**`
+    pub strong: Style,
+    /// `` `code` ``, without a style the backticks are kept
+    pub code: Style,
+    /// label of `[label](url)`
+    pub link: Style,
+    /// only ASCII characters, e.g. `ENTER` instead of `⏎`
+    pub ascii: bool,
+    /// separator line before the title
+    pub decorations: bool,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme::colored()
+    }
+}
+
+impl Theme {
+    /// The default look: bold questions, dimmed hints, yellow warnings and green answers
+    pub fn colored() -> Self {
+        Theme {
+            title: Style::new().bold().underline(),
+            question: Style::new().bold(),
+            hint: Style::new().dimmed(),
+            error: Style::new().color(Color::Yellow),
+            echo: Style::new().color(Color::Green),
+            preferred: Style::new().color(Color::Yellow).italic(),
+            message: Style::new().italic(),
+            emphasis: Style::new().italic(),
+            strong: Style::new().bold(),
+            code: Style::new().color(Color::Cyan),
+            link: Style::new().underline().color(Color::Blue),
+            ascii: false,
+            decorations: true,
+        }
+    }
+
+    /// Text attributes without colours
+    pub fn monochrome() -> Self {
+        Theme {
+            title: Style::new().bold().underline(),
+            question: Style::new().bold(),
+            hint: Style::new().dimmed(),
+            error: Style::new().bold(),
+            echo: Style::new().bold(),
+            preferred: Style::new().italic(),
+            message: Style::new().italic(),
+            emphasis: Style::new().italic(),
+            strong: Style::new().bold(),
+            code: Style::new(),
+            link: Style::new().underline(),
+            ascii: false,
+            decorations: true,
+        }
+    }
+
+    /// No ANSI codes, but with the unicode glyphs and decorations
+    pub fn no_color() -> Self {
+        Theme {
+            title: Style::new(),
+            question: Style::new(),
+            hint: Style::new(),
+            error: Style::new(),
+            echo: Style::new(),
+            preferred: Style::new(),
+            message: Style::new(),
+            emphasis: Style::new(),
+            strong: Style::new(),
+            code: Style::new(),
+            link: Style::new(),
+            ascii: false,
+            decorations: true,
+        }
+    }
+
+    /// No ANSI codes, only ASCII characters and no decorations
+    pub fn plain() -> Self {
+        Theme {
+            ascii: true,
+            decorations: false,
+            ..Theme::no_color()
+        }
+    }
+
+    /// Theme for the standard output
+    pub fn from_env() -> Self {
+        Theme::from_env_for(io::stdout().is_terminal())
+    }
+
+    /// Theme for an output stream, that is a terminal or not. Output that isn't
+    /// a terminal gets the plain theme, unless `CLICOLOR_FORCE` is set.
+    pub fn from_env_for(is_terminal: bool) -> Self {
+        Theme::from_vars(is_terminal, |name| env::var(name).ok())
+    }
+
+    /// Like `from_env_for`, but the variables are looked up with `var`
+    pub fn from_vars<F: Fn(&str) -> Option<String>>(is_terminal: bool, var: F) -> Self {
+        let var = |name: &str| var(name).filter(|v| ! v.is_empty());
+        if var("NO_COLOR").is_some() {
+            return if is_terminal { Theme::no_color() } else { Theme::plain() };
+        }
+        if var("CLICOLOR_FORCE").is_some_and(|v| v != "0") {
+            return Theme::colored();
+        }
+        if ! is_terminal || var("TERM").is_some_and(|v| v == "dumb") {
+            return Theme::plain();
+        }
+        if var("CLICOLOR").is_some_and(|v| v == "0") {
+            return Theme::no_color();
+        }
+        Theme::colored()
+    }
+
+    /// Replaces the unicode glyphs in the text, if the theme is ASCII only
+    pub fn text(&self, text: &str) -> String {
+        if self.ascii {
+            text.replace("⏎", "ENTER").replace("↑/↓", "up/down")
+        } else {
+            text.to_string()
+        }
+    }
+
+    /// Separator line before the title, if the theme has decorations
+    pub fn separator(&self) -> Option<&'static str> {
+        if self.decorations {
+            Some("________________________________________________________________________________")
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_paint() {
+        assert_eq!("\x1b[1;4mTitle\x1b[0m", Theme::colored().title.paint("Title"));
+        assert_eq!("\x1b[3;33mlast\x1b[0m", Theme::colored().preferred.paint("last"));
+        assert_eq!("no", Theme::plain().echo.paint("no"));
+        assert_eq!("take it with ENTER", Theme::plain().text("take it with ⏎"));
+        assert_eq!("take it with ⏎", Theme::no_color().text("take it with ⏎"));
+        assert!(Theme::plain().separator().is_none());
+    }
+
+    #[test]
+    fn test_from_vars() {
+        let vars = |set: &'static [(&'static str, &'static str)]| move |name: &str| {
+            set.iter().find(|(n, _)| *n == name).map(|(_, v)| v.to_string())
+        };
+        assert_eq!(Theme::no_color(), Theme::from_vars(true, vars(&[("NO_COLOR", "1")])));
+        assert_eq!(Theme::plain(), Theme::from_vars(false, vars(&[("NO_COLOR", "1")])));
+        assert_eq!(Theme::plain(), Theme::from_vars(false, vars(&[])));
+        assert_eq!(Theme::colored(), Theme::from_vars(true, vars(&[])));
+        assert_eq!(Theme::colored(), Theme::from_vars(false, vars(&[("CLICOLOR_FORCE", "1")])));
+        assert_eq!(Theme::no_color(), Theme::from_vars(true, vars(&[("CLICOLOR", "0")])));
+        assert_eq!(Theme::plain(), Theme::from_vars(true, vars(&[("TERM", "dumb")])));
+        // empty values count as not set
+        assert_eq!(Theme::colored(), Theme::from_vars(true, vars(&[("NO_COLOR", "")])));
+    }
+}
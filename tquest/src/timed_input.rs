@@ -0,0 +1,258 @@
+//! Line input with a timeout for the line based UI
+//!
+//! The remaining seconds are shown in front of the prompt. The first key stops
+//! the countdown, so a person at the terminal can take the time they need. When
+//! the time is up, the caller takes the default value.
+//!
+//! Input, that isn't a terminal, e.g. a pipe, is read line by line in a thread by
+//! `TimedLines`, so that the caller can stop waiting for it.
+//!
+use crossterm::cursor::MoveToColumn;
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::style::Print;
+use crossterm::terminal::{self, Clear, ClearType};
+use crossterm::queue;
+
+use std::io::{self, BufRead, Read, Write};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::theme::Theme;
+
+
+#[derive(Debug, PartialEq)]
+pub(crate) enum TimedAction {
+    Line(String),
+    TimedOut,
+    Cancel,
+}
+
+pub(crate) struct TimedLine {
+    value: String,
+    /// false after the first key, then the input doesn't time out
+    counting: bool,
+    /// shows the remaining seconds, off for screen readers
+    countdown: bool,
+}
+
+impl TimedLine {
+    pub(crate) fn new(initial: &str, countdown: bool) -> Self {
+        TimedLine { value: initial.to_string(), counting: true, countdown }
+    }
+
+    pub(crate) fn handle_key(&mut self, key: &KeyEvent) -> Option<TimedAction> {
+        self.counting = false;
+        if key.modifiers.contains(KeyModifiers::CONTROL) {
+            return match key.code {
+                KeyCode::Char('c') | KeyCode::Char('d') => Some(TimedAction::Cancel),
+                _ => None,
+            };
+        }
+        match key.code {
+            KeyCode::Enter => return Some(TimedAction::Line(self.value.clone())),
+            KeyCode::Char(c) => self.value.push(c),
+            KeyCode::Backspace => {
+                self.value.pop();
+            },
+            KeyCode::Esc => self.value.clear(),
+            _ => {},
+        }
+        None
+    }
+
+    fn prompt(&self, remaining: Duration, theme: &Theme) -> String {
+        if self.counting && self.countdown {
+            let secs = remaining.as_millis().div_ceil(1000);
+            format!("{} >> {}", theme.hint.paint(&format!("[{}s]", secs)), self.value)
+        } else {
+            format!(">> {}", self.value)
+        }
+    }
+}
+
+/// Reads a line from the terminal, until Enter is pressed or the timeout expires
+pub(crate) fn read_line(initial: &str, timeout: Duration, countdown: bool, theme: &Theme) -> io::Result<TimedAction> {
+    let mut out = io::stderr();
+    terminal::enable_raw_mode()?;
+    let ret = run_line(&mut TimedLine::new(initial, countdown), &mut out, timeout, theme, |wait| {
+        if event::poll(wait)? {
+            event::read().map(Some)
+        } else {
+            Ok(None)
+        }
+    });
+    let _ = queue!(out, Print("\r\n"));
+    let _ = out.flush();
+    terminal::disable_raw_mode()?;
+    ret
+}
+
+/// Input lines, that are read in a thread. A line, that comes after its question
+/// timed out, is taken by the next question. As `BufRead` the reads fail with
+/// `ErrorKind::TimedOut`, if there was no line within the timeout of the handle.
+pub struct TimedLines {
+    lines: Receiver<io::Result<String>>,
+    /// the current line, as far as it isn't consumed
+    buf: Vec<u8>,
+    pos: usize,
+    timeout: Arc<Mutex<Option<Duration>>>,
+}
+
+impl TimedLines {
+    pub fn new<R: BufRead + Send + 'static>(mut input: R) -> Self {
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || loop {
+            let mut line = String::new();
+            match input.read_line(&mut line) {
+                // the end of the input closes the channel
+                Ok(0) => break,
+                Ok(_) => {
+                    if tx.send(Ok(line)).is_err() {
+                        break;
+                    }
+                },
+                Err(e) => {
+                    let _ = tx.send(Err(e));
+                    break;
+                },
+            }
+        });
+        TimedLines {
+            lines: rx,
+            buf: Vec::new(),
+            pos: 0,
+            timeout: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Handle to set the timeout of the reads over `BufRead`
+    pub(crate) fn timeout_handle(&self) -> Arc<Mutex<Option<Duration>>> {
+        self.timeout.clone()
+    }
+
+    /// Returns the next line with its line break, an empty string at the end of the
+    /// input or None, if no line came in time.
+    pub(crate) fn next_line(&mut self, timeout: Option<Duration>) -> io::Result<Option<String>> {
+        let received = match timeout {
+            Some(t) => match self.lines.recv_timeout(t) {
+                Ok(l) => Some(l),
+                Err(RecvTimeoutError::Timeout) => return Ok(None),
+                Err(RecvTimeoutError::Disconnected) => None,
+            },
+            None => self.lines.recv().ok(),
+        };
+        match received {
+            Some(l) => l.map(Some),
+            None => Ok(Some(String::new())),
+        }
+    }
+}
+
+impl Read for TimedLines {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let available = self.fill_buf()?;
+        let n = available.len().min(buf.len());
+        buf[..n].copy_from_slice(&available[..n]);
+        self.consume(n);
+        Ok(n)
+    }
+}
+
+impl BufRead for TimedLines {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        if self.pos >= self.buf.len() {
+            let timeout = *self.timeout.lock().unwrap();
+            match self.next_line(timeout)? {
+                Some(l) => {
+                    self.buf = l.into_bytes();
+                    self.pos = 0;
+                },
+                None => return Err(io::Error::new(io::ErrorKind::TimedOut, "no input within the timeout")),
+            }
+        }
+        Ok(&self.buf[self.pos..])
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.pos = (self.pos + amt).min(self.buf.len());
+    }
+}
+
+/// The function returns the next event or None, if there was none in the given time
+fn run_line<W: Write, F: FnMut(Duration) -> io::Result<Option<Event>>>(state: &mut TimedLine, out: &mut W, timeout: Duration, theme: &Theme, mut next_event: F) -> io::Result<TimedAction> {
+    let start = Instant::now();
+    loop {
+        let remaining = timeout.saturating_sub(start.elapsed());
+        if state.counting && remaining.is_zero() {
+            return Ok(TimedAction::TimedOut);
+        }
+        queue!(out, MoveToColumn(0), Clear(ClearType::CurrentLine), Print(state.prompt(remaining, theme)))?;
+        out.flush()?;
+        // the countdown is redrawn several times per second
+        let wait = if state.counting { remaining.min(Duration::from_millis(200)) } else { Duration::from_secs(1) };
+        if let Some(Event::Key(k)) = next_event(wait)? {
+            if k.kind == KeyEventKind::Press {
+                if let Some(a) = state.handle_key(&k) {
+                    return Ok(a);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn events(keys: Vec<KeyCode>) -> impl FnMut(Duration) -> io::Result<Option<Event>> {
+        let mut keys = keys.into_iter();
+        move |_| Ok(keys.next().map(|k| Event::Key(KeyEvent::from(k))))
+    }
+
+    #[test]
+    fn test_run_line() {
+        let theme = Theme::plain();
+        let mut out: Vec<u8> = Vec::new();
+        let keys = vec![KeyCode::Char('4'), KeyCode::Char('3'), KeyCode::Backspace, KeyCode::Char('2'), KeyCode::Enter];
+        let r = run_line(&mut TimedLine::new("", true), &mut out, Duration::from_secs(30), &theme, events(keys)).unwrap();
+        assert_eq!(TimedAction::Line("42".to_string()), r);
+        let s = String::from_utf8(out).unwrap();
+        assert!(s.contains("[30s] >> "), "{}", s);
+        // the countdown stops with the first key
+        assert!(s.ends_with(">> 42"), "{}", s);
+
+        let mut out: Vec<u8> = Vec::new();
+        let r = run_line(&mut TimedLine::new("7", true), &mut out, Duration::from_millis(50), &theme, events(Vec::new())).unwrap();
+        assert_eq!(TimedAction::TimedOut, r);
+
+        let mut state = TimedLine::new("", false);
+        assert_eq!(">> ", state.prompt(Duration::from_secs(5), &theme));
+        let ctrl_c = KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL);
+        assert_eq!(Some(TimedAction::Cancel), state.handle_key(&ctrl_c));
+    }
+
+    #[test]
+    fn test_timed_lines() {
+        let (reader, mut writer) = io::pipe().unwrap();
+        let mut lines = TimedLines::new(io::BufReader::new(reader));
+        assert_eq!(None, lines.next_line(Some(Duration::from_millis(20))).unwrap());
+        writer.write_all(b"Homer\n").unwrap();
+        assert_eq!(Some("Homer\n".to_string()), lines.next_line(Some(Duration::from_secs(5))).unwrap());
+
+        // as BufRead the timeout comes from the handle
+        *lines.timeout_handle().lock().unwrap() = Some(Duration::from_millis(20));
+        let mut line = String::new();
+        assert_eq!(io::ErrorKind::TimedOut, lines.read_line(&mut line).unwrap_err().kind());
+        writer.write_all(b"Marge\n").unwrap();
+        *lines.timeout_handle().lock().unwrap() = None;
+        lines.read_line(&mut line).unwrap();
+        assert_eq!("Marge\n", line);
+
+        // the end of the input
+        drop(writer);
+        assert_eq!(Some(String::new()), lines.next_line(None).unwrap());
+        assert_eq!(0, lines.read_line(&mut line).unwrap());
+    }
+}
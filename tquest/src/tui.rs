@@ -0,0 +1,706 @@
+//! Full-screen terminal view, built on ratatui
+//!
+//! The view shows a sidebar with the tree of blocks and questions and the progress,
+//! and a main pane with the current question, its help and the validation feedback.
+//! Options, yes/no questions and proceed screens are chosen from menus with the
+//! arrow keys.
+//!
+use crate::questionaire::{EntryType, QuestionAnswerInput, QuestionEntry, Questionaire, QuestionaireEntry, SubBlock};
+use crate::progress::Progress;
+use crate::ui::{get_question_timeout, get_timeout_answer, get_timeout_text, get_valid_input_hint, validate_input, MsgLevel, ProceedScreenResult, QuestionScreenResult, QuestionaireView};
+use anyhow::Result;
+use ratatui::backend::{Backend, CrosstermBackend};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::crossterm::execute;
+use ratatui::crossterm::terminal::{self, EnterAlternateScreen, LeaveAlternateScreen};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style, Stylize};
+use ratatui::text::{Line, Span, Text};
+use ratatui::widgets::{Block, Gauge, List, ListItem, ListState, Paragraph, Wrap};
+use ratatui::{Frame, Terminal};
+
+use std::collections::HashSet;
+use std::io::{self, Stderr};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Once;
+use std::time::{Duration, Instant};
+
+
+const YES: &str = "yes";
+const NO: &str = "no";
+const CONTINUE: &str = "continue";
+const MAX_MESSAGES: usize = 3;
+
+/// true, while a view has switched the terminal to the full-screen mode
+static TERMINAL_OWNED: AtomicBool = AtomicBool::new(false);
+
+/// Entry of the sidebar tree
+struct TreeItem {
+    id: String,
+    text: String,
+    depth: usize,
+}
+
+/// Everything that is shown, besides the current screen
+#[derive(Default)]
+struct ViewState {
+    title: String,
+    tree: Vec<TreeItem>,
+    current: Option<String>,
+    answered: HashSet<String>,
+    /// position and count of the current question, used without a progress
+    position: (usize, usize),
+    /// progress from the controller
+    progress: Option<Progress>,
+    /// message text and true for urgent messages
+    messages: Vec<(String, bool)>,
+}
+
+enum Input {
+    Text { value: String, cursor: usize, masked: bool },
+    Menu { items: Vec<String>, selected: usize },
+}
+
+/// Content of the main pane
+struct Screen {
+    text: String,
+    hint: String,
+    help: Option<String>,
+    show_help: bool,
+    input: Input,
+    feedback: Option<String>,
+    /// remaining seconds, until the default is taken
+    countdown: Option<u64>,
+}
+
+/// What the input loop returns
+enum ScreenResult {
+    Canceled,
+    Text(String),
+    Selected(usize),
+    TimedOut,
+}
+
+pub struct TuiView<B: Backend> {
+    terminal: Terminal<B>,
+    /// waits for the next event, with a time it returns None if there was none
+    next_event: Box<dyn FnMut(Option<Duration>) -> io::Result<Option<Event>>>,
+    /// true, if the terminal was switched to the alternate screen and has to be restored
+    owns_terminal: bool,
+    state: ViewState,
+    fast_forward: bool,
+    timeout: Option<Duration>,
+    /// messages since the last input, they are printed after the terminal is restored
+    pending_messages: Vec<String>,
+}
+
+impl TuiView<CrosstermBackend<Stderr>> {
+    /// Switches the terminal to the full-screen mode, it's restored when the view is dropped.
+    /// The view is drawn on stderr, so that stdout stays free for the answers.
+    pub fn new(questionaire: &Questionaire) -> Result<Self> {
+        install_panic_hook();
+        TERMINAL_OWNED.store(true, Ordering::SeqCst);
+        terminal::enable_raw_mode()?;
+        execute!(io::stderr(), EnterAlternateScreen)?;
+        let terminal = Terminal::new(CrosstermBackend::new(io::stderr()))?;
+        let mut ret = TuiView::with_terminal(terminal, questionaire, Box::new(poll_event));
+        ret.owns_terminal = true;
+        Ok(ret)
+    }
+}
+
+impl<B: Backend> TuiView<B> {
+    pub(crate) fn with_terminal(terminal: Terminal<B>, questionaire: &Questionaire, next_event: Box<dyn FnMut(Option<Duration>) -> io::Result<Option<Event>>>) -> Self {
+        let mut tree: Vec<TreeItem> = Vec::new();
+        add_tree_items(&questionaire.init_block, 0, &mut tree);
+        TuiView {
+            terminal,
+            next_event,
+            owns_terminal: false,
+            state: ViewState {
+                title: questionaire.title.clone(),
+                tree,
+                ..Default::default()
+            },
+            fast_forward: false,
+            timeout: None,
+            pending_messages: Vec::new(),
+        }
+    }
+
+    fn set_current(&mut self, id: &str, pos: usize, count: usize) {
+        if let Some(c) = self.state.current.take() {
+            if c != id {
+                self.state.answered.insert(c);
+            }
+        }
+        self.state.current = Some(id.to_string());
+        if pos != 0 {
+            self.state.position = (pos, count);
+        }
+    }
+
+    fn draw(&mut self, screen: &Screen) -> Result<()> {
+        let state = &self.state;
+        self.terminal.draw(|f| render(f, state, screen))?;
+        Ok(())
+    }
+
+    /// Handles the keys until the screen is finished. The validate function returns
+    /// an error text for invalid text inputs. With a timeout the remaining seconds
+    /// are shown, until the first key stops the countdown.
+    fn run_screen<F: Fn(&str) -> Option<String>>(&mut self, screen: &mut Screen, timeout: Option<Duration>, validate: F) -> Result<ScreenResult> {
+        self.pending_messages.clear();
+        let start = Instant::now();
+        let mut counting = timeout.is_some();
+        loop {
+            let remaining = timeout.map(|t| t.saturating_sub(start.elapsed())).filter(|_| counting);
+            if remaining.is_some_and(|r| r.is_zero()) {
+                return Ok(ScreenResult::TimedOut);
+            }
+            screen.countdown = remaining.map(|r| r.as_millis().div_ceil(1000) as u64);
+            self.draw(screen)?;
+            // the countdown is redrawn several times per second
+            let wait = remaining.map(|r| r.min(Duration::from_millis(200)));
+            let key = match (self.next_event)(wait)? {
+                Some(Event::Key(k)) if k.kind == KeyEventKind::Press => k,
+                _ => continue,
+            };
+            counting = false;
+            if is_cancel(&key) {
+                return Ok(ScreenResult::Canceled);
+            }
+            if key.code == KeyCode::F(1) {
+                screen.show_help = ! screen.show_help;
+                continue;
+            }
+            match &mut screen.input {
+                Input::Text { value, cursor, .. } => {
+                    match key.code {
+                        KeyCode::Enter => {
+                            let v = value.trim().to_string();
+                            match validate(&v) {
+                                None => return Ok(ScreenResult::Text(v)),
+                                Some(e) => screen.feedback = Some(e),
+                            }
+                        },
+                        KeyCode::Char(c) => {
+                            value.insert(byte_pos(value, *cursor), c);
+                            *cursor += 1;
+                        },
+                        KeyCode::Backspace if *cursor > 0 => {
+                            *cursor -= 1;
+                            value.remove(byte_pos(value, *cursor));
+                        },
+                        KeyCode::Delete if *cursor < value.chars().count() => {
+                            value.remove(byte_pos(value, *cursor));
+                        },
+                        KeyCode::Left => *cursor = cursor.saturating_sub(1),
+                        KeyCode::Right => *cursor = (*cursor + 1).min(value.chars().count()),
+                        KeyCode::Home => *cursor = 0,
+                        KeyCode::End => *cursor = value.chars().count(),
+                        _ => {},
+                    }
+                },
+                Input::Menu { items, selected } => {
+                    match key.code {
+                        KeyCode::Enter => return Ok(ScreenResult::Selected(*selected)),
+                        KeyCode::Up => *selected = selected.checked_sub(1).unwrap_or(items.len() - 1),
+                        KeyCode::Down => *selected = (*selected + 1) % items.len(),
+                        KeyCode::Home => *selected = 0,
+                        KeyCode::End => *selected = items.len() - 1,
+                        // not 'h', the letters select options by type ahead
+                        KeyCode::Char('?') => screen.show_help = ! screen.show_help,
+                        KeyCode::Char(c) => {
+                            // first letter selects, e.g. 'y' and 'n' for yes/no menus
+                            let c = c.to_ascii_lowercase();
+                            if let Some(i) = items.iter().position(|i| i.to_lowercase().starts_with(c)) {
+                                *selected = i;
+                            }
+                        },
+                        _ => {},
+                    }
+                },
+            }
+        }
+    }
+}
+
+impl<B: Backend> Drop for TuiView<B> {
+    fn drop(&mut self) {
+        if self.owns_terminal {
+            restore_terminal();
+            // e.g. the files created after the questionaire, there was no input to show them
+            for m in &self.pending_messages {
+                eprintln!("{}", m);
+            }
+        }
+    }
+}
+
+impl<B: Backend> QuestionaireView for TuiView<B> {
+    fn print_title(&mut self, title: &str) {
+        self.state.title = title.to_string();
+    }
+
+    fn set_fast_forward(&mut self, v: bool) {
+        self.fast_forward = v;
+    }
+
+    fn set_progress(&mut self, progress: &Progress) {
+        self.state.progress = Some(*progress);
+    }
+
+    fn set_timeout(&mut self, timeout: Option<Duration>) {
+        self.timeout = timeout;
+    }
+
+    fn show_msg(&mut self, msg: &str, level: MsgLevel) {
+        let urgent = ! matches!(level, MsgLevel::Normal);
+        self.state.messages.push((msg.to_string(), urgent));
+        if self.state.messages.len() > MAX_MESSAGES {
+            self.state.messages.remove(0);
+        }
+        self.pending_messages.push(msg.to_string());
+    }
+
+    #[cfg(feature = "encryption")]
+    fn ask_secret(&mut self, text: &str) -> Result<Option<String>> {
+        let mut screen = Screen {
+            text: text.to_string(),
+            hint: "Enter the passphrase and take it with ⏎".to_string(),
+            help: None,
+            show_help: false,
+            input: Input::Text { value: String::new(), cursor: 0, masked: true },
+            feedback: None,
+            countdown: None,
+        };
+        match self.run_screen(&mut screen, None, |_| None)? {
+            ScreenResult::Text(s) => Ok(Some(s)),
+            _ => Ok(None),
+        }
+    }
+
+    fn show_info_screen(&mut self, question_entry: &QuestionEntry) -> Result<ProceedScreenResult> {
+        self.set_current(&question_entry.id, 0, 0);
+        if self.fast_forward {
+            return Ok(ProceedScreenResult::Proceeded(true));
+        }
+        let mut screen = Screen {
+            text: question_entry.query_text.clone(),
+            hint: "Continue with ⏎".to_string(),
+            help: question_entry.help_text.clone(),
+            show_help: false,
+            input: Input::Menu { items: vec![CONTINUE.to_string()], selected: 0 },
+            feedback: None,
+            countdown: None,
+        };
+        let timeout = self.timeout;
+        Ok(match self.run_screen(&mut screen, timeout, |_| None)? {
+            ScreenResult::Canceled => ProceedScreenResult::Canceled,
+            _ => ProceedScreenResult::Proceeded(true),
+        })
+    }
+
+    fn show_proceed_screen<'a, T: Into<Option<&'a str>>>(&mut self, id: &str, text: &str, help_text: T, question_count: usize, current: usize, preferred: Option<bool>) -> Result<ProceedScreenResult> {
+        self.set_current(id, current, question_count);
+        if self.fast_forward {
+            if let Some(p) = preferred {
+                return Ok(ProceedScreenResult::Proceeded(p));
+            }
+        }
+        let mut screen = Screen {
+            text: text.to_string(),
+            hint: "Choose with ↑/↓ or y/n and take it with ⏎".to_string(),
+            help: help_text.into().map(|h| h.to_string()),
+            show_help: false,
+            input: Input::Menu {
+                items: vec![YES.to_string(), NO.to_string()],
+                selected: if preferred == Some(false) { 1 } else { 0 },
+            },
+            feedback: None,
+            countdown: None,
+        };
+        let timeout = self.timeout;
+        Ok(match self.run_screen(&mut screen, timeout, |_| None)? {
+            ScreenResult::Selected(i) => ProceedScreenResult::Proceeded(i == 0),
+            ScreenResult::TimedOut => {
+                let v = preferred.unwrap_or(true);
+                self.show_msg(&get_timeout_text(Some(if v { YES } else { NO })), MsgLevel::Urgent);
+                ProceedScreenResult::TimedOut(v)
+            },
+            _ => ProceedScreenResult::Canceled,
+        })
+    }
+
+    fn show_question_screen(&mut self, question_entry: &QuestionEntry, question_count: usize, preferred: Option<QuestionAnswerInput>) -> Result<QuestionScreenResult> {
+        self.set_current(&question_entry.id, question_entry.pos, question_count);
+        let preferred_txt = preferred.as_ref().map(|p| p.to_string()).unwrap_or_default();
+        if self.fast_forward && preferred.is_some() {
+            if let Ok(ret) = validate_input(&preferred_txt, &question_entry.entry_type, question_entry.required) {
+                return Ok(QuestionScreenResult::Proceeded(ret));
+            }
+        }
+
+        let (input, hint) = match &question_entry.entry_type {
+            EntryType::Option(o) if ! o.options.is_empty() => {
+                let selected = o.options.iter().position(|v| *v == preferred_txt)
+                    .or(o.default_value.map(|d| d as usize).filter(|d| *d < o.options.len()))
+                    .unwrap_or(0);
+                (Input::Menu { items: o.options.clone(), selected }, "Choose with ↑/↓ and take it with ⏎".to_string())
+            },
+            EntryType::Bool(b) => {
+                let p = match preferred {
+                    Some(QuestionAnswerInput::Bool(Some(v))) => Some(v),
+                    _ => b.default_value,
+                };
+                let selected = if p == Some(false) { 1 } else { 0 };
+                (Input::Menu { items: vec![YES.to_string(), NO.to_string()], selected }, "Choose with ↑/↓ or y/n and take it with ⏎".to_string())
+            },
+            _ => {
+                let cursor = preferred_txt.chars().count();
+                (Input::Text { value: preferred_txt, cursor, masked: false }, get_valid_input_hint(question_entry))
+            },
+        };
+        let mut screen = Screen {
+            text: question_entry.query_text.clone(),
+            hint,
+            help: question_entry.help_text.clone(),
+            show_help: false,
+            input,
+            feedback: None,
+            countdown: None,
+        };
+        let entry_type = &question_entry.entry_type;
+        let required = question_entry.required;
+        let timeout = get_question_timeout(question_entry, self.timeout);
+        let result = self.run_screen(&mut screen, timeout, |v| {
+            validate_input(v, entry_type, required).err().map(|e| format!("Wrong input! {}", e))
+        })?;
+        Ok(match result {
+            ScreenResult::Canceled => QuestionScreenResult::Canceled,
+            ScreenResult::Text(v) => QuestionScreenResult::Proceeded(validate_input(&v, entry_type, required)?),
+            ScreenResult::Selected(i) => {
+                let answer = match entry_type {
+                    EntryType::Bool(_) => QuestionAnswerInput::Bool(Some(i == 0)),
+                    EntryType::Option(o) => QuestionAnswerInput::Option(o.options.get(i).cloned()),
+                    _ => QuestionAnswerInput::None,
+                };
+                QuestionScreenResult::Proceeded(answer)
+            },
+            ScreenResult::TimedOut => {
+                let answer = get_timeout_answer(question_entry);
+                self.show_msg(&get_timeout_text(answer.as_ref().map(|a| a.to_string()).as_deref()), MsgLevel::Urgent);
+                match answer {
+                    Some(a) => QuestionScreenResult::TimedOut(a),
+                    None => QuestionScreenResult::Canceled,
+                }
+            },
+        })
+    }
+}
+
+fn add_tree_items(block: &SubBlock, depth: usize, tree: &mut Vec<TreeItem>) {
+    for e in &block.entries {
+        match e {
+            QuestionaireEntry::Question(q) => tree.push(TreeItem { id: q.id.clone(), text: q.query_text.clone(), depth }),
+            QuestionaireEntry::RepeatedQuestion(q) => tree.push(TreeItem { id: q.id.clone(), text: q.query_text.clone(), depth }),
+            QuestionaireEntry::Block(b) => {
+                tree.push(TreeItem { id: b.id.clone(), text: b.start_text.clone(), depth });
+                add_tree_items(b, depth + 1, tree);
+            },
+        }
+    }
+}
+
+fn restore_terminal() {
+    TERMINAL_OWNED.store(false, Ordering::SeqCst);
+    let _ = terminal::disable_raw_mode();
+    let _ = execute!(io::stderr(), LeaveAlternateScreen);
+}
+
+/// Restores the terminal before a panic message is printed. The hook is installed
+/// once for all views and does nothing, if no view owns the terminal.
+fn install_panic_hook() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        let hook = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            if TERMINAL_OWNED.load(Ordering::SeqCst) {
+                restore_terminal();
+            }
+            hook(info);
+        }));
+    });
+}
+
+/// Reads the next terminal event, with a time it's None if nothing happened
+fn poll_event(wait: Option<Duration>) -> io::Result<Option<Event>> {
+    match wait {
+        Some(w) if ! event::poll(w)? => Ok(None),
+        _ => event::read().map(Some),
+    }
+}
+
+fn is_cancel(key: &KeyEvent) -> bool {
+    key.code == KeyCode::Esc
+        || (key.modifiers.contains(KeyModifiers::CONTROL) && matches!(key.code, KeyCode::Char('c') | KeyCode::Char('d')))
+}
+
+fn byte_pos(s: &str, char_pos: usize) -> usize {
+    s.char_indices().nth(char_pos).map(|(i, _)| i).unwrap_or(s.len())
+}
+
+fn render(f: &mut Frame, state: &ViewState, screen: &Screen) {
+    let [title_area, body, footer] = Layout::vertical([Constraint::Length(1), Constraint::Min(0), Constraint::Length(1)]).areas(f.area());
+    f.render_widget(Paragraph::new(state.title.as_str()).bold().underlined(), title_area);
+    let keys = match screen.input {
+        Input::Text { .. } => "⏎ take  F1 help  Esc cancel",
+        Input::Menu { .. } => "↑/↓ choose  ⏎ take  ?/F1 help  Esc cancel",
+    };
+    f.render_widget(Paragraph::new(keys).dim(), footer);
+
+    let [sidebar, main] = Layout::horizontal([Constraint::Percentage(35), Constraint::Min(0)]).areas(body);
+    render_sidebar(f, state, sidebar);
+    render_main(f, state, screen, main);
+}
+
+fn render_sidebar(f: &mut Frame, state: &ViewState, area: Rect) {
+    let [tree_area, gauge_area] = Layout::vertical([Constraint::Min(0), Constraint::Length(3)]).areas(area);
+    let mut list_state = ListState::default();
+    let items: Vec<ListItem> = state.tree.iter().enumerate().map(|(i, t)| {
+        let is_current = state.current.as_deref() == Some(t.id.as_str());
+        let marker = if is_current {
+            list_state.select(Some(i));
+            "▶"
+        } else if state.answered.contains(&t.id) {
+            "✔"
+        } else {
+            "·"
+        };
+        ListItem::new(format!("{}{} {}", "  ".repeat(t.depth), marker, t.text))
+    }).collect();
+    let list = List::new(items)
+        .block(Block::bordered().title("Questions"))
+        .highlight_style(Style::new().add_modifier(Modifier::BOLD));
+    f.render_stateful_widget(list, tree_area, &mut list_state);
+
+    let (ratio, label) = match &state.progress {
+        Some(p) => (p.ratio(), format!("{}/{} {}%", p.answered, p.total(), p.percent())),
+        None => {
+            let (pos, count) = state.position;
+            let ratio = if count == 0 { 0.0 } else { (pos as f64 / count as f64).min(1.0) };
+            (ratio, format!("{}/{}", pos, count))
+        },
+    };
+    let gauge = Gauge::default()
+        .block(Block::bordered().title("Progress"))
+        .gauge_style(Style::new().fg(Color::Green))
+        .ratio(ratio.min(1.0))
+        .label(label);
+    f.render_widget(gauge, gauge_area);
+}
+
+fn render_main(f: &mut Frame, state: &ViewState, screen: &Screen, area: Rect) {
+    let input_height = match &screen.input {
+        Input::Text { .. } => 3,
+        Input::Menu { items, .. } => items.len() as u16 + 2,
+    };
+    let message_height = if state.messages.is_empty() { 0 } else { state.messages.len() as u16 + 2 };
+    let [question_area, input_area, feedback_area, message_area] = Layout::vertical([
+        Constraint::Min(3),
+        Constraint::Length(input_height),
+        Constraint::Length(1),
+        Constraint::Length(message_height),
+    ]).areas(area);
+
+    let mut text = Text::default();
+    text.push_line(Line::from(screen.text.as_str()).bold());
+    text.push_line(Line::default());
+    text.push_line(Line::from(screen.hint.as_str()).dim());
+    if let Some(c) = screen.countdown {
+        text.push_line(Line::from(format!("The default is taken in {}s, any key stops the countdown", c)).yellow());
+    }
+    if let Some(h) = screen.help.as_ref() {
+        text.push_line(Line::default());
+        if screen.show_help {
+            text.push_line(Line::from(format!("Help: {}", h)).italic());
+        } else {
+            text.push_line(Line::from("Help is available").dim().italic());
+        }
+    }
+    f.render_widget(Paragraph::new(text).wrap(Wrap { trim: false }).block(Block::bordered()), question_area);
+
+    match &screen.input {
+        Input::Text { value, cursor, masked } => {
+            let shown = if *masked { "*".repeat(value.chars().count()) } else { value.clone() };
+            f.render_widget(Paragraph::new(shown).block(Block::bordered().title("Answer")), input_area);
+            let x = input_area.x + 1 + (*cursor as u16).min(input_area.width.saturating_sub(3));
+            f.set_cursor_position((x, input_area.y + 1));
+        },
+        Input::Menu { items, selected } => {
+            let list = List::new(items.iter().map(|i| ListItem::new(i.as_str())))
+                .block(Block::bordered().title("Answer"))
+                .highlight_symbol("> ")
+                .highlight_style(Style::new().fg(Color::Green).add_modifier(Modifier::BOLD));
+            let mut list_state = ListState::default().with_selected(Some(*selected));
+            f.render_stateful_widget(list, input_area, &mut list_state);
+        },
+    }
+
+    if let Some(fb) = screen.feedback.as_ref() {
+        f.render_widget(Paragraph::new(fb.as_str()).yellow(), feedback_area);
+    }
+    if ! state.messages.is_empty() {
+        let lines: Vec<Line> = state.messages.iter().map(|(m, urgent)| {
+            let s = Span::from(m.as_str()).italic();
+            Line::from(if *urgent { s.yellow() } else { s })
+        }).collect();
+        f.render_widget(Paragraph::new(lines).wrap(Wrap { trim: false }).block(Block::bordered().title("Messages")), message_area);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::controller::QuestionaireController;
+    use crate::persistence::NoPersistence;
+    use crate::test_helper;
+    use crate::{AnswerEntry, QuestionaireResult};
+    use ratatui::backend::TestBackend;
+    use std::collections::VecDeque;
+
+    /// After the last key the time runs out, if the view waits with a timeout
+    fn keys(s: &[KeyCode]) -> Box<dyn FnMut(Option<Duration>) -> io::Result<Option<Event>>> {
+        let mut events: VecDeque<Event> = s.iter().map(|c| Event::Key(KeyEvent::from(*c))).collect();
+        Box::new(move |wait| match (events.pop_front(), wait) {
+            (Some(e), _) => Ok(Some(e)),
+            (None, Some(w)) => {
+                std::thread::sleep(w);
+                Ok(None)
+            },
+            (None, None) => Err(io::Error::new(io::ErrorKind::UnexpectedEof, "no more events")),
+        })
+    }
+
+    fn text(s: &str) -> Vec<KeyCode> {
+        let mut ret: Vec<KeyCode> = s.chars().map(KeyCode::Char).collect();
+        ret.push(KeyCode::Enter);
+        ret
+    }
+
+    fn screen_text(view: &TuiView<TestBackend>) -> String {
+        let buffer = view.terminal.backend().buffer();
+        let mut ret = String::new();
+        for y in 0..buffer.area.height {
+            for x in 0..buffer.area.width {
+                ret.push_str(buffer[(x, y)].symbol());
+            }
+            ret.push('\n');
+        }
+        ret
+    }
+
+    #[test]
+    fn test_small_questionaire() {
+        let q = test_helper::create_small_questionaire();
+        let mut events: Vec<KeyCode> = vec![KeyCode::Enter];
+        // too short, the name is fixed with backspace and one more char
+        events.extend(text("H"));
+        events.extend([KeyCode::Backspace, KeyCode::Char('M'), KeyCode::Char('o')]);
+        events.extend(text("e"));
+        events.extend(text("1956-03-12"));
+        events.push(KeyCode::Enter);
+        let view = TuiView::with_terminal(Terminal::new(TestBackend::new(100, 30)).unwrap(), &q, keys(&events));
+        let mut c = QuestionaireController::new(&q, view, NoPersistence::new());
+        match c.run().unwrap() {
+            QuestionaireResult::Finished(a) => {
+                assert_eq!(2, a.iterations[0].len());
+                match &a.iterations[0][0] {
+                    AnswerEntry::Question(qa) => assert_eq!("Moe", qa.answer.to_string()),
+                    _ => panic!("unexpected answer"),
+                }
+            },
+            QuestionaireResult::Canceled => panic!("unexpected cancel"),
+        }
+    }
+
+    #[test]
+    fn test_render_and_cancel() {
+        let q = test_helper::create_complex_questionaire();
+        let mut events: Vec<KeyCode> = text("Homer");
+        events.push(KeyCode::Esc);
+        let mut view = TuiView::with_terminal(Terminal::new(TestBackend::new(120, 40)).unwrap(), &q, keys(&events));
+        view.print_title("Fun");
+        view.show_msg("Something happened", MsgLevel::Urgent);
+        let entries = &q.init_block.entries;
+        let (name, birth) = match (&entries[0], &entries[1]) {
+            (QuestionaireEntry::Question(n), QuestionaireEntry::Question(b)) => (n, b),
+            _ => panic!("unexpected questionaire"),
+        };
+        assert!(matches!(view.show_question_screen(name, 10, None).unwrap(), QuestionScreenResult::Proceeded(_)));
+        assert!(matches!(view.show_question_screen(birth, 10, None).unwrap(), QuestionScreenResult::Canceled));
+        let s = screen_text(&view);
+        assert!(s.starts_with("Fun"));
+        assert!(s.contains("✔ What's your name?"), "{}", s);
+        assert!(s.contains("▶ What's your date of birth?"), "{}", s);
+        assert!(s.contains("2/10"), "{}", s);
+        assert!(s.contains("Something happened"), "{}", s);
+    }
+
+    #[test]
+    fn test_menu() {
+        let q = test_helper::create_complex_questionaire();
+        let e = QuestionEntry::builder()
+            .id("color")
+            .query_text("Color?")
+            .entry_type(EntryType::Option(crate::OptionEntry::builder()
+                .options(vec!["red".to_string(), "green".to_string(), "blue".to_string()])
+                .default_value(1)
+                .build()))
+            .build();
+        let mut view = TuiView::with_terminal(Terminal::new(TestBackend::new(80, 30)).unwrap(), &q,
+            keys(&[KeyCode::Down, KeyCode::Enter, KeyCode::Up, KeyCode::Up, KeyCode::Enter, KeyCode::Char('b'), KeyCode::Enter]));
+        let mut answer = || match view.show_question_screen(&e, 1, None).unwrap() {
+            QuestionScreenResult::Proceeded(a) => a.to_string(),
+            _ => panic!("unexpected cancel"),
+        };
+        // the default is preselected, the menu wraps around and type ahead selects
+        assert_eq!("blue", answer());
+        assert_eq!("blue", answer());
+        assert_eq!("blue", answer());
+
+        // '?' toggles the help and 'h' selects by type ahead
+        let e = QuestionEntry::builder()
+            .id("eyes")
+            .query_text("Eye color?")
+            .help_text("The color of your eyes")
+            .entry_type(EntryType::Option(crate::OptionEntry::builder()
+                .options(vec!["blue".to_string(), "hazel".to_string()])
+                .build()))
+            .build();
+        let mut view = TuiView::with_terminal(Terminal::new(TestBackend::new(80, 30)).unwrap(), &q,
+            keys(&[KeyCode::Char('?'), KeyCode::Char('h'), KeyCode::Enter]));
+        assert!(matches!(view.show_question_screen(&e, 1, None).unwrap(), QuestionScreenResult::Proceeded(QuestionAnswerInput::Option(Some(o))) if o == "hazel"));
+        assert!(screen_text(&view).contains("Help: The color of your eyes"));
+    }
+
+    #[test]
+    fn test_timeout() {
+        let q = test_helper::create_complex_questionaire();
+        let e = QuestionEntry::builder()
+            .id("workers")
+            .query_text("Workers?")
+            .entry_type(EntryType::Int(crate::IntEntry::builder().default_value(3).build()))
+            .build();
+        let mut view = TuiView::with_terminal(Terminal::new(TestBackend::new(80, 30)).unwrap(), &q, keys(&[]));
+        view.set_timeout(Some(Duration::from_millis(50)));
+        assert!(matches!(view.show_question_screen(&e, 1, None).unwrap(), QuestionScreenResult::TimedOut(QuestionAnswerInput::Int(Some(3)))));
+        assert!(screen_text(&view).contains("The default is taken in 1s"));
+        assert!(matches!(view.show_proceed_screen("p", "Proceed?", None, 0, 0, Some(false)).unwrap(), ProceedScreenResult::TimedOut(false)));
+        // the first key stops the countdown
+        let mut view = TuiView::with_terminal(Terminal::new(TestBackend::new(80, 30)).unwrap(), &q, keys(&[KeyCode::Char('5'), KeyCode::Enter]));
+        view.set_timeout(Some(Duration::from_millis(50)));
+        assert!(matches!(view.show_question_screen(&e, 1, None).unwrap(), QuestionScreenResult::Proceeded(QuestionAnswerInput::Int(Some(5)))));
+    }
+}
@@ -1,10 +1,20 @@
 
 use anyhow::Result;
-use colored::Colorize;
-use std::io;
+use crate::text::{help_text, print_paged, render_text, terminal_width};
+use crate::theme::{Style, Theme};
+use crate::progress::Progress;
+use crate::accessible::{self, Reply};
+use std::collections::HashMap;
+use std::io::{self, BufReader, IsTerminal};
 use std::process;
-use rustyline::DefaultEditor;
+use std::time::Duration;
+use crate::input_helper::{InputHelper, BOOL_WORDS};
+use crate::menu::{self, MenuAction};
+use crate::timed_input::{self, TimedAction, TimedLines};
+use rustyline::config::{Behavior, Config};
+use rustyline::Editor;
 use rustyline::error::ReadlineError;
+use rustyline::history::{DefaultHistory, History};
 use crate::questionaire::{QuestionAnswerInput, QuestionEntry, StringEntry, 
     IntEntry, FloatEntry, BoolEntry, OptionEntry, EntryType};
 
@@ -12,14 +22,18 @@ use crate::questionaire::{QuestionAnswerInput, QuestionEntry, StringEntry,
 /// This is returned for normal question entries.
 pub enum QuestionScreenResult {
     Canceled,
-    Proceeded(QuestionAnswerInput)
+    Proceeded(QuestionAnswerInput),
+    /// nobody answered in time, e.g. the default value was taken
+    TimedOut(QuestionAnswerInput),
 }
 
 /// This type is returned for questions, where a decision how to
 /// proceed is needed
 pub enum ProceedScreenResult {
     Canceled,
-    Proceeded(bool)
+    Proceeded(bool),
+    /// nobody answered in time, e.g. the preferred or default decision was taken
+    TimedOut(bool),
 }
 
 pub enum MsgLevel {
@@ -33,127 +47,332 @@ pub trait QuestionaireView {
     fn show_proceed_screen<'a, T: Into<Option<&'a str>>>(&mut self, id: &str, text: &str, help_text: T, question_count: usize, current: usize, preferred: Option<bool>) -> Result<ProceedScreenResult>;
     fn show_question_screen(&mut self, question_entry: &QuestionEntry, question_count: usize, preferred: Option<QuestionAnswerInput>) -> Result<QuestionScreenResult>;
     fn show_msg(&mut self, _msg: &str, _level: MsgLevel) {}
+    /// Takes the given answers without asking, e.g. for imported data
+    fn set_fast_forward(&mut self, _v: bool) {}
+    /// Called by the controller before each screen
+    fn set_progress(&mut self, _progress: &Progress) {}
+    /// Time to answer a screen, questions can have their own. Views without
+    /// support for timeouts ignore it.
+    fn set_timeout(&mut self, _timeout: Option<Duration>) {}
+    /// Shows the text of an `InfoTxt` entry. Returns `Proceeded(true)` to go on,
+    /// the default shows it as message and doesn't wait.
+    fn show_info_screen(&mut self, question_entry: &QuestionEntry) -> Result<ProceedScreenResult> {
+        self.show_msg(&question_entry.query_text, MsgLevel::Normal);
+        Ok(ProceedScreenResult::Proceeded(true))
+    }
+    /// Asks for a secret, e.g. a passphrase, without echoing the input.
+    /// Returns None, if the view doesn't support it or the user canceled.
+    #[cfg(feature = "encryption")]
+    fn ask_secret(&mut self, _text: &str) -> Result<Option<String>> {
+        Ok(None)
+    }
+}
+
+/// Allows to pass a view by reference, to inspect it after the run
+impl<V: QuestionaireView> QuestionaireView for &mut V {
+    fn print_title<'a>(&mut self, title: &str) {
+        (**self).print_title(title)
+    }
+    fn show_proceed_screen<'a, T: Into<Option<&'a str>>>(&mut self, id: &str, text: &str, help_text: T, question_count: usize, current: usize, preferred: Option<bool>) -> Result<ProceedScreenResult> {
+        (**self).show_proceed_screen(id, text, help_text, question_count, current, preferred)
+    }
+    fn show_question_screen(&mut self, question_entry: &QuestionEntry, question_count: usize, preferred: Option<QuestionAnswerInput>) -> Result<QuestionScreenResult> {
+        (**self).show_question_screen(question_entry, question_count, preferred)
+    }
+    fn show_msg(&mut self, msg: &str, level: MsgLevel) {
+        (**self).show_msg(msg, level)
+    }
+    fn set_fast_forward(&mut self, v: bool) {
+        (**self).set_fast_forward(v)
+    }
+    fn set_progress(&mut self, progress: &Progress) {
+        (**self).set_progress(progress)
+    }
+    fn show_info_screen(&mut self, question_entry: &QuestionEntry) -> Result<ProceedScreenResult> {
+        (**self).show_info_screen(question_entry)
+    }
+    fn set_timeout(&mut self, timeout: Option<Duration>) {
+        (**self).set_timeout(timeout)
+    }
+    #[cfg(feature = "encryption")]
+    fn ask_secret(&mut self, text: &str) -> Result<Option<String>> {
+        (**self).ask_secret(text)
+    }
 }
 
 trait ViewHelper {
     fn get_input_hint(&self) -> String;
 }
 
+/// Hint for the expected input of a question
+pub(crate) fn get_valid_input_hint(question_entry: &QuestionEntry) -> String {
+    let mut s: String = match &question_entry.entry_type {
+        EntryType::String (s) => {
+            s.get_input_hint()
+        },
+        EntryType::Int(s) => {
+            s.get_input_hint()
+        },
+        EntryType::Float(s) => {
+            s.get_input_hint()
+        },
+        EntryType::Bool(s) => {
+            s.get_input_hint()
+        },
+        EntryType::Option(s) => {
+            s.get_input_hint()
+        },
+        _ => {
+            "".to_string()
+        },
+    };
+    if question_entry.help_text.is_some() {
+        s.push_str(" (for more info type 'h')");
+    };
+    s
+}
+
+/// Text of a question with its position, e.g. `[2/5] What's your name?`
+pub(crate) fn get_question_text(question_entry: &QuestionEntry, question_count: usize) -> String {
+    get_proceed_text(&question_entry.query_text, question_count, question_entry.pos)
+}
+
+/// Text of a proceed screen, with the position, if it's not 0
+pub(crate) fn get_proceed_text(text: &str, question_count: usize, current: usize) -> String {
+    if current != 0 {
+        format!("[{}/{}] {}", current, question_count, text)
+    } else {
+        text.to_string()
+    }
+}
+
+/// Position and count to show. With the progress of the controller the position
+/// is the current step on the path, screens without a position (0) keep it.
+pub(crate) fn get_position(progress: Option<&Progress>, question_count: usize, current: usize) -> (usize, usize) {
+    match progress {
+        Some(p) if current != 0 => (p.current(), p.total()),
+        _ => (current, question_count),
+    }
+}
+
+/// Progress bar above the text of a screen, only for themes with decorations
+pub(crate) fn get_progress_bar(progress: Option<&Progress>, current: usize, theme: &Theme) -> Option<String> {
+    match progress {
+        Some(p) if current != 0 && theme.decorations => Some(theme.hint.paint(&p.bar(20, theme.ascii))),
+        _ => None,
+    }
+}
+
+/// Timeout of a question, its own or the one of the runner
+pub(crate) fn get_question_timeout(question_entry: &QuestionEntry, timeout: Option<Duration>) -> Option<Duration> {
+    question_entry.timeout.map(Duration::from_secs).or(timeout)
+}
+
+/// Answer after a timeout: the default value, an optional question without
+/// default stays empty. None for required questions without default.
+pub(crate) fn get_timeout_answer(question_entry: &QuestionEntry) -> Option<QuestionAnswerInput> {
+    validate_input("", &question_entry.entry_type, question_entry.required).ok()
+}
+
+/// Message after a timeout, with the taken answer
+pub(crate) fn get_timeout_text(answer: Option<&str>) -> String {
+    match answer {
+        Some("") => "Timeout, the question stays unanswered".to_string(),
+        Some(a) => format!("Timeout, the default is taken: {}", a),
+        None => "Timeout, the question has no default".to_string(),
+    }
+}
+
+/// Hint for the expected input of a proceed screen
+/// Hint of yes/no screens, `enter` is the answer, that only ENTER takes
+pub(crate) fn get_proceed_input_hint(has_help: bool, enter: bool) -> &'static str {
+    match (has_help, enter) {
+        (true, true) => "type [y|n] or only ENTER for yes (for more info type 'h')",
+        (false, true) => "type [y|n] or only ENTER for yes",
+        (true, false) => "type [y|n] or only ENTER for no (for more info type 'h')",
+        (false, false) => "type [y|n] or only ENTER for no",
+    }
+}
+
+pub(crate) fn validate_input(str: &str, entry_type: &EntryType, required: bool) -> Result<QuestionAnswerInput> {
+    match entry_type {
+        EntryType::String (s) => {
+            s.validate(&str, required)
+        },
+        EntryType::Int(s) => {
+            s.validate(&str, required)
+        },
+        EntryType::Float(s) => {
+            s.validate(&str, required)
+        },
+        EntryType::Bool(s) => {
+            s.validate(&str, required)
+        },
+        EntryType::Option(s) => {
+            s.validate(&str, required)
+        },
+        _ => {
+            panic!("unexpected EntryType for question screen");
+        }
+    }
+}
+
 
 pub struct Ui {
     pub fast_forward: bool,
+    /// true, if selection menus can be used. Without a terminal the input is typed.
+    interactive: bool,
+    theme: Theme,
+    /// texts for screen readers, see `set_accessible`
+    accessible: bool,
+    /// one editor for the whole run
+    editor: Editor<InputHelper, DefaultHistory>,
+    /// given inputs per question id, e.g. for questions in looping blocks
+    history: HashMap<String, Vec<String>>,
+    progress: Option<Progress>,
+    /// time to answer, set by the runner
+    timeout: Option<Duration>,
+    /// CTRL-C and CTRL-D end the program, otherwise the screens return `Canceled`
+    exit_on_cancel: bool,
+    /// lines of stdin, if it isn't a terminal and a question had a timeout
+    stdin_lines: Option<TimedLines>,
 }
 
 
+/// Editor for the answers. With a terminal it reads from and draws on the terminal
+/// itself, so that stdout stays free for the output of the program.
+fn new_editor() -> rustyline::Result<Editor<InputHelper, DefaultHistory>> {
+    let behavior = if io::stdin().is_terminal() { Behavior::PreferTerm } else { Behavior::Stdio };
+    Editor::with_config(Config::builder().behavior(behavior).build())
+}
+
 impl Ui  {
     pub fn new() -> Result<Self> {
         Ok(Self {
             fast_forward: false,
+            interactive: io::stdin().is_terminal() && io::stderr().is_terminal(),
+            theme: Theme::from_env_for(io::stderr().is_terminal()),
+            accessible: false,
+            editor: new_editor()?,
+            history: HashMap::new(),
+            progress: None,
+            timeout: None,
+            exit_on_cancel: true,
+            stdin_lines: None,
         }) 
     }
-}
 
-
-impl QuestionaireView for Ui {
-    fn print_title<'a>(&mut self, title: &str) {
-        println!("\n________________________________________________________________________________");
-        println!("\n{}\n", title.bold().underline());
+    /// Replaces the theme, that is chosen from the environment
+    pub fn set_theme(&mut self, v: Theme) {
+        self.theme = v;
     }
 
-    fn show_msg<'a>(&mut self, msg: &str, level: MsgLevel) {
-        match level {
-            MsgLevel::Normal => {
-                println!("\n{}\n", msg.italic());
-            },
-            _ => {
-                println!("\n{}\n", msg.yellow().italic());
-            }
+    /// Switches the accessibility mode for screen readers on or off. Questions are
+    /// announced with number and type in words, without colours, glyphs and menus.
+    pub fn set_accessible(&mut self, v: bool) {
+        self.accessible = v;
+        if v {
+            self.theme = Theme::plain();
+            self.interactive = false;
         }
     }
 
-    fn show_proceed_screen<'a, T: Into<Option<&'a str>>>(&mut self, _id: &str, text: &str, help_text: T, question_count: usize, current: usize, preferred: Option<bool>) -> Result<ProceedScreenResult> {
+    /// Yes/no screen of the block decisions and proceed queries. Only ENTER takes
+    /// the last answer or the default. `position` is the current step and the count.
+    pub(crate) fn proceed_screen(&mut self, id: &str, text: &str, ht: Option<&str>, position: (usize, usize), preferred: Option<bool>, default: bool) -> Result<ProceedScreenResult> {
         const YES: &str = "yes";
         const NO: &str = "no";
 
-        fn get_valid_input_hint<'b>(has_help: bool) -> &'b str{
-            if has_help {
-                "type [y|n] or only ENTER for yes (for more info type 'h')"
-            } else {
-                "type [y|n] or only ENTER for yes"
-            }
+        fn print_wrong_input(theme: &Theme, has_help: bool, enter: bool) {
+            let msg = format!("Wrong input! {}", get_proceed_input_hint(has_help, enter));
+            eprintln!("\n{}\n", theme.error.paint(&theme.text(&msg)));
         }
 
-        fn print_wrong_input(has_help: bool) {
-            let msg = format!("Wrong input! {}", get_valid_input_hint(has_help));
-            println!("\n{}\n",msg.yellow());
-        }
-
-        fn print_result_and_return(input: bool) -> Result<ProceedScreenResult> {
+        fn print_result_and_return(theme: &Theme, input: bool) -> Result<ProceedScreenResult> {
             if input {
-                println!(">>> {}", format!("{}", YES).green());
+                eprintln!(">>> {}", theme.echo.paint(YES));
 
             } else {
-                println!(">>> {}", format!("{}", NO).green());
+                eprintln!(">>> {}", theme.echo.paint(NO));
             }
             Ok(ProceedScreenResult::Proceeded(input))
         }
 
-        let ht = help_text.into();
-        let text_to_display = if current != 0 {
-            format!("[{}/{}] {}", current, question_count, text)
+        let (current, question_count) = position;
+        let enter = preferred.unwrap_or(default);
+        if self.accessible {
+            return self.show_proceed_screen_accessible(id, text, ht, position, preferred, enter);
+        }
+        let text_to_display = get_proceed_text(text, question_count, current);
+        let hint = if self.interactive && self.timeout.is_none() {
+            get_menu_hint(ht.is_some())
         } else {
-            text.to_string()
+            get_proceed_input_hint(ht.is_some(), enter)
         };
-        println!("\n{}\n({})", text_to_display.bold(), get_valid_input_hint(ht.is_some()).dimmed());
+        let theme = self.theme.clone();
+        if let Some(bar) = get_progress_bar(self.progress.as_ref(), current, &theme) {
+            eprint!("\n{}", bar);
+        }
+        eprintln!("\n{}\n({})", render_text(&text_to_display, terminal_width(), &theme.question, &theme), theme.hint.paint(&theme.text(hint)));
         if let Some(a) = preferred {
-            let preferred_txt = format!("{}", a).yellow().italic();
-            println!("last input, take it with ⏎: {}", preferred_txt);
+            let preferred_txt = theme.preferred.paint(&format!("{}", a));
+            eprintln!("{}: {}", theme.text("last input, take it with ⏎"), preferred_txt);
         }
         if self.fast_forward  && preferred.is_some() {
             // fast forward mode
-            if let Some(a) = preferred {
-                return print_result_and_return(a);
-            } else {
-                return print_result_and_return(true);
+            return print_result_and_return(&theme, enter);
+        }
+        // the menu has no countdown, with a timeout the answer is typed
+        if self.interactive && self.timeout.is_none() {
+            let items = vec![YES.to_string(), NO.to_string()];
+            let preselected = if enter { 0 } else { 1 };
+            loop {
+                match menu::select(&items, preselected, &theme)? {
+                    MenuAction::Select(i) => return print_result_and_return(&theme, i == 0),
+                    MenuAction::Help => {
+                        if let Some(help_text_str) = ht {
+                            print_paged(&format!("\n{}\n", render_text(help_text_str, terminal_width(), &Style::new(), &theme)));
+                        }
+                    },
+                    MenuAction::Cancel => return self.proceed_canceled("CTRL-C"),
+                }
             }
         }
-        let mut rl = DefaultEditor::new()?;
+        let bool_words: Vec<String> = BOOL_WORDS.iter().map(|s| s.to_string()).collect();
+        let last_input = preferred.map(|a| if a { YES } else { NO }.to_string());
         loop {
-            let readline = rl.readline(">> ");
+            let helper = InputHelper::new(bool_words.clone(), last_input.clone()).hint_style(theme.hint);
+            let readline = self.read_line_timed(id, helper, "", self.timeout);
             match readline {
-                Ok(line) => {
-                    match line.to_lowercase().as_str().trim() {
-                        "y" | "yes" => return print_result_and_return(true),
-                        "n" | "no" => return print_result_and_return(false),
+                Ok(None) => return self.proceed_timed_out(enter),
+                Ok(Some(line)) => {
+                    let input = line.to_lowercase().trim().to_string();
+                    match input.as_str() {
+                        "y" | "yes" | "n" | "no" => self.remember_input(id, &input),
+                        _ => {},
+                    }
+                    match input.as_str() {
+                        "y" | "yes" => return print_result_and_return(&theme, true),
+                        "n" | "no" => return print_result_and_return(&theme, false),
                         "h" | "help" | "?" => {
                             if let Some(help_text_str) = ht {
-                                println!("\n{}\n", help_text_str);
-                                println!("\n{}\n",get_valid_input_hint(ht.is_some()).dimmed());
+                                print_paged(&format!("\n{}\n", render_text(help_text_str, terminal_width(), &Style::new(), &theme)));
+                                eprintln!("\n{}\n", theme.hint.paint(&theme.text(get_proceed_input_hint(ht.is_some(), enter))));
                             } else {
-                                print_wrong_input(ht.is_some());
+                                print_wrong_input(&theme, ht.is_some(), enter);
                             }
                         },
                         other => {
                             if other.len() == 0 {
-                                if let Some(a) = preferred {
-                                    return print_result_and_return(a);
-                                } else {
-                                    return print_result_and_return(true);
-                                }
+                                return print_result_and_return(&theme, enter);
                             } else {
-                                print_wrong_input(ht.is_some());
+                                print_wrong_input(&theme, ht.is_some(), enter);
                             }
                         }
                     }        
                 },
-                Err(ReadlineError::Interrupted) => {
-                    println!("CTRL-C");
-                    process::exit(1);
-                },
-                Err(ReadlineError::Eof) => {
-                    println!("CTRL-D");
-                    process::exit(1);
-                },
+                Err(ReadlineError::Interrupted) => return self.proceed_canceled("CTRL-C"),
+                Err(ReadlineError::Eof) => return self.proceed_canceled("CTRL-D"),
                 Err(err) => {
                     panic!("Error: {:?}", err);
                 }
@@ -161,130 +380,350 @@ impl QuestionaireView for Ui {
         }
     }
 
-    fn show_question_screen(&mut self, question_entry: &QuestionEntry, question_count: usize, preferred: Option<QuestionAnswerInput>) -> Result<QuestionScreenResult>{
-        fn get_valid_input_hint(question_entry: &QuestionEntry) -> String {
-            let mut s: String = match &question_entry.entry_type {
-                EntryType::String (s) => {
-                    s.get_input_hint()
-                },
-                EntryType::Int(s) => {
-                    s.get_input_hint()
-                },
-                EntryType::Float(s) => {
-                    s.get_input_hint()
-                },
-                EntryType::Bool(s) => {
-                    s.get_input_hint()
-                },
-                EntryType::Option(s) => {
-                    s.get_input_hint()
+    /// With false the screens return `Canceled` for CTRL-C and CTRL-D instead of
+    /// ending the program, e.g. for single prompts
+    pub(crate) fn set_exit_on_cancel(&mut self, v: bool) {
+        self.exit_on_cancel = v;
+    }
+
+    fn show_proceed_screen_accessible(&mut self, id: &str, text: &str, help_text: Option<&str>, position: (usize, usize), preferred: Option<bool>, enter: bool) -> Result<ProceedScreenResult> {
+        let (current, question_count) = position;
+        let yes_no = |v: bool| if v { "yes" } else { "no" };
+        eprintln!("\n{}", accessible::proceed_announcement(text, question_count, current));
+        if let Some(a) = preferred {
+            eprintln!("Last answer: {}.", yes_no(a));
+            if self.fast_forward {
+                eprintln!("Answer: {}", yes_no(a));
+                return Ok(ProceedScreenResult::Proceeded(a));
+            }
+        }
+        eprintln!("{}", accessible::proceed_hint(help_text.is_some(), Some(enter)));
+        if let Some(t) = self.timeout {
+            eprintln!("{}", accessible::timeout_hint(t));
+        }
+        let bool_words: Vec<String> = BOOL_WORDS.iter().map(|s| s.to_string()).collect();
+        loop {
+            match self.read_line_timed(id, InputHelper::new(bool_words.clone(), None), "", self.timeout) {
+                Ok(None) => return self.proceed_timed_out(enter),
+                Ok(Some(line)) => match accessible::proceed_reply(text, help_text, question_count, current, &line, Some(enter)) {
+                    Ok(v) => {
+                        eprintln!("Answer: {}", yes_no(v));
+                        return Ok(ProceedScreenResult::Proceeded(v));
+                    },
+                    Err(lines) => lines.iter().for_each(|l| eprintln!("{}", l)),
                 },
-                _ => {
-                    "".to_string()
+                Err(ReadlineError::Interrupted) | Err(ReadlineError::Eof) => return self.proceed_canceled("CTRL-C"),
+                Err(err) => return Err(err.into()),
+            }
+        }
+    }
+
+    fn show_question_screen_accessible(&mut self, question_entry: &QuestionEntry, question_count: usize, preferred: Option<QuestionAnswerInput>) -> Result<QuestionScreenResult> {
+        let preferred_txt = preferred.as_ref().map(|a| a.to_string()).unwrap_or_default();
+        if let Some(a) = preferred.filter(|_| self.fast_forward) {
+            if validate_input(&preferred_txt, &question_entry.entry_type, question_entry.required).is_ok() {
+                eprintln!("\n{}", accessible::question_announcement(question_entry, question_count));
+                eprintln!("{}", accessible::answer_text(&a));
+                return Ok(QuestionScreenResult::Proceeded(a));
+            }
+        }
+        eprintln!();
+        for l in accessible::question_intro(question_entry, question_count, &preferred_txt) {
+            eprintln!("{}", l);
+        }
+        let timeout = get_question_timeout(question_entry, self.timeout);
+        if let Some(t) = timeout {
+            eprintln!("{}", accessible::timeout_hint(t));
+        }
+        loop {
+            match self.read_line_timed(&question_entry.id, InputHelper::for_question(question_entry, None), "", timeout) {
+                Ok(None) => return self.question_timed_out(question_entry),
+                Ok(Some(line)) => match accessible::question_reply(question_entry, question_count, &line, &preferred_txt) {
+                    Reply::Answer(a) => {
+                        self.remember_input(&question_entry.id, line.trim());
+                        eprintln!("{}", accessible::answer_text(&a));
+                        return Ok(QuestionScreenResult::Proceeded(a));
+                    },
+                    Reply::Say(lines) => lines.iter().for_each(|l| eprintln!("{}", l)),
                 },
-            };
-            if question_entry.help_text.is_some() {
-                s.push_str(" (for more info type 'h')");
-            };
-            s
+                Err(ReadlineError::Interrupted) | Err(ReadlineError::Eof) => return self.question_canceled("CTRL-C"),
+                Err(err) => return Err(err.into()),
+            }
+        }
+    }
+
+    /// Reads one line with the history of the question. The initial text is
+    /// placed in the input buffer and can be edited.
+    fn read_line(&mut self, id: &str, helper: InputHelper, initial: &str) -> rustyline::Result<String> {
+        self.editor.set_helper(Some(helper));
+        self.editor.clear_history()?;
+        if let Some(entries) = self.history.get(id) {
+            for e in entries {
+                self.editor.history_mut().add(e)?;
+            }
         }
+        self.editor.readline_with_initial(">> ", (initial, ""))
+    }
 
-        fn print_result_and_return(ret: QuestionAnswerInput) -> Result<QuestionScreenResult> {
+    /// Reads a line like `read_line`. With a timeout and a terminal the remaining
+    /// seconds are shown and None is returned, if nobody answered in time.
+    fn read_line_timed(&mut self, id: &str, helper: InputHelper, initial: &str, timeout: Option<Duration>) -> rustyline::Result<Option<String>> {
+        match timeout {
+            Some(t) if io::stdin().is_terminal() && io::stderr().is_terminal() => {
+                match timed_input::read_line(initial, t, ! self.accessible, &self.theme)? {
+                    TimedAction::Line(l) => Ok(Some(l)),
+                    TimedAction::TimedOut => Ok(None),
+                    TimedAction::Cancel => Err(ReadlineError::Interrupted),
+                }
+            },
+            // e.g. piped input, after the first timeout all lines are read in the thread
+            _ if ! io::stdin().is_terminal() && (timeout.is_some() || self.stdin_lines.is_some()) => {
+                let lines = self.stdin_lines.get_or_insert_with(|| TimedLines::new(BufReader::new(io::stdin())));
+                match lines.next_line(timeout)? {
+                    None => Ok(None),
+                    Some(l) if l.is_empty() => Err(ReadlineError::Eof),
+                    Some(l) => Ok(Some(l.trim_end_matches(['\n', '\r']).to_string())),
+                }
+            },
+            _ => self.read_line(id, helper, initial).map(Some),
+        }
+    }
+
+    /// Takes the answer, that ENTER would take
+    fn proceed_timed_out(&self, v: bool) -> Result<ProceedScreenResult> {
+        eprintln!("{}", self.theme.error.paint(&get_timeout_text(Some(if v { "yes" } else { "no" }))));
+        Ok(ProceedScreenResult::TimedOut(v))
+    }
+
+    fn question_timed_out(&self, question_entry: &QuestionEntry) -> Result<QuestionScreenResult> {
+        let answer = get_timeout_answer(question_entry);
+        eprintln!("{}", self.theme.error.paint(&get_timeout_text(answer.as_ref().map(|a| a.to_string()).as_deref())));
+        Ok(match answer {
+            Some(a) => QuestionScreenResult::TimedOut(a),
+            None => QuestionScreenResult::Canceled,
+        })
+    }
+
+    /// Ends the program or returns `Canceled`, see `set_exit_on_cancel`
+    fn proceed_canceled(&self, key: &str) -> Result<ProceedScreenResult> {
+        eprintln!("{}", key);
+        if self.exit_on_cancel {
+            process::exit(1);
+        }
+        Ok(ProceedScreenResult::Canceled)
+    }
+
+    fn question_canceled(&self, key: &str) -> Result<QuestionScreenResult> {
+        eprintln!("{}", key);
+        if self.exit_on_cancel {
+            process::exit(1);
+        }
+        Ok(QuestionScreenResult::Canceled)
+    }
+
+    fn remember_input(&mut self, id: &str, input: &str) {
+        if ! input.is_empty() {
+            self.history.entry(id.to_string()).or_default().push(input.to_string());
+        }
+    }
+}
+
+fn get_menu_hint(has_help: bool) -> &'static str {
+    if has_help {
+        "choose with ↑/↓ and take it with ⏎, type to filter (for more info type '?')"
+    } else {
+        "choose with ↑/↓ and take it with ⏎, type to filter"
+    }
+}
+
+/// Text that is pre-filled in the input line: the last input or the default value
+fn get_initial_input(question_entry: &QuestionEntry, preferred_txt: &str) -> String {
+    if ! preferred_txt.is_empty() {
+        return preferred_txt.to_string();
+    }
+    let default_value = match &question_entry.entry_type {
+        EntryType::String(s) => s.default_value.clone(),
+        EntryType::Int(i) => i.default_value.map(|v| v.to_string()),
+        EntryType::Float(f) => f.default_value.map(|v| v.to_string()),
+        _ => None,
+    };
+    default_value.unwrap_or_default()
+}
+
+
+impl QuestionaireView for Ui {
+    fn print_title<'a>(&mut self, title: &str) {
+        if let Some(sep) = self.theme.separator() {
+            eprintln!("\n{}", sep);
+        }
+        eprintln!("\n{}\n", self.theme.title.paint(title));
+    }
+
+    fn set_fast_forward(&mut self, v: bool) {
+        self.fast_forward = v;
+    }
+
+    fn set_progress(&mut self, progress: &Progress) {
+        self.progress = Some(*progress);
+    }
+
+    fn set_timeout(&mut self, timeout: Option<Duration>) {
+        self.timeout = timeout;
+    }
+
+    fn show_msg<'a>(&mut self, msg: &str, level: MsgLevel) {
+        match level {
+            MsgLevel::Normal => {
+                eprintln!("\n{}\n", self.theme.message.paint(msg));
+            },
+            _ => {
+                eprintln!("\n{}\n", self.theme.error.paint(msg));
+            }
+        }
+    }
+
+    #[cfg(feature = "encryption")]
+    fn ask_secret(&mut self, text: &str) -> Result<Option<String>> {
+        eprintln!("\n{}", self.theme.question.paint(text));
+        match rpassword::prompt_password(">> ") {
+            Ok(s) => Ok(Some(s)),
+            Err(e) if e.kind() == io::ErrorKind::Interrupted || e.kind() == io::ErrorKind::UnexpectedEof => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn show_info_screen(&mut self, question_entry: &QuestionEntry) -> Result<ProceedScreenResult> {
+        let theme = self.theme.clone();
+        if self.accessible {
+            eprintln!("\n{}", accessible::info_announcement(&question_entry.query_text));
+        } else {
+            eprintln!("\n{}", render_text(&question_entry.query_text, terminal_width(), &Style::new(), &theme));
+        }
+        // without a person at the terminal there is nobody to acknowledge it
+        if self.fast_forward || ! io::stdin().is_terminal() {
+            return Ok(ProceedScreenResult::Proceeded(true));
+        }
+        let hint = if self.accessible { accessible::INFO_HINT } else { "continue with ⏎" };
+        eprintln!("({})", theme.hint.paint(&theme.text(hint)));
+        match self.read_line_timed(&question_entry.id, InputHelper::default(), "", self.timeout) {
+            Ok(_) => Ok(ProceedScreenResult::Proceeded(true)),
+            Err(ReadlineError::Interrupted) | Err(ReadlineError::Eof) => self.proceed_canceled("CTRL-C"),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn show_proceed_screen<'a, T: Into<Option<&'a str>>>(&mut self, id: &str, text: &str, help_text: T, question_count: usize, current: usize, preferred: Option<bool>) -> Result<ProceedScreenResult> {
+        let position = get_position(self.progress.as_ref(), question_count, current);
+        self.proceed_screen(id, text, help_text.into(), position, preferred, true)
+    }
+
+    fn show_question_screen(&mut self, question_entry: &QuestionEntry, question_count: usize, preferred: Option<QuestionAnswerInput>) -> Result<QuestionScreenResult>{
+        fn print_result_and_return(theme: &Theme, ret: QuestionAnswerInput) -> Result<QuestionScreenResult> {
             match &ret {
                 QuestionAnswerInput::String(x) => {
                     if let Some(v) =x {
-                        println!(">>> {}", format!("{}", v).green());
+                        eprintln!(">>> {}", theme.echo.paint(&format!("{}", v)));
                     }
                 },
                 QuestionAnswerInput::Int(x) => {
                     if let Some(v) =x {
-                        println!(">>> {}", format!("{}", v).green());
+                        eprintln!(">>> {}", theme.echo.paint(&format!("{}", v)));
                     }
                 },
                 QuestionAnswerInput::Float(x) => {
                     if let Some(v) =x {
-                        println!(">>> {}", format!("{}", v).green());
+                        eprintln!(">>> {}", theme.echo.paint(&format!("{}", v)));
                     }
                 },
                 QuestionAnswerInput::Bool(x) => {
                     if let Some(v) =x {
-                        println!(">>> {}", format!("{}", v).green());
+                        eprintln!(">>> {}", theme.echo.paint(&format!("{}", v)));
                     }
                 },
                 QuestionAnswerInput::Option(x) => {
                     if let Some(v) =x {
-                        println!(">>> {}", format!("{}", v).green());
+                        eprintln!(">>> {}", theme.echo.paint(&format!("{}", v)));
                     }
                 },
                 QuestionAnswerInput::None => {
-                        println!(">>> ???");
+                        eprintln!(">>> ???");
                 }
             }
             return Ok(QuestionScreenResult::Proceeded(ret));
         }
 
-        fn print_wrong_input(question_entry: &QuestionEntry) {
+        fn print_wrong_input(theme: &Theme, question_entry: &QuestionEntry) {
             let msg = format!("Wrong input! {}", get_valid_input_hint(question_entry));
-            println!("{}",msg.yellow());
+            eprintln!("{}", theme.error.paint(&theme.text(&msg)));
         }
 
-        fn print_help_text(question_entry: &QuestionEntry) {
-            let msg = if let Some(help_text) = &question_entry.help_text {
-                format!("Help: {}", help_text)
+        fn print_help_text(theme: &Theme, question_entry: &QuestionEntry) {
+            let msg = if let Some(h) = &question_entry.help_text {
+                help_text(h)
             } else {
                 "no help available".to_string()
             };
-            println!("\n{}\n",msg.italic());
+            print_paged(&format!("\n{}\n", render_text(&msg, terminal_width(), &theme.message, theme)));
         }
 
-        fn validate_input(str: &str, entry_type: &EntryType, required: bool) -> Result<QuestionAnswerInput> {
-            match entry_type {
-                EntryType::String (s) => {
-                    s.validate(&str, required)
-                },
-                EntryType::Int(s) => {
-                    s.validate(&str, required)
-                },
-                EntryType::Float(s) => {
-                    s.validate(&str, required)
-                },
-                EntryType::Bool(s) => {
-                    s.validate(&str, required)
-                },
-                EntryType::Option(s) => {
-                    s.validate(&str, required)
-                },
-                _ => {
-                    panic!("unexpected EntryType for question screen");
-                }
-            }
+        let (pos, question_count) = get_position(self.progress.as_ref(), question_count, question_entry.pos);
+        let question_entry = &QuestionEntry { pos, ..question_entry.clone() };
+        if self.accessible {
+            return self.show_question_screen_accessible(question_entry, question_count, preferred);
         }
-
-        let text_to_display = format!("[{}/{}] {}", question_entry.pos, question_count, question_entry.query_text);
-        println!("\n{}\n({})", text_to_display.bold(), get_valid_input_hint(&question_entry).dimmed());
+        let text_to_display = get_question_text(question_entry, question_count);
+        let timeout = get_question_timeout(question_entry, self.timeout);
+        let option_menu = match &question_entry.entry_type {
+            EntryType::Option(o) if self.interactive && timeout.is_none() && ! o.options.is_empty() => Some(o),
+            _ => None,
+        };
+        let hint = if option_menu.is_some() {
+            get_menu_hint(question_entry.help_text.is_some()).to_string()
+        } else {
+            get_valid_input_hint(&question_entry)
+        };
+        let theme = self.theme.clone();
+        if let Some(bar) = get_progress_bar(self.progress.as_ref(), pos, &theme) {
+            eprint!("\n{}", bar);
+        }
+        eprintln!("\n{}\n({})", render_text(&text_to_display, terminal_width(), &theme.question, &theme), theme.hint.paint(&theme.text(&hint)));
         let preferred_txt = if let Some(a) = preferred {
-            let s = format!("{}", a).yellow().italic();
+            let s = theme.preferred.paint(&format!("{}", a));
             if self.fast_forward {
                 // fast forward mode
                 let input_txt: String = a.to_string();
                 if let Ok(_ret) = validate_input(&input_txt, &question_entry.entry_type, question_entry.required) {
                     // validate was ok ...
-                    return print_result_and_return(a);
+                    return print_result_and_return(&theme, a);
                 }
             }
     
-            println!("last input, take it w/ ⏎: {}", s);
+            eprintln!("{}: {}", theme.text("last input, take it w/ ⏎"), s);
             format!("{}", a)
         } else {
             "".to_string()
         };
-        let mut rl = DefaultEditor::new()?;
+        if let Some(o) = option_menu {
+            let preselected = o.options.iter().position(|v| *v == preferred_txt)
+                .or(o.default_value.map(|d| d as usize))
+                .unwrap_or(0);
+            loop {
+                match menu::select(&o.options, preselected, &theme)? {
+                    MenuAction::Select(i) => return print_result_and_return(&theme, QuestionAnswerInput::Option(Some(o.options[i].clone()))),
+                    MenuAction::Help => print_help_text(&theme, question_entry),
+                    MenuAction::Cancel => return self.question_canceled("CTRL-C"),
+                }
+            }
+        }
+        let last_input = Some(preferred_txt.clone()).filter(|s| ! s.is_empty());
+        let initial = get_initial_input(question_entry, &preferred_txt);
         loop {
-            let readline = rl.readline(">> ");
-            let mut input = String::new(); 
+            let helper = InputHelper::for_question(question_entry, last_input.clone()).hint_style(theme.hint);
+            let readline = self.read_line_timed(&question_entry.id, helper, &initial, timeout);
 
             match readline {
-                Ok(line) => {
+                Ok(None) => return self.question_timed_out(question_entry),
+                Ok(Some(line)) => {
                     let mut str: String = line.trim().to_string();
 
                     if (str.len() == 0) && (preferred_txt.len() > 0) {
@@ -294,24 +733,19 @@ impl QuestionaireView for Ui {
                     };
         
                     if ((str == "h") || (str == "?")) && (question_entry.help_text.is_some()){
-                        print_help_text(&question_entry);
+                        print_help_text(&theme, &question_entry);
                     } else {                
                         if let Ok(ret) = validate_input(&str, &question_entry.entry_type, question_entry.required) {
                             // validate was ok ...
-                            return print_result_and_return(ret);
+                            self.remember_input(&question_entry.id, &str);
+                            return print_result_and_return(&theme, ret);
                         } else {
-                            print_wrong_input(question_entry);
+                            print_wrong_input(&theme, question_entry);
                         }
                     }        
                 },
-                Err(ReadlineError::Interrupted) => {
-                    println!("CTRL-C");
-                    process::exit(1);
-                },
-                Err(ReadlineError::Eof) => {
-                    println!("CTRL-D");
-                    process::exit(1);
-                },
+                Err(ReadlineError::Interrupted) => return self.question_canceled("CTRL-C"),
+                Err(ReadlineError::Eof) => return self.question_canceled("CTRL-D"),
                 Err(err) => {
                     panic!("Error: {:?}", err);
                 }
@@ -403,3 +837,33 @@ impl ViewHelper for OptionEntry {
         s
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_initial_input() {
+        let mut e = QuestionEntry::builder()
+            .id("workers")
+            .query_text("How many workers?")
+            .entry_type(EntryType::Int(IntEntry::builder().default_value(3).build()))
+            .build();
+        assert_eq!("3", get_initial_input(&e, ""));
+        assert_eq!("5", get_initial_input(&e, "5"));
+        e.entry_type = EntryType::String(StringEntry::builder().default_value("Springfield").build());
+        assert_eq!("Springfield", get_initial_input(&e, ""));
+        e.entry_type = EntryType::Option(OptionEntry::builder()
+            .options(vec!["red".to_string(), "green".to_string()])
+            .default_value(1)
+            .build());
+        assert_eq!("", get_initial_input(&e, ""));
+        assert_eq!("red", get_initial_input(&e, "red"));
+    }
+
+    #[test]
+    fn test_proceed_input_hint() {
+        assert_eq!("type [y|n] or only ENTER for yes", get_proceed_input_hint(false, true));
+        assert_eq!("type [y|n] or only ENTER for no (for more info type 'h')", get_proceed_input_hint(true, false));
+    }
+}
@@ -0,0 +1,187 @@
+//! `tquest ask` - asks a single question, e.g. in shell scripts
+//!
+use anyhow::Result;
+use clap::{Args, Subcommand};
+use tquest::{BoolEntry, EntryType, FloatEntry, IntEntry, OptionEntry, QuestionEntry, StringEntry, Ui};
+
+
+/// Exit code, if the question was canceled
+pub const EXIT_CANCELED: i32 = 1;
+
+#[derive(Args)]
+pub struct AskArgs {
+    #[command(subcommand)]
+    kind: AskKind,
+}
+
+#[derive(Args)]
+struct CommonArgs {
+    /// Text of the question
+    text: String,
+
+    /// Help text, that is shown for 'h'
+    #[arg(long)]
+    help_text: Option<String>,
+
+    /// Allows an empty answer without default, then nothing is printed
+    #[arg(long)]
+    optional: bool,
+
+    /// Texts for screen readers: the question with its type in words, short
+    /// hints and numbered options
+    #[arg(long)]
+    accessible: bool,
+}
+
+#[derive(Subcommand)]
+enum AskKind {
+    /// Asks for a string
+    String {
+        #[command(flatten)]
+        common: CommonArgs,
+        #[arg(long)]
+        default: Option<String>,
+        /// Regular expression, that the answer has to match
+        #[arg(long)]
+        regexp: Option<String>,
+        #[arg(long)]
+        min_length: Option<usize>,
+        #[arg(long)]
+        max_length: Option<usize>,
+    },
+    /// Asks for an integer
+    Int {
+        #[command(flatten)]
+        common: CommonArgs,
+        #[arg(long, allow_negative_numbers = true)]
+        default: Option<i32>,
+        #[arg(long, allow_negative_numbers = true)]
+        min: Option<i32>,
+        #[arg(long, allow_negative_numbers = true)]
+        max: Option<i32>,
+    },
+    /// Asks for a floating point number
+    Float {
+        #[command(flatten)]
+        common: CommonArgs,
+        #[arg(long, allow_negative_numbers = true)]
+        default: Option<f32>,
+        #[arg(long, allow_negative_numbers = true)]
+        min: Option<f32>,
+        #[arg(long, allow_negative_numbers = true)]
+        max: Option<f32>,
+    },
+    /// Asks a yes/no question, prints `true` or `false`
+    Bool {
+        #[command(flatten)]
+        common: CommonArgs,
+        #[arg(long)]
+        default: Option<bool>,
+    },
+    /// Asks to choose one of the options, prints the chosen option
+    Option {
+        #[command(flatten)]
+        common: CommonArgs,
+        /// Index of the default option
+        #[arg(long)]
+        default: Option<u32>,
+        /// Options to choose from
+        #[arg(required = true)]
+        options: Vec<String>,
+    },
+}
+
+pub fn run(args: &AskArgs) -> Result<i32> {
+    let entry = question_entry(&args.kind);
+    let mut ui = Ui::new()?;
+    ui.set_accessible(common_args(&args.kind).accessible);
+    match tquest::ask_in(&mut ui, &entry)? {
+        Some(answer) => {
+            let s = answer.to_string();
+            if ! s.is_empty() {
+                println!("{}", s);
+            }
+            Ok(0)
+        },
+        None => Ok(EXIT_CANCELED),
+    }
+}
+
+fn common_args(kind: &AskKind) -> &CommonArgs {
+    match kind {
+        AskKind::String { common, .. } | AskKind::Int { common, .. } | AskKind::Float { common, .. }
+            | AskKind::Bool { common, .. } | AskKind::Option { common, .. } => common,
+    }
+}
+
+fn question_entry(kind: &AskKind) -> QuestionEntry {
+    let (common, entry_type) = match kind {
+        AskKind::String { common, default, regexp, min_length, max_length } => (common, EntryType::String(StringEntry {
+            default_value: default.clone(),
+            regexp: regexp.clone(),
+            min_length: *min_length,
+            max_length: *max_length,
+        })),
+        AskKind::Int { common, default, min, max } => (common, EntryType::Int(IntEntry {
+            default_value: *default,
+            min: *min,
+            max: *max,
+        })),
+        AskKind::Float { common, default, min, max } => (common, EntryType::Float(FloatEntry {
+            default_value: *default,
+            min: *min,
+            max: *max,
+        })),
+        AskKind::Bool { common, default } => (common, EntryType::Bool(BoolEntry {
+            default_value: *default,
+        })),
+        AskKind::Option { common, default, options } => (common, EntryType::Option(OptionEntry {
+            default_value: *default,
+            options: options.clone(),
+        })),
+    };
+    QuestionEntry {
+        id: "ask".to_string(),
+        pos: 0,
+        required: ! common.optional,
+        query_text: common.text.clone(),
+        help_text: common.help_text.clone(),
+        entry_type,
+        timeout: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    #[derive(Parser)]
+    struct TestCli {
+        #[command(flatten)]
+        ask: AskArgs,
+    }
+
+    fn entry(args: &[&str]) -> QuestionEntry {
+        let cli = TestCli::try_parse_from(args).unwrap();
+        question_entry(&cli.ask.kind)
+    }
+
+    #[test]
+    fn test_question_entry() {
+        let e = entry(&["ask", "int", "--min", "1", "--max", "10", "--default", "3", "How many workers?"]);
+        assert_eq!("How many workers?", e.query_text);
+        assert!(e.required);
+        assert_eq!(EntryType::Int(IntEntry { default_value: Some(3), min: Some(1), max: Some(10) }), e.entry_type);
+
+        let e = entry(&["ask", "option", "--default", "1", "Color?", "red", "green"]);
+        assert_eq!(EntryType::Option(OptionEntry { default_value: Some(1), options: vec!["red".to_string(), "green".to_string()] }), e.entry_type);
+
+        let e = entry(&["ask", "string", "--optional", "--regexp", "^a", "Name?"]);
+        assert!(! e.required);
+        let cli = TestCli::try_parse_from(["ask", "bool", "--accessible", "Go?"]).unwrap();
+        assert!(common_args(&cli.ask.kind).accessible);
+        assert_eq!(Some("^a".to_string()), match e.entry_type { EntryType::String(s) => s.regexp, _ => None });
+        assert!(TestCli::try_parse_from(["ask", "int", "--min", "x", "Count?"]).is_err());
+    }
+}
@@ -0,0 +1,130 @@
+//! Loading of questionaire definitions
+//!
+use anyhow::{anyhow, Context, Result};
+use serde_json::Value;
+use tquest::{Questionaire, QuestionaireEntry, SubBlock};
+
+use std::fs;
+use std::path::Path;
+
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DefinitionFormat {
+    Json,
+    Yaml,
+}
+
+impl DefinitionFormat {
+    /// Guesses the format from the file extension, JSON is the default
+    pub fn from_path(path: &str) -> DefinitionFormat {
+        match Path::new(path).extension().and_then(|e| e.to_str()).map(|e| e.to_lowercase()).as_deref() {
+            Some("yaml") | Some("yml") => DefinitionFormat::Yaml,
+            _ => DefinitionFormat::Json,
+        }
+    }
+}
+
+/// Parses a JSON or YAML file into a generic value. In YAML enums are written as
+/// maps, like in JSON, and not with YAML tags.
+pub fn parse_value(content: &str, format: DefinitionFormat) -> Result<Value> {
+    Ok(match format {
+        DefinitionFormat::Json => serde_json::from_str(content)?,
+        DefinitionFormat::Yaml => serde_yaml::from_str(content)?,
+    })
+}
+
+pub fn load_value(path: &str) -> Result<Value> {
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("Can't read file '{}'", path))?;
+    parse_value(&content, DefinitionFormat::from_path(path))
+        .with_context(|| format!("Invalid file '{}'", path))
+}
+
+/// true, if the value looks like a questionaire definition
+pub fn is_definition(v: &Value) -> bool {
+    v.get("init_block").is_some()
+}
+
+pub fn parse_questionaire(content: &str, format: DefinitionFormat) -> Result<Questionaire> {
+    questionaire_from_value(parse_value(content, format)?)
+}
+
+pub fn questionaire_from_value(v: Value) -> Result<Questionaire> {
+    let q: Questionaire = serde_json::from_value(v)?;
+    if q.init_block.entries.is_empty() {
+        return Err(anyhow!("The questionaire has no questions"));
+    }
+    // the positions aren't needed in definition files, the builder calculates them
+    let mut builder = Questionaire::builder();
+    builder.id(&q.init_block.id)
+        .title(&q.title)
+        .start_text(&q.init_block.start_text)
+        .questions(q.init_block.entries.clone())
+        .templates(q.templates.clone());
+    if let Some(t) = q.init_block.end_text.as_ref() {
+        builder.end_text(t);
+    }
+    if let Some(t) = q.init_block.help_text.as_ref() {
+        builder.help_text(t);
+    }
+    Ok(builder.build())
+}
+
+pub fn load_questionaire(path: &str) -> Result<Questionaire> {
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("Can't read definition file '{}'", path))?;
+    parse_questionaire(&content, DefinitionFormat::from_path(path))
+        .with_context(|| format!("Invalid definition file '{}'", path))
+}
+
+/// Calls the function for all entries of the block and its sub blocks, in the order of
+/// the definition, together with the block that contains the entry.
+pub fn walk_entries<'a, F: FnMut(&'a QuestionaireEntry, &'a SubBlock)>(block: &'a SubBlock, f: &mut F) {
+    for e in &block.entries {
+        f(e, block);
+        if let QuestionaireEntry::Block(b) = e {
+            walk_entries(b, f);
+        }
+    }
+}
+
+/// Id of an entry
+pub fn entry_id(e: &QuestionaireEntry) -> &str {
+    match e {
+        QuestionaireEntry::Block(b) => &b.id,
+        QuestionaireEntry::Question(q) => &q.id,
+        QuestionaireEntry::RepeatedQuestion(q) => &q.id,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const YAML: &str = r#"
+title: Test
+init_block:
+  id: root
+  entries:
+    - Question:
+        id: id01
+        query_text: "What's your name?"
+        entry_type:
+          String:
+            min_length: 2
+"#;
+
+    #[test]
+    fn test_formats() {
+        assert_eq!(DefinitionFormat::Yaml, DefinitionFormat::from_path("q.YML"));
+        assert_eq!(DefinitionFormat::Json, DefinitionFormat::from_path("q.json"));
+        assert_eq!(DefinitionFormat::Json, DefinitionFormat::from_path("q"));
+        let q = parse_questionaire(YAML, DefinitionFormat::Yaml).unwrap();
+        assert_eq!("Test", q.title);
+        assert_eq!(1, q.init_block.entries.len());
+        assert_eq!(Some(1), q.pos_count);
+        let json = serde_json::to_string(&q).unwrap();
+        assert_eq!(q, parse_questionaire(&json, DefinitionFormat::Json).unwrap());
+        assert!(parse_questionaire("{}", DefinitionFormat::Json).is_err());
+    }
+}
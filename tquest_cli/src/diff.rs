@@ -0,0 +1,234 @@
+//! `tquest diff` - compares two definitions or two result files
+//!
+use crate::definition::{self, entry_id, walk_entries};
+use anyhow::{anyhow, Result};
+use clap::Args;
+use serde_json::Value;
+use tquest::{Questionaire, QuestionaireEntry};
+
+use std::collections::HashMap;
+
+
+#[derive(Args)]
+pub struct DiffArgs {
+    /// Old definition or result file, JSON or YAML
+    old: String,
+    /// New definition or result file, JSON or YAML
+    new: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Change {
+    Added(String, String),
+    Removed(String, String),
+    /// key, old and new value
+    Changed(String, String, String),
+}
+
+impl std::fmt::Display for Change {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Change::Added(k, v) => write!(f, "+ {}: {}", k, v),
+            Change::Removed(k, v) => write!(f, "- {}: {}", k, v),
+            Change::Changed(k, old, new) => write!(f, "~ {}: {} -> {}", k, old, new),
+        }
+    }
+}
+
+pub fn run(args: &DiffArgs) -> Result<i32> {
+    let old = definition::load_value(&args.old)?;
+    let new = definition::load_value(&args.new)?;
+    let changes = match (definition::is_definition(&old), definition::is_definition(&new)) {
+        (true, true) => diff_definitions(&definition::questionaire_from_value(old)?, &definition::questionaire_from_value(new)?),
+        (false, false) => diff_results(&old, &new),
+        _ => return Err(anyhow!("Can't compare a questionaire definition with a result file")),
+    };
+    for c in &changes {
+        println!("{}", c);
+    }
+    Ok(if changes.is_empty() { 0 } else { 1 })
+}
+
+/// Entry without its position and sub entries, together with the id of its block
+struct EntrySummary {
+    text: String,
+    parent: String,
+    fields: serde_json::Map<String, Value>,
+}
+
+fn summaries(q: &Questionaire) -> Vec<(String, EntrySummary)> {
+    let mut ret: Vec<(String, EntrySummary)> = Vec::new();
+    walk_entries(&q.init_block, &mut |e, parent| {
+        let (text, value) = match e {
+            QuestionaireEntry::Block(b) => (b.start_text.clone(), serde_json::to_value(b)),
+            QuestionaireEntry::Question(qe) => (qe.query_text.clone(), serde_json::to_value(qe)),
+            QuestionaireEntry::RepeatedQuestion(rq) => (rq.query_text.clone(), serde_json::to_value(rq)),
+        };
+        let mut fields = match value {
+            Ok(Value::Object(m)) => m,
+            _ => serde_json::Map::new(),
+        };
+        // positions change with every inserted question and the entries are compared on their own
+        fields.remove("pos");
+        fields.remove("entries");
+        ret.push((entry_id(e).to_string(), EntrySummary { text, parent: parent.id.clone(), fields }));
+    });
+    ret
+}
+
+/// Reports added, removed and changed entries. Changes name the changed fields.
+pub fn diff_definitions(old: &Questionaire, new: &Questionaire) -> Vec<Change> {
+    let old_entries = summaries(old);
+    let new_entries = summaries(new);
+    let old_by_id: HashMap<&str, &EntrySummary> = old_entries.iter().map(|(id, s)| (id.as_str(), s)).collect();
+    let new_by_id: HashMap<&str, &EntrySummary> = new_entries.iter().map(|(id, s)| (id.as_str(), s)).collect();
+
+    let mut ret: Vec<Change> = Vec::new();
+    for (id, s) in &old_entries {
+        if ! new_by_id.contains_key(id.as_str()) {
+            ret.push(Change::Removed(id.clone(), s.text.clone()));
+        }
+    }
+    for (id, s) in &new_entries {
+        match old_by_id.get(id.as_str()) {
+            None => ret.push(Change::Added(id.clone(), s.text.clone())),
+            Some(o) => {
+                let mut changed: Vec<String> = Vec::new();
+                if o.parent != s.parent {
+                    changed.push(format!("moved from block '{}' to '{}'", o.parent, s.parent));
+                }
+                let mut keys: Vec<&String> = o.fields.keys().chain(s.fields.keys()).collect();
+                keys.sort();
+                keys.dedup();
+                for k in keys {
+                    if o.fields.get(k) != s.fields.get(k) {
+                        changed.push(k.clone());
+                    }
+                }
+                if ! changed.is_empty() {
+                    ret.push(Change::Changed(id.clone(), o.text.clone(), format!("{} ({})", s.text, changed.join(", "))));
+                }
+            },
+        }
+    }
+    ret
+}
+
+fn flatten_value(v: &Value, path: &str, ret: &mut Vec<(String, String)>) {
+    match v {
+        Value::Object(m) => {
+            for (k, v) in m {
+                let p = if path.is_empty() { k.clone() } else { format!("{}.{}", path, k) };
+                flatten_value(v, &p, ret);
+            }
+        },
+        Value::Array(a) => {
+            for (i, v) in a.iter().enumerate() {
+                flatten_value(v, &format!("{}[{}]", path, i), ret);
+            }
+        },
+        Value::String(s) => ret.push((path.to_string(), s.clone())),
+        Value::Null => ret.push((path.to_string(), "-".to_string())),
+        other => ret.push((path.to_string(), other.to_string())),
+    }
+}
+
+/// Reports the answers, that changed between two results of `tquest run`
+pub fn diff_results(old: &Value, new: &Value) -> Vec<Change> {
+    let mut old_answers: Vec<(String, String)> = Vec::new();
+    flatten_value(old, "", &mut old_answers);
+    let mut new_answers: Vec<(String, String)> = Vec::new();
+    flatten_value(new, "", &mut new_answers);
+    let old_by_key: HashMap<&str, &str> = old_answers.iter().map(|(k, v)| (k.as_str(), v.as_str())).collect();
+    let new_by_key: HashMap<&str, &str> = new_answers.iter().map(|(k, v)| (k.as_str(), v.as_str())).collect();
+
+    let mut ret: Vec<Change> = Vec::new();
+    for (k, v) in &old_answers {
+        if ! new_by_key.contains_key(k.as_str()) {
+            ret.push(Change::Removed(k.clone(), v.clone()));
+        }
+    }
+    for (k, v) in &new_answers {
+        match old_by_key.get(k.as_str()) {
+            None => ret.push(Change::Added(k.clone(), v.clone())),
+            Some(o) if *o != v => ret.push(Change::Changed(k.clone(), o.to_string(), v.clone())),
+            _ => {},
+        }
+    }
+    ret
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::definition::{parse_questionaire, parse_value, DefinitionFormat};
+
+    const OLD: &str = r#"
+init_block:
+  id: root
+  entries:
+    - Question:
+        id: name
+        query_text: "What's your name?"
+    - Question:
+        id: birth
+        query_text: "What's your date of birth?"
+    - Block:
+        id: jobs
+        start_text: "Did you work?"
+        entries:
+          - Question:
+              id: company
+              query_text: "Company?"
+"#;
+
+    const NEW: &str = r#"
+init_block:
+  id: root
+  entries:
+    - Question:
+        id: hobby
+        query_text: "What's your hobby?"
+    - Question:
+        id: name
+        query_text: "What's your full name?"
+        required: true
+    - Question:
+        id: company
+        query_text: "Company?"
+    - Block:
+        id: jobs
+        start_text: "Did you work?"
+        entries:
+          - Question:
+              id: role
+              query_text: "Role?"
+"#;
+
+    #[test]
+    fn test_diff_definitions() {
+        let old = parse_questionaire(OLD, DefinitionFormat::Yaml).unwrap();
+        let new = parse_questionaire(NEW, DefinitionFormat::Yaml).unwrap();
+        let changes: Vec<String> = diff_definitions(&old, &new).iter().map(|c| c.to_string()).collect();
+        assert_eq!(vec![
+            "- birth: What's your date of birth?",
+            "+ hobby: What's your hobby?",
+            "~ name: What's your name? -> What's your full name? (query_text, required)",
+            "~ company: Company? -> Company? (moved from block 'jobs' to 'root')",
+            "+ role: Role?",
+        ], changes);
+        assert!(diff_definitions(&old, &old).is_empty());
+    }
+
+    #[test]
+    fn test_diff_results() {
+        let old = parse_value(r#"{"name": "Homer", "jobs": [{"company": "Power Plant"}], "age": 39}"#, DefinitionFormat::Json).unwrap();
+        let new = parse_value("name: Homer\njobs:\n- company: Kwik-E-Mart\n- company: Moe's\n", DefinitionFormat::Yaml).unwrap();
+        let changes: Vec<String> = diff_results(&old, &new).iter().map(|c| c.to_string()).collect();
+        assert_eq!(vec![
+            "- age: 39",
+            "~ jobs[0].company: Power Plant -> Kwik-E-Mart",
+            "+ jobs[1].company: Moe's",
+        ], changes);
+    }
+}
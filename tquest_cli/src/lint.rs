@@ -0,0 +1,285 @@
+//! `tquest lint` - reports problems of questionaire definitions
+//!
+use crate::definition::{self, entry_id, walk_entries};
+use anyhow::Result;
+use clap::Args;
+use regex::Regex;
+use tquest::{EntryType, Questionaire, QuestionaireEntry};
+
+use std::collections::HashMap;
+
+
+#[derive(Args)]
+pub struct LintArgs {
+    /// Questionaire definitions, JSON or YAML
+    #[arg(required = true)]
+    definitions: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LintIssue {
+    /// id of the entry with the problem
+    pub id: String,
+    pub message: String,
+}
+
+pub fn run(args: &LintArgs) -> Result<i32> {
+    let mut issue_count = 0;
+    for d in &args.definitions {
+        let issues = match definition::load_questionaire(d) {
+            Ok(q) => lint(&q),
+            Err(e) => vec![LintIssue { id: String::new(), message: format!("{:#}", e) }],
+        };
+        for i in &issues {
+            if i.id.is_empty() {
+                println!("{}: {}", d, i.message);
+            } else {
+                println!("{}: {}: {}", d, i.id, i.message);
+            }
+        }
+        issue_count += issues.len();
+    }
+    Ok(if issue_count == 0 { 0 } else { 1 })
+}
+
+pub fn lint(q: &Questionaire) -> Vec<LintIssue> {
+    let mut ret: Vec<LintIssue> = Vec::new();
+    let mut issue = |id: &str, message: String| ret.push(LintIssue { id: id.to_string(), message });
+
+    let mut ids: HashMap<&str, usize> = HashMap::new();
+    ids.insert(&q.init_block.id, 1);
+    walk_entries(&q.init_block, &mut |e, _| {
+        *ids.entry(entry_id(e)).or_default() += 1;
+    });
+
+    if q.init_block.id.is_empty() {
+        issue("", "The questionaire has no id".to_string());
+    }
+    for (id, m) in lint_proceed_queries(&q.init_block.entries) {
+        issue(id, m);
+    }
+    walk_entries(&q.init_block, &mut |e, _| {
+        let id = entry_id(e);
+        if id.is_empty() {
+            issue(id, format!("Entry without id: {}", entry_text(e)));
+        } else if ids.get(id).is_some_and(|c| *c > 1) {
+            issue(id, "Duplicate id".to_string());
+            // only reported once
+            ids.remove(id);
+        }
+        match e {
+            QuestionaireEntry::Block(b) => {
+                if b.entries.is_empty() {
+                    issue(id, "Block without entries".to_string());
+                }
+                if b.start_text.is_empty() {
+                    issue(id, "Block without start text".to_string());
+                }
+                for (id, m) in lint_proceed_queries(&b.entries) {
+                    issue(id, m);
+                }
+            },
+            QuestionaireEntry::Question(qe) => {
+                if qe.query_text.is_empty() {
+                    issue(id, "Question without text".to_string());
+                }
+                for m in lint_entry_type(&qe.entry_type) {
+                    issue(id, m);
+                }
+            },
+            QuestionaireEntry::RepeatedQuestion(rq) => {
+                if rq.query_text.is_empty() {
+                    issue(id, "Question without text".to_string());
+                }
+                if matches!(rq.entry_type, EntryType::InfoTxt | EntryType::ProceedQuery(_)) {
+                    issue(id, "Repeated question needs a type with an input".to_string());
+                }
+                if rq.max_count > 0 && rq.min_count > rq.max_count {
+                    issue(id, format!("min_count ({}) is bigger than max_count ({})", rq.min_count, rq.max_count));
+                }
+                for m in lint_entry_type(&rq.entry_type) {
+                    issue(id, m);
+                }
+            },
+        }
+    });
+    ret
+}
+
+/// A proceed query skips the given number of the following entries of its block
+fn lint_proceed_queries(entries: &[QuestionaireEntry]) -> Vec<(&str, String)> {
+    let mut ret = Vec::new();
+    for (i, e) in entries.iter().enumerate() {
+        if let QuestionaireEntry::Question(q) = e {
+            if let EntryType::ProceedQuery(n) = q.entry_type {
+                let following = entries.len() - i - 1;
+                if n == 0 {
+                    ret.push((q.id.as_str(), "Proceed query without entries to skip".to_string()));
+                } else if n as usize > following {
+                    ret.push((q.id.as_str(), format!("Proceed query skips {} entries, but only {} follow", n, following)));
+                }
+            }
+        }
+    }
+    ret
+}
+
+fn entry_text(e: &QuestionaireEntry) -> &str {
+    match e {
+        QuestionaireEntry::Block(b) => &b.start_text,
+        QuestionaireEntry::Question(q) => &q.query_text,
+        QuestionaireEntry::RepeatedQuestion(q) => &q.query_text,
+    }
+}
+
+fn lint_entry_type(t: &EntryType) -> Vec<String> {
+    fn check_range<T: PartialOrd + std::fmt::Display>(min: Option<T>, max: Option<T>, ret: &mut Vec<String>) {
+        if let (Some(min), Some(max)) = (min, max) {
+            if min > max {
+                ret.push(format!("min ({}) is bigger than max ({})", min, max));
+            }
+        }
+    }
+
+    let mut ret: Vec<String> = Vec::new();
+    // the defaults are checked with the same validation as the input
+    match t {
+        EntryType::String(s) => {
+            check_range(s.min_length, s.max_length, &mut ret);
+            let mut regexp_ok = true;
+            if let Some(r) = s.regexp.as_ref() {
+                if let Err(e) = Regex::new(r) {
+                    ret.push(format!("Invalid regexp '{}': {}", r, e));
+                    regexp_ok = false;
+                }
+            }
+            if let Some(d) = s.default_value.as_ref() {
+                if regexp_ok && s.validate(d, true).is_err() {
+                    ret.push(format!("Default value '{}' isn't a valid input", d));
+                }
+            }
+        },
+        EntryType::Int(i) => {
+            check_range(i.min, i.max, &mut ret);
+            if let Some(d) = i.default_value {
+                if i.validate(&d.to_string(), true).is_err() {
+                    ret.push(format!("Default value {} isn't a valid input", d));
+                }
+            }
+        },
+        EntryType::Float(f) => {
+            check_range(f.min, f.max, &mut ret);
+            if let Some(d) = f.default_value {
+                if f.validate(&d.to_string(), true).is_err() {
+                    ret.push(format!("Default value {} isn't a valid input", d));
+                }
+            }
+        },
+        EntryType::Option(o) => {
+            if o.options.is_empty() {
+                ret.push("Option entry without options".to_string());
+            }
+            if let Some(d) = o.default_value {
+                if d as usize >= o.options.len() {
+                    ret.push(format!("Default index {} is outside of the {} options", d, o.options.len()));
+                }
+            }
+            for (i, v) in o.options.iter().enumerate() {
+                if o.options[..i].contains(v) {
+                    ret.push(format!("Duplicate option '{}'", v));
+                }
+            }
+        },
+        EntryType::Bool(_) | EntryType::ProceedQuery(_) | EntryType::InfoTxt => {},
+    }
+    ret
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::definition::{parse_questionaire, DefinitionFormat};
+
+    #[test]
+    fn test_lint() {
+        let yaml = r#"
+title: Lint
+init_block:
+  id: root
+  entries:
+    - Question:
+        id: id01
+        query_text: Name
+        entry_type:
+          String:
+            regexp: "[a-z"
+    - Question:
+        id: id02
+        query_text: Age
+        entry_type:
+          Int:
+            min: 1
+            max: 10
+            default_value: 11
+    - Block:
+        id: id01
+        start_text: More?
+        entries:
+          - Question:
+              id: id03
+              query_text: Color
+              entry_type:
+                Option:
+                  options: [red, red]
+                  default_value: 2
+          - Question:
+              id: id04
+              query_text: Code
+              entry_type:
+                String:
+                  regexp: "^\\d+$"
+                  default_value: "42"
+"#;
+        let q = parse_questionaire(yaml, DefinitionFormat::Yaml).unwrap();
+        let issues = lint(&q);
+        let s: Vec<String> = issues.iter().map(|i| format!("{}: {}", i.id, i.message)).collect();
+        assert_eq!(5, s.len(), "{:?}", s);
+        assert!(s[0].starts_with("id01: Duplicate id"));
+        assert!(s[1].starts_with("id01: Invalid regexp '[a-z'"));
+        assert_eq!("id02: Default value 11 isn't a valid input", s[2]);
+        assert_eq!("id03: Default index 2 is outside of the 2 options", s[3]);
+        assert_eq!("id03: Duplicate option 'red'", s[4]);
+    }
+
+    #[test]
+    fn test_lint_proceed_query() {
+        let yaml = r#"
+init_block:
+  id: root
+  entries:
+    - Question:
+        id: info
+        query_text: Some words about the job
+        entry_type: InfoTxt
+    - Question:
+        id: job
+        query_text: Do you have a job?
+        entry_type:
+          ProceedQuery: 3
+    - Question:
+        id: company
+        query_text: Company
+    - RepeatedQuestion:
+        id: notes
+        query_text: Notes
+        entry_type: InfoTxt
+"#;
+        let q = parse_questionaire(yaml, DefinitionFormat::Yaml).unwrap();
+        let s: Vec<String> = lint(&q).iter().map(|i| format!("{}: {}", i.id, i.message)).collect();
+        assert_eq!(vec![
+            "job: Proceed query skips 3 entries, but only 2 follow".to_string(),
+            "notes: Repeated question needs a type with an input".to_string(),
+        ], s);
+    }
+
+}
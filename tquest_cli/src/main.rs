@@ -0,0 +1,50 @@
+//! `tquest` - runs questionaires from definition files
+//!
+mod ask;
+mod definition;
+mod diff;
+mod lint;
+mod render;
+mod run;
+
+use clap::{Parser, Subcommand};
+
+
+#[derive(Parser)]
+#[command(name = "tquest", version, about = "Runs questionaires, that are defined in JSON or YAML files")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Runs a questionaire definition and writes the answers
+    Run(run::RunArgs),
+    /// Asks a single question and prints the answer
+    Ask(ask::AskArgs),
+    /// Reports problems of questionaire definitions
+    Lint(lint::LintArgs),
+    /// Prints a definition as a printable form
+    Render(render::RenderArgs),
+    /// Compares two definitions or two result files
+    Diff(diff::DiffArgs),
+}
+
+fn main() {
+    let cli = Cli::parse();
+    let r = match cli.command {
+        Command::Run(args) => run::run(&args),
+        Command::Ask(args) => ask::run(&args),
+        Command::Lint(args) => lint::run(&args),
+        Command::Render(args) => render::run(&args),
+        Command::Diff(args) => diff::run(&args),
+    };
+    match r {
+        Ok(code) => std::process::exit(code),
+        Err(e) => {
+            eprintln!("error: {:#}", e);
+            std::process::exit(2);
+        },
+    }
+}
@@ -0,0 +1,218 @@
+//! `tquest render` - prints the question tree as a printable form
+//!
+use crate::definition;
+use anyhow::Result;
+use clap::Args;
+use tquest::{EntryType, Questionaire, QuestionaireEntry, SubBlock};
+
+use std::fs;
+
+
+const ANSWER_LINE: &str = "____________________________________________";
+const INDENT: &str = "    ";
+
+#[derive(Args)]
+pub struct RenderArgs {
+    /// Questionaire definition, JSON or YAML
+    definition: String,
+
+    /// File for the form, default is stdout
+    #[arg(short, long)]
+    output: Option<String>,
+}
+
+pub fn run(args: &RenderArgs) -> Result<i32> {
+    let q = definition::load_questionaire(&args.definition)?;
+    let text = render(&q);
+    if let Some(o) = args.output.as_ref() {
+        fs::write(o, text)?;
+    } else {
+        print!("{}", text);
+    }
+    Ok(0)
+}
+
+/// Renders the questionaire with the positions of the entries, e.g.
+/// ```text
+/// 1. What's your name? *
+///    (string, min-length: 2)
+///    ______________________
+/// ```
+pub fn render(q: &Questionaire) -> String {
+    let mut ret = String::new();
+    if ! q.title.is_empty() {
+        ret.push_str(&format!("{}\n{}\n\n", q.title, "=".repeat(q.title.chars().count())));
+    }
+    if ! q.init_block.start_text.is_empty() {
+        ret.push_str(&format!("{}\n\n", q.init_block.start_text));
+    }
+    render_entries(&q.init_block, "", &mut ret);
+    if let Some(t) = q.init_block.end_text.as_ref() {
+        ret.push_str(&format!("{}\n", t));
+    }
+    ret
+}
+
+fn render_entries(block: &SubBlock, indent: &str, ret: &mut String) {
+    for e in &block.entries {
+        match e {
+            QuestionaireEntry::Question(qe) => {
+                let required = if qe.required { " *" } else { "" };
+                let prefix = format!("{}. ", qe.pos);
+                ret.push_str(&format!("{}{}{}{}\n", indent, prefix, qe.query_text, required));
+                let inner = format!("{}{}", indent, " ".repeat(prefix.len()));
+                render_help(&qe.help_text, &inner, ret);
+                render_answer(&qe.entry_type, &inner, 1, ret);
+            },
+            QuestionaireEntry::RepeatedQuestion(rq) => {
+                let prefix = format!("{}. ", rq.pos);
+                ret.push_str(&format!("{}{}{}\n", indent, prefix, rq.query_text));
+                let inner = format!("{}{}", indent, " ".repeat(prefix.len()));
+                render_help(&rq.help_text, &inner, ret);
+                let count = match (rq.min_count, rq.max_count) {
+                    (min, 0) => min.max(3),
+                    (min, max) => min.max(max.min(5)),
+                };
+                let range = match (rq.min_count, rq.max_count) {
+                    (0, 0) => "any number of answers".to_string(),
+                    (min, 0) => format!("at least {} answers", min),
+                    (min, max) => format!("{} to {} answers", min, max),
+                };
+                ret.push_str(&format!("{}({})\n", inner, range));
+                render_answer(&rq.entry_type, &inner, count, ret);
+            },
+            QuestionaireEntry::Block(b) => {
+                let prefix = match b.pos {
+                    Some(p) => format!("{}. ", p),
+                    None => String::new(),
+                };
+                ret.push_str(&format!("{}{}{}  [ ] yes  [ ] no\n", indent, prefix, b.start_text));
+                let inner = format!("{}{}", indent, INDENT);
+                render_help(&b.help_text, &inner, ret);
+                if b.loop_over_entries {
+                    ret.push_str(&format!("{}(answer the following for every entry)\n", inner));
+                }
+                ret.push('\n');
+                render_entries(b, &inner, ret);
+                if let Some(t) = b.end_text.as_ref() {
+                    ret.push_str(&format!("{}{}  [ ] yes  [ ] no\n\n", inner, t));
+                }
+            },
+        }
+    }
+}
+
+fn render_help(help: &Option<String>, indent: &str, ret: &mut String) {
+    if let Some(h) = help.as_ref() {
+        ret.push_str(&format!("{}{}\n", indent, h));
+    }
+}
+
+fn render_answer(t: &EntryType, indent: &str, count: usize, ret: &mut String) {
+    let hint = match t {
+        EntryType::String(s) => {
+            let mut h = "string".to_string();
+            if let Some(min) = s.min_length {
+                h.push_str(&format!(", min-length: {}", min));
+            }
+            if let Some(max) = s.max_length {
+                h.push_str(&format!(", max-length: {}", max));
+            }
+            if let Some(r) = s.regexp.as_ref() {
+                h.push_str(&format!(", regexp: {}", r));
+            }
+            if let Some(d) = s.default_value.as_ref() {
+                h.push_str(&format!(", default: {}", d));
+            }
+            Some(h)
+        },
+        EntryType::Int(i) => Some(number_hint("integer", i.min, i.max, i.default_value)),
+        EntryType::Float(f) => Some(number_hint("floating point number", f.min, f.max, f.default_value)),
+        EntryType::Bool(b) => {
+            for _ in 0..count {
+                ret.push_str(&format!("{}[ ] yes  [ ] no\n", indent));
+            }
+            if let Some(d) = b.default_value {
+                ret.push_str(&format!("{}(default: {})\n", indent, if d { "yes" } else { "no" }));
+            }
+            None
+        },
+        EntryType::Option(o) => {
+            for (i, v) in o.options.iter().enumerate() {
+                let default = if o.default_value == Some(i as u32) { " (default)" } else { "" };
+                ret.push_str(&format!("{}( ) {}{}\n", indent, v, default));
+            }
+            None
+        },
+        EntryType::ProceedQuery(_) | EntryType::InfoTxt => None,
+    };
+    if let Some(h) = hint {
+        ret.push_str(&format!("{}({})\n", indent, h));
+        for _ in 0..count {
+            ret.push_str(&format!("{}{}\n", indent, ANSWER_LINE));
+        }
+    }
+    ret.push('\n');
+}
+
+fn number_hint<T: std::fmt::Display>(name: &str, min: Option<T>, max: Option<T>, default: Option<T>) -> String {
+    let mut h = name.to_string();
+    if let Some(min) = min {
+        h.push_str(&format!(", min: {}", min));
+    }
+    if let Some(max) = max {
+        h.push_str(&format!(", max: {}", max));
+    }
+    if let Some(d) = default {
+        h.push_str(&format!(", default: {}", d));
+    }
+    h
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::definition::{parse_questionaire, DefinitionFormat};
+
+    #[test]
+    fn test_render() {
+        let yaml = r#"
+title: Jobs
+init_block:
+  id: root
+  entries:
+    - Question:
+        id: name
+        query_text: "What's your name?"
+        required: true
+        entry_type:
+          String:
+            min_length: 2
+    - Block:
+        id: jobs
+        start_text: "Did you work in a job?"
+        loop_over_entries: true
+        entries:
+          - Question:
+              id: kind
+              query_text: "Kind of job?"
+              entry_type:
+                Option:
+                  options: [Employed, Freelancer]
+                  default_value: 0
+"#;
+        let q = parse_questionaire(yaml, DefinitionFormat::Yaml).unwrap();
+        let expected = concat!(
+            "Jobs\n====\n\n",
+            "1. What's your name? *\n",
+            "   (string, min-length: 2)\n",
+            "   ____________________________________________\n\n",
+            "2. Did you work in a job?  [ ] yes  [ ] no\n",
+            "    (answer the following for every entry)\n\n",
+            "    3. Kind of job?\n",
+            "       ( ) Employed (default)\n",
+            "       ( ) Freelancer\n\n",
+        );
+        assert_eq!(expected, render(&q));
+    }
+}
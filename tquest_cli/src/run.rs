@@ -0,0 +1,172 @@
+//! `tquest run` - runs a questionaire and writes the answers
+//!
+use crate::definition;
+use anyhow::Result;
+use clap::{Args, ValueEnum};
+use tquest::{answers_to_json, read_journal, BlockAnswer, EnvExporter, QuestionaireResult, QuestionaireRunner, Theme};
+
+use std::fs;
+
+
+/// Exit code, if the questionaire was canceled
+pub const EXIT_CANCELED: i32 = 1;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, ValueEnum)]
+pub enum OutputFormat {
+    #[default]
+    Json,
+    Yaml,
+    /// `export KEY='value'` lines for shell scripts
+    Env,
+}
+
+#[derive(Args)]
+pub struct RunArgs {
+    /// Questionaire definition, JSON or YAML (`.yaml`, `.yml`)
+    definition: String,
+
+    /// Title that is printed at the start, default is the title of the questionaire
+    #[arg(long)]
+    title: Option<String>,
+
+    /// File to store the answers while the questionaire runs
+    #[arg(short, long)]
+    persistence_file: Option<String>,
+
+    /// Persistence file of an earlier run, with answers to import
+    #[arg(short, long)]
+    import: Option<String>,
+
+    /// Takes the imported answers without asking for them again
+    #[arg(short, long)]
+    autofill: bool,
+
+    /// Format of the answers
+    #[arg(short, long, value_enum, default_value_t)]
+    format: OutputFormat,
+
+    /// File for the answers, default is stdout
+    #[arg(short, long)]
+    output: Option<String>,
+
+    /// No colours and only ASCII characters, e.g. for logged runs
+    #[arg(long)]
+    plain: bool,
+
+    /// Texts for screen readers: questions with number and type in words,
+    /// short hints and numbered options
+    #[arg(long)]
+    accessible: bool,
+
+    /// Seconds to answer a question, then its default is taken
+    #[arg(long)]
+    timeout: Option<u64>,
+
+    /// Runs the questionaire in a full-screen terminal UI
+    #[cfg(feature = "tui")]
+    #[arg(long)]
+    tui: bool,
+}
+
+pub fn run(args: &RunArgs) -> Result<i32> {
+    let questionaire = definition::load_questionaire(&args.definition)?;
+    let title = args.title.clone().unwrap_or(questionaire.title.clone());
+    let imported_data = if let Some(f) = args.import.as_ref() {
+        Some(read_journal(f)?)
+    } else {
+        None
+    };
+
+    let mut builder = QuestionaireRunner::builder();
+    builder.title(&title)
+        .imported_data(imported_data)
+        .autofil(args.autofill);
+    #[cfg(feature = "tui")]
+    builder.tui(args.tui);
+    builder.accessible(args.accessible);
+    if args.plain {
+        builder.theme(Theme::plain());
+    }
+    if let Some(t) = args.timeout {
+        builder.timeout(t);
+    }
+    if let Some(pf) = args.persistence_file.as_ref() {
+        builder.persistence_file(pf);
+    }
+    let answer = match builder.build(questionaire)?.run()? {
+        QuestionaireResult::Finished(a) => a,
+        QuestionaireResult::Canceled => {
+            eprintln!("The questionaire was canceled");
+            return Ok(EXIT_CANCELED);
+        },
+    };
+
+    let text = format_answers(&answer, args.format)?;
+    if let Some(o) = args.output.as_ref() {
+        fs::write(o, text)?;
+    } else {
+        print!("{}", text);
+    }
+    Ok(0)
+}
+
+pub fn format_answers(answer: &BlockAnswer, format: OutputFormat) -> Result<String> {
+    Ok(match format {
+        OutputFormat::Json => {
+            let mut s = serde_json::to_string_pretty(&answers_to_json(answer))?;
+            s.push('\n');
+            s
+        },
+        OutputFormat::Yaml => serde_yaml::to_string(&answers_to_json(answer))?,
+        OutputFormat::Env => EnvExporter::new().export(answer),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tquest::{AnswerEntry, QuestionAnswer, QuestionAnswerInput};
+
+    fn answer() -> BlockAnswer {
+        let q = |id: &str, v: &str| AnswerEntry::Question(QuestionAnswer {
+            id: id.to_string(),
+            answer: QuestionAnswerInput::String(Some(v.to_string())),
+            timed_out: false,
+        });
+        BlockAnswer {
+            id: "root".to_string(),
+            iterations: vec![vec![
+                q("name", "Homer"),
+                AnswerEntry::Block(BlockAnswer {
+                    id: "jobs".to_string(),
+                    iterations: vec![vec![q("company", "Power Plant")]],
+                }),
+            ]],
+        }
+    }
+
+    #[test]
+    fn test_format_answers() {
+        let a = answer();
+        assert_eq!("{\n  \"jobs\": [\n    {\n      \"company\": \"Power Plant\"\n    }\n  ],\n  \"name\": \"Homer\"\n}\n",
+            format_answers(&a, OutputFormat::Json).unwrap());
+        assert_eq!("jobs:\n- company: Power Plant\nname: Homer\n", format_answers(&a, OutputFormat::Yaml).unwrap());
+        assert_eq!("export NAME='Homer'\nexport JOBS_COUNT='1'\nexport JOBS_0_COMPANY='Power Plant'\n",
+            format_answers(&a, OutputFormat::Env).unwrap());
+    }
+
+    #[test]
+    fn test_format_timed_out_answers() {
+        let a = BlockAnswer {
+            id: "root".to_string(),
+            iterations: vec![vec![AnswerEntry::Question(QuestionAnswer {
+                id: "name".to_string(),
+                answer: QuestionAnswerInput::String(Some("Homer".to_string())),
+                timed_out: true,
+            })]],
+        };
+        assert_eq!("{\n  \"name\": \"Homer\",\n  \"name_timed_out\": true\n}\n", format_answers(&a, OutputFormat::Json).unwrap());
+        assert_eq!("name: Homer\nname_timed_out: true\n", format_answers(&a, OutputFormat::Yaml).unwrap());
+        assert_eq!("export NAME='Homer'\nexport NAME_TIMED_OUT='1'\n", format_answers(&a, OutputFormat::Env).unwrap());
+    }
+}